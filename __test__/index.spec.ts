@@ -1,6 +1,48 @@
+import { spawnSync } from 'node:child_process'
+import { fileURLToPath } from 'node:url'
+import { Worker } from 'node:worker_threads'
+
 import test from 'ava'
 
-import { NwPathMonitor } from '../index.js'
+import {
+  BACKEND_TIMEOUT,
+  ChangeFilter,
+  ConnectionCostFlag,
+  decodeConnectivityFlags,
+  getGlobalNetworkInfo,
+  getWifiInfo,
+  HostReachability,
+  InternetMonitor,
+  mergeNetworkInfo,
+  meteredStatusFromCostFlags,
+  NwCompositePathMonitor,
+  NwEndpointPathMonitor,
+  NwInterfaceType,
+  NwPathMonitor,
+  networkStatusCode,
+  networkStatusFromCode,
+  watchInterfaceAvailability,
+  type NetworkInfo,
+} from '../index.js'
+
+const bindingPath = fileURLToPath(new URL('../index.js', import.meta.url))
+
+const internetMonitorTest = process.platform === 'darwin' ? test.skip : test
+const nwPathMonitorTest = process.platform === 'darwin' ? test : test.skip
+// only the builds with the `mock` feature can push a state,
+// InternetMonitor is not exported on macOS without the `mock` feature
+const mockTest = InternetMonitor && 'pushState' in InternetMonitor.prototype ? test : test.skip
+
+type MockMonitor = InternetMonitor & { pushState(info: NetworkInfo): void }
+
+// a monitor of the mock build and its first state, offline until a state is pushed
+const mockMonitor = (monitor: InternetMonitor = new InternetMonitor()) => {
+  const mock = monitor as MockMonitor
+  return { monitor: mock, offline: mock.current() }
+}
+
+// the threadsafe function calls of the callbacks land on the next ticks
+const flush = () => new Promise((resolve) => setTimeout(resolve, 50))
 
 test('should not throw while listening', (t) => {
   t.notThrows(() => {
@@ -11,3 +53,788 @@ test('should not throw while listening', (t) => {
     })
   })
 })
+
+test('getGlobalNetworkInfo can be called repeatedly', (t) => {
+  for (let i = 0; i < 10; i++) {
+    t.is(typeof getGlobalNetworkInfo().status, 'string')
+  }
+})
+
+test('NetworkStatus codes round trip', (t) => {
+  const statuses = ['Invalid', 'Satisfied', 'Unsatisfied', 'Satisfiable', 'Unknown'] as const
+  statuses.forEach((status, code) => {
+    t.is(networkStatusCode(status), code)
+    t.is(networkStatusFromCode(code), status)
+  })
+  t.throws(() => networkStatusFromCode(5), { message: /not a NetworkStatus code/ })
+})
+
+test('meteredStatusFromCostFlags maps the NLM_CONNECTION_COST bits', (t) => {
+  const cases: [number, boolean, boolean][] = [
+    // [costFlags, isExpensive, isLowDataMode]
+    [0, false, false],
+    [ConnectionCostFlag.Unrestricted, false, false],
+    [ConnectionCostFlag.Fixed, true, false],
+    [ConnectionCostFlag.Variable, true, false],
+    [ConnectionCostFlag.Unrestricted | ConnectionCostFlag.Roaming, true, false],
+    [ConnectionCostFlag.Fixed | ConnectionCostFlag.OverDataLimit, true, true],
+    [ConnectionCostFlag.Fixed | ConnectionCostFlag.ApproachingDataLimit, true, true],
+    [ConnectionCostFlag.Unrestricted | ConnectionCostFlag.Congested, false, true],
+    [ConnectionCostFlag.Unrestricted | ConnectionCostFlag.ApproachingDataLimit, false, true],
+  ]
+  for (const [costFlags, isExpensive, isLowDataMode] of cases) {
+    t.deepEqual(meteredStatusFromCostFlags(costFlags), { isExpensive, isLowDataMode }, `costFlags ${costFlags}`)
+  }
+})
+
+test('decodeConnectivityFlags names the NLM_CONNECTIVITY bits', (t) => {
+  // NLM_CONNECTIVITY_IPV4_LOCALNETWORK | NLM_CONNECTIVITY_IPV6_NOTRAFFIC, an isolated LAN
+  t.deepEqual(decodeConnectivityFlags(0x20 | 0x2), {
+    ipv4NoTraffic: false,
+    ipv6NoTraffic: true,
+    hasIpv4Subnet: false,
+    hasIpv4LocalNetwork: true,
+    hasIpv4Internet: false,
+    hasIpv6Subnet: false,
+    hasIpv6LocalNetwork: false,
+    hasIpv6Internet: false,
+  })
+  t.false(Object.values(decodeConnectivityFlags(0)).some(Boolean))
+  if (process.platform !== 'win32') {
+    const { connectivityFlags, ipv4Connectivity, ipv6Connectivity } = getGlobalNetworkInfo()
+    t.is(connectivityFlags, 0)
+    t.deepEqual([ipv4Connectivity, ipv6Connectivity], ['None', 'None'])
+  }
+})
+
+test('mergeNetworkInfo keeps the best path and unions its capabilities', (t) => {
+  const base = getGlobalNetworkInfo()
+  const path = (overrides: Partial<NetworkInfo>): NetworkInfo => ({
+    ...base,
+    hasIpv4: false,
+    hasIpv6: false,
+    usesWifi: false,
+    usesCellular: false,
+    gateways: [],
+    ...overrides,
+  })
+  const wifi = path({ status: 'Satisfied', hasIpv4: true, usesWifi: true, gateways: ['192.168.1.1'] })
+  const cellular = path({ status: 'Satisfied', hasIpv6: true, isExpensive: true, usesCellular: true })
+  const offline = path({ status: 'Unsatisfied', hasIpv4: true, hasIpv6: true })
+
+  const merged = mergeNetworkInfo([offline, wifi, cellular])!
+  t.is(merged.status, 'Satisfied')
+  // the first of the equally good paths is preferred
+  t.false(merged.isExpensive)
+  t.true(merged.usesWifi && merged.usesCellular)
+  t.is(merged.stack, 'DualStack')
+  t.deepEqual(merged.gateways, ['192.168.1.1'])
+  t.is(mergeNetworkInfo([offline, path({ status: 'Invalid' })])!.status, 'Unsatisfied')
+  t.is(mergeNetworkInfo([]), null)
+})
+
+nwPathMonitorTest('dropping thousands of NwPathMonitor keeps the memory flat', async (t) => {
+  const churn = () => {
+    for (let i = 0; i < 1000; i++) {
+      const pm = new NwPathMonitor()
+      pm.startWeak(() => {})
+      // half of them are stopped before they are dropped, twice to cover a repeated stop
+      if (i % 2 === 0) {
+        pm.stop()
+        pm.stop()
+      }
+    }
+  }
+  const collect = async () => {
+    for (let i = 0; i < 5; i++) {
+      globalThis.gc?.()
+      await new Promise((resolve) => setTimeout(resolve, 10))
+    }
+  }
+
+  churn()
+  await collect()
+  const baseline = process.memoryUsage().rss
+  for (let round = 0; round < 5; round++) {
+    churn()
+    await collect()
+  }
+  // the monitors are released on GC, the later rounds must not grow the memory much
+  t.true(process.memoryUsage().rss - baseline < 64 * 1024 * 1024)
+})
+
+nwPathMonitorTest('the process exits after stop even with a strong start', (t) => {
+  const script = `
+    const { NwPathMonitor } = require(${JSON.stringify(bindingPath)})
+    const pm = new NwPathMonitor()
+    pm.start(() => {})
+    pm.start(() => {})
+    setTimeout(() => pm.stop(), 100)
+  `
+  const { status, error } = spawnSync(process.execPath, ['-e', script], { timeout: 10_000 })
+  t.falsy(error)
+  t.is(status, 0)
+})
+
+nwPathMonitorTest('a stopped monitor receives paths again once restarted', async (t) => {
+  const pm = new NwPathMonitor()
+  await new Promise<NetworkInfo>((resolve) => pm.startWeak(resolve))
+  pm.stop()
+  // the cancelled nw_path_monitor never delivers again, the restart must use a new one
+  const path = await new Promise<NetworkInfo>((resolve) => pm.startWeak(resolve))
+  pm.stop()
+  t.is(typeof path.status, 'string')
+})
+
+nwPathMonitorTest('the composite monitor reports every interface type', async (t) => {
+  const monitor = new NwCompositePathMonitor()
+  const paths = await new Promise<Record<string, unknown>>((resolve) => {
+    monitor.startWeak((paths) => {
+      if (paths.wifi && paths.cellular && paths.wired) {
+        resolve(paths)
+      }
+    })
+  })
+  monitor.stop()
+  t.deepEqual(Object.keys(paths).sort(), ['cellular', 'wifi', 'wired'])
+})
+
+nwPathMonitorTest('withOptions rejects prohibiting every interface type', (t) => {
+  t.throws(
+    () =>
+      NwPathMonitor.withOptions({
+        prohibited: [
+          NwInterfaceType.Other,
+          NwInterfaceType.Wifi,
+          NwInterfaceType.Cellular,
+          NwInterfaceType.Wired,
+          NwInterfaceType.Loopback,
+        ],
+      }),
+    { message: /Prohibiting every interface type/ },
+  )
+  t.notThrows(() => NwPathMonitor.withOptions({ prohibited: [NwInterfaceType.Cellular] }))
+})
+
+nwPathMonitorTest('withOptions accepts a queue label', async (t) => {
+  const pm = NwPathMonitor.withOptions({ queueLabel: 'com.example.app.network' })
+  const path = await new Promise<NetworkInfo>((resolve) => pm.startWeak(resolve))
+  pm.stop()
+  t.is(typeof path.status, 'string')
+  t.throws(() => NwPathMonitor.withOptions({ queueLabel: 'nul\0label' }), { message: /NUL character/ })
+})
+
+nwPathMonitorTest('replacing a strong update handler with a weak one releases it', (t) => {
+  const script = `
+    const { NwPathMonitor } = require(${JSON.stringify(bindingPath)})
+    const pm = new NwPathMonitor()
+    pm.start(() => {})
+    pm.startWeak(() => {})
+  `
+  // the block of the first start would keep the process alive if it was leaked
+  const { status, error } = spawnSync(process.execPath, ['-e', script], { timeout: 10_000 })
+  t.falsy(error)
+  t.is(status, 0)
+})
+
+nwPathMonitorTest('paths carry the DNS configuration generation', async (t) => {
+  const pm = new NwPathMonitor()
+  const path = await new Promise<NetworkInfo>((resolve) => pm.startWeak(resolve))
+  t.is(typeof path.dnsGeneration, 'number')
+  t.true(pm.current().dnsGeneration! >= path.dnsGeneration!)
+  pm.stop()
+})
+
+nwPathMonitorTest('onCancel is called once the monitor is stopped', async (t) => {
+  const pm = new NwPathMonitor()
+  await new Promise((resolve) => pm.startWeak(resolve))
+  const cancelled = new Promise((resolve) => pm.onCancel(resolve))
+  pm.stop()
+  await cancelled
+
+  // a monitor which is never started is cancelled right away
+  const idle = new NwPathMonitor()
+  const idleCancelled = new Promise((resolve) => idle.onCancel(resolve))
+  idle.stop()
+  await idleCancelled
+  t.pass()
+})
+
+nwPathMonitorTest('the endpoint monitor reports the path to its endpoint', async (t) => {
+  const monitor = new NwEndpointPathMonitor('localhost', 80)
+  const path = await new Promise<NetworkInfo>((resolve) => monitor.startWeak(resolve))
+  t.is(typeof path.status, 'string')
+  monitor.stop()
+  t.throws(() => monitor.current(), { message: /call start first/ })
+})
+
+nwPathMonitorTest('several interface types are watched at once', async (t) => {
+  const watchers: { dispose(): void }[] = []
+  const availabilities = await Promise.all(
+    [NwInterfaceType.Wifi, NwInterfaceType.Wired].map(
+      (interfaceType) =>
+        new Promise<{ available: boolean }>((resolve) => {
+          watchers.push(watchInterfaceAvailability(interfaceType, resolve))
+        }),
+    ),
+  )
+  watchers.forEach((watcher) => watcher.dispose())
+  for (const { available } of availabilities) {
+    t.is(typeof available, 'boolean')
+  }
+})
+
+nwPathMonitorTest('getWifiInfo describes the Wi-Fi association', (t) => {
+  const wifi = getWifiInfo()
+  if (wifi) {
+    t.is(typeof wifi.rssi, 'number')
+    t.true(wifi.channel > 0)
+  } else {
+    t.is(wifi, null)
+  }
+})
+
+nwPathMonitorTest('HostReachability decodes the flags of a host', (t) => {
+  const reachability = new HostReachability('localhost')
+  const flags = reachability.current()
+  t.is(typeof flags.isReachable, 'boolean')
+  t.is(typeof flags.isConnectionRequired, 'boolean')
+  t.is(typeof flags.isWwan, 'boolean')
+  // it coexists with a path monitor
+  const pm = new NwPathMonitor()
+  pm.startWeak(() => {})
+  reachability.startWeak(() => {})
+  reachability.stop()
+  pm.stop()
+  t.pass()
+})
+
+nwPathMonitorTest('path updates run with the requested QoS class', async (t) => {
+  const pm = NwPathMonitor.newWeakByDefault('Utility')
+  const path = await new Promise<NetworkInfo>((resolve) => pm.start(resolve))
+  pm.stop()
+  t.is(typeof path.status, 'string')
+  t.throws(() => new NwPathMonitor('Unknown' as any))
+})
+
+nwPathMonitorTest('unsatisfiedReason is only reported for unusable paths', async (t) => {
+  const pm = new NwPathMonitor()
+  const path = await new Promise<NetworkInfo>((resolve) => pm.startWeak(resolve))
+  pm.stop()
+  if (path.status === 'Satisfied') {
+    t.is(path.unsatisfiedReason, undefined)
+  } else {
+    t.pass()
+  }
+})
+
+nwPathMonitorTest('primaryInterface is the preferred interface of the path', async (t) => {
+  const pm = new NwPathMonitor()
+  const path = await new Promise<NetworkInfo>((resolve) => pm.startWeak(resolve))
+  pm.stop()
+  if (path.status === 'Satisfied') {
+    t.deepEqual(path.primaryInterface, path.interfaces[0])
+  } else {
+    t.pass()
+  }
+})
+
+nwPathMonitorTest('interfaces pair the BSD name with the interface type', async (t) => {
+  const pm = new NwPathMonitor()
+  const path = await new Promise<NetworkInfo>((resolve) => pm.startWeak(resolve))
+  pm.stop()
+  for (const { name, type } of path.interfaces) {
+    // e.g. `en0`, `pdp_ip0` or `utun3`, the same names as `ifconfig`
+    t.regex(name, /^[a-z_]+\d+$/)
+    t.is(typeof type, 'number')
+  }
+})
+
+nwPathMonitorTest('an unsatisfied path has no gateways', async (t) => {
+  const pm = new NwPathMonitor()
+  const path = await new Promise<NetworkInfo>((resolve) => pm.startWeak(resolve))
+  t.true(Array.isArray(pm.current().gateways))
+  pm.stop()
+  if (path.status === 'Unsatisfied') {
+    t.deepEqual(path.gateways, [])
+  } else {
+    t.true(path.gateways.every((gateway) => typeof gateway === 'string'))
+  }
+})
+
+nwPathMonitorTest('thousands of path updates keep the memory flat', async (t) => {
+  const updates = async () => {
+    for (let batch = 0; batch < 10; batch++) {
+      const monitors = Array.from({ length: 100 }, () => new NwPathMonitor())
+      // every monitor receives the current path right after it's started
+      await Promise.all(monitors.map((pm) => new Promise((resolve) => pm.startWeak(resolve))))
+      monitors.forEach((pm) => pm.stop())
+    }
+    globalThis.gc?.()
+    await new Promise((resolve) => setTimeout(resolve, 10))
+  }
+
+  await updates()
+  // on macOS the rss is the `resident_size` of `mach_task_basic_info`
+  const baseline = process.memoryUsage().rss
+  for (let round = 0; round < 5; round++) {
+    await updates()
+  }
+  // the update handlers drain their autorelease pool, the later rounds must not grow the memory much
+  t.true(process.memoryUsage().rss - baseline < 64 * 1024 * 1024)
+})
+
+internetMonitorTest('each worker thread owns an independent InternetMonitor', async (t) => {
+  const monitor = new InternetMonitor()
+  monitor.start(() => {})
+
+  const runWorker = () =>
+    new Promise<{ info: NetworkInfo }>((resolve, reject) => {
+      const worker = new Worker(new URL('./monitor-worker.mjs', import.meta.url))
+      worker.once('message', resolve)
+      worker.once('error', reject)
+    })
+
+  const results = await Promise.all([runWorker(), runWorker()])
+  for (const { info } of results) {
+    t.is(typeof info.status, 'string')
+  }
+
+  // stopping the monitors inside the workers must not affect the one on the main thread
+  t.notThrows(() => monitor.current())
+  monitor.stop()
+})
+
+mockTest('each worker thread calls back its own InternetMonitor', async (t) => {
+  const runWorker = (status: NetworkInfo['status']) =>
+    new Promise<{ info: NetworkInfo; updates: NetworkInfo[] }>((resolve, reject) => {
+      const worker = new Worker(new URL('./monitor-worker.mjs', import.meta.url), { workerData: { status } })
+      worker.once('message', resolve)
+      worker.once('error', reject)
+    })
+
+  const [satisfied, unsatisfied] = await Promise.all([runWorker('Satisfied'), runWorker('Unsatisfied')])
+  // every worker only sees the state pushed to its own monitor
+  t.deepEqual(satisfied.updates.map(({ status }) => status), ['Satisfied'])
+  t.deepEqual(unsatisfied.updates.map(({ status }) => status), ['Unsatisfied'])
+})
+
+internetMonitorTest('tearing down an environment detaches its running monitor', async (t) => {
+  const worker = new Worker(new URL('./monitor-worker.mjs', import.meta.url), { workerData: { leak: true } })
+  await new Promise((resolve, reject) => {
+    worker.once('message', resolve)
+    worker.once('error', reject)
+  })
+  // terminating the worker runs the environment cleanup hooks while `start` is still active
+  t.is(await worker.terminate(), 1)
+
+  const monitor = new InternetMonitor()
+  monitor.start(() => {})
+  t.notThrows(() => monitor.current())
+  monitor.stop()
+})
+
+internetMonitorTest('a worker exits while its strong listener is registered', async (t) => {
+  const worker = new Worker(new URL('./monitor-worker.mjs', import.meta.url), { workerData: { leak: true, exit: true } })
+  worker.once('error', (err) => t.fail(err.message))
+  // a callback the teardown doesn't release would keep the worker's event loop alive
+  const code = await new Promise<number>((resolve) => worker.once('exit', resolve))
+  t.is(code, 0)
+
+  const monitor = new InternetMonitor()
+  monitor.start(() => {})
+  t.notThrows(() => monitor.current())
+  monitor.stop()
+})
+
+internetMonitorTest('multiple InternetMonitor instances run side by side', (t) => {
+  const first = new InternetMonitor()
+  const second = new InternetMonitor()
+  first.start(() => {})
+  second.start(() => {})
+
+  // stopping one monitor must leave the other one listening
+  first.stop()
+  t.deepEqual(Object.keys(second.current()).sort(), Object.keys(first.current()).sort())
+  t.notThrows(() => second.stop())
+})
+
+mockTest('multiple InternetMonitor instances call back their own callback', (t) => {
+  const { monitor: first, offline } = mockMonitor()
+  const { monitor: second } = mockMonitor()
+  const firstUpdates: string[] = []
+  const secondUpdates: string[] = []
+  first.start(({ status }: NetworkInfo) => firstUpdates.push(status))
+  second.start(({ status }: NetworkInfo) => secondUpdates.push(status))
+
+  first.pushState({ ...offline, status: 'Satisfied' })
+  second.pushState({ ...offline, status: 'Satisfiable' })
+  t.deepEqual(firstUpdates, ['Satisfied'])
+  t.deepEqual(secondUpdates, ['Satisfiable'])
+
+  // stopping one monitor must leave the other one calling back
+  first.stop()
+  first.pushState({ ...offline, status: 'Unsatisfied' })
+  second.pushState({ ...offline, status: 'Satisfied' })
+  t.deepEqual(firstUpdates, ['Satisfied'])
+  t.deepEqual(secondUpdates, ['Satisfiable', 'Satisfied'])
+  second.stop()
+})
+
+internetMonitorTest('InternetMonitor.create resolves a ready monitor', async (t) => {
+  const monitor = await InternetMonitor.create()
+  t.true(monitor instanceof InternetMonitor)
+  monitor.start(() => {})
+  t.is(typeof monitor.current().status, 'string')
+  monitor.stop()
+})
+
+internetMonitorTest('snapshotJson shares the NetworkInfo schema', (t) => {
+  const monitor = new InternetMonitor()
+  const snapshot = JSON.parse(monitor.snapshotJson())
+  const { interfaceType, ...info } = monitor.current()
+  t.like(snapshot, info)
+  // the interface type is serialized by name, e.g. `"wifi"`, instead of the numeric enum value
+  t.is(typeof snapshot.interfaceType, interfaceType === undefined ? 'object' : 'string')
+  t.is(typeof snapshot.platform, 'string')
+  t.is(typeof snapshot.timestamp, 'number')
+})
+
+internetMonitorTest('canUseUnmetered combines the status and the cost fields', (t) => {
+  const monitor = new InternetMonitor()
+  const { status, isExpensive, isLowDataMode, isRoaming } = monitor.current()
+  t.is(monitor.canUseUnmetered(), status === 'Satisfied' && !isExpensive && !isLowDataMode && !isRoaming)
+  t.notThrows(() => monitor.onUnmeteredChange(() => {}))
+  monitor.stop()
+})
+
+internetMonitorTest('refresh replaces the cached snapshot', (t) => {
+  const monitor = new InternetMonitor()
+  const refreshed = monitor.refresh()
+  t.deepEqual(monitor.current(), refreshed)
+})
+
+internetMonitorTest('InternetMonitor.create accepts a timeout', async (t) => {
+  const monitor = await InternetMonitor.create({ timeoutMs: 30_000 })
+  t.is(typeof monitor.current().status, 'string')
+})
+
+internetMonitorTest('InternetMonitor.create rejects with the BackendTimeout code', async (t) => {
+  // a zero timeout never waits for the backend
+  const error = await t.throwsAsync(InternetMonitor.create({ timeoutMs: 0 }))
+  t.is((error as Error & { code?: string }).code, BACKEND_TIMEOUT)
+  t.is(BACKEND_TIMEOUT, 'BackendTimeout')
+})
+
+internetMonitorTest('connectionClass agrees with the current status', (t) => {
+  const monitor = new InternetMonitor()
+  const connectionClass = monitor.connectionClass()
+  if (monitor.current().status === 'Satisfied') {
+    t.not(connectionClass, 'Offline')
+  } else {
+    t.is(connectionClass, 'Offline')
+  }
+})
+
+internetMonitorTest('currentAll reports every interface', (t) => {
+  const monitor = new InternetMonitor()
+  const interfaces = monitor.currentAll()
+  for (const { name, addresses, status } of interfaces) {
+    t.is(typeof name, 'string')
+    t.true(addresses.every((address) => typeof address === 'string'))
+    t.true(['Satisfied', 'Satisfiable', 'Unsatisfied'].includes(status))
+  }
+  if (monitor.current().status !== 'Satisfied') {
+    t.false(interfaces.some(({ status }) => status === 'Satisfied'))
+  }
+})
+
+internetMonitorTest('listNetworks marks the connected networks', (t) => {
+  const monitor = new InternetMonitor()
+  for (const network of monitor.listNetworks()) {
+    t.is(typeof network.name, 'string')
+    t.is(network.connectivity === 'Unsatisfied', !network.isConnected)
+  }
+})
+
+internetMonitorTest('a weak by default monitor does not keep the process alive', (t) => {
+  const script = `
+    const { InternetMonitor } = require(${JSON.stringify(bindingPath)})
+    const monitor = InternetMonitor.newWeakByDefault()
+    monitor.start(() => {})
+  `
+  const { status, error } = spawnSync(process.execPath, ['-e', script], { timeout: 10_000 })
+  t.falsy(error)
+  t.is(status, 0)
+})
+
+internetMonitorTest('stop can be called repeatedly and the monitor restarted', (t) => {
+  const monitor = new InternetMonitor()
+  monitor.start(() => {})
+  t.notThrows(() => monitor.stop())
+  t.notThrows(() => monitor.stop())
+  monitor.start(() => {})
+  t.notThrows(() => monitor.stop())
+  t.notThrows(() => monitor.stop())
+})
+
+internetMonitorTest('starting twice only keeps the latest callback', (t) => {
+  const script = `
+    const { InternetMonitor } = require(${JSON.stringify(bindingPath)})
+    const monitor = new InternetMonitor()
+    monitor.start(() => {})
+    monitor.start(() => {})
+    monitor.stop()
+  `
+  // a leaked registration of the first start would keep the process alive
+  const { status, error } = spawnSync(process.execPath, ['-e', script], { timeout: 10_000 })
+  t.falsy(error)
+  t.is(status, 0)
+})
+
+internetMonitorTest('removing the last listener releases the monitor', (t) => {
+  const script = `
+    const { InternetMonitor } = require(${JSON.stringify(bindingPath)})
+    const monitor = new InternetMonitor()
+    const onChange = () => {}
+    const onOffline = () => {}
+    monitor.on('change', onChange)
+    monitor.on('offline', onOffline)
+    // removing a listener which was never added is a no-op
+    monitor.off('online', onChange)
+    monitor.off('change', onChange)
+    monitor.off('offline', onOffline)
+  `
+  // a leaked listener would keep the process alive
+  const { status, error } = spawnSync(process.execPath, ['-e', script], { timeout: 10_000 })
+  t.falsy(error)
+  t.is(status, 0)
+})
+
+nwPathMonitorTest('listeners added again after the last off receive paths', async (t) => {
+  const pm = new NwPathMonitor()
+  const onChange = () => {}
+  pm.on('change', onChange)
+  // removing the last listener stops the monitor
+  pm.off('change', onChange)
+  const path = await new Promise<NetworkInfo>((resolve) => pm.on('change', resolve))
+  pm.stop()
+  t.is(typeof path.status, 'string')
+})
+
+mockTest('listeners added again after the last off receive the changes', async (t) => {
+  const { monitor, offline } = mockMonitor()
+  const onChange = () => {}
+  monitor.on('change', onChange)
+  monitor.off('change', onChange)
+  const received: string[] = []
+  monitor.on('change', ({ status }: NetworkInfo) => received.push(status))
+  monitor.pushState({ ...offline, status: 'Satisfied' })
+  await flush()
+  t.deepEqual(received, ['Satisfied'])
+  monitor.close()
+})
+
+internetMonitorTest('every method throws once the monitor is closed', (t) => {
+  const monitor = new InternetMonitor()
+  monitor.start(() => {})
+  monitor.close()
+  // closing twice is fine
+  t.notThrows(() => monitor.close())
+
+  const calls: [string, () => unknown][] = [
+    ['history', () => monitor.history()],
+    ['current', () => monitor.current()],
+    ['refresh', () => monitor.refresh()],
+    ['currentAll', () => monitor.currentAll()],
+    ['networkCategory', () => monitor.networkCategory()],
+    ['listNetworks', () => monitor.listNetworks()],
+    ['connectionClass', () => monitor.connectionClass()],
+    ['canUseUnmetered', () => monitor.canUseUnmetered()],
+    ['powerState', () => monitor.powerState()],
+    ['snapshotJson', () => monitor.snapshotJson()],
+    ['start', () => monitor.start(() => {})],
+    ['startStrong', () => monitor.startStrong(() => {})],
+    ['startWeak', () => monitor.startWeak(() => {})],
+    ['startWithBackoff', () => monitor.startWithBackoff(null, () => {})],
+    ['onMeteredChange', () => monitor.onMeteredChange(() => {})],
+    ['onUnmeteredChange', () => monitor.onUnmeteredChange(() => {})],
+    ['onPrimaryInterfaceChange', () => monitor.onPrimaryInterfaceChange(() => {})],
+    ['onReconnect', () => monitor.onReconnect(() => {})],
+    ['onStableOnline', () => monitor.onStableOnline(1000, () => {})],
+    ['startFiltered', () => monitor.startFiltered(ChangeFilter.OnAny, () => {})],
+    ['on', () => monitor.on('change', () => {})],
+    ['off', () => monitor.off('change', () => {})],
+    ['connected', () => monitor.connected()],
+    ['ready', () => monitor.ready()],
+    ['startThroughput', () => monitor.startThroughput(100)],
+    ['throughput', () => monitor.throughput()],
+    ['stopThroughput', () => monitor.stopThroughput()],
+    ['stop', () => monitor.stop()],
+  ]
+  for (const [name, call] of calls) {
+    t.throws(call, { message: 'monitor has been disposed' }, name)
+  }
+})
+
+internetMonitorTest('throughput is sampled until stopThroughput', async (t) => {
+  const monitor = new InternetMonitor()
+  t.throws(() => monitor.startThroughput(0))
+  monitor.startThroughput(20)
+  t.is(monitor.throughput(), null)
+  await new Promise((resolve) => setTimeout(resolve, 200))
+  const throughput = monitor.throughput()
+  t.truthy(throughput)
+  t.true(throughput!.rxBps >= 0)
+  t.true(throughput!.txBps >= 0)
+  monitor.stopThroughput()
+  t.is(monitor.throughput(), null)
+  monitor.close()
+})
+
+mockTest('history keeps the last historyCapacity states', async (t) => {
+  const { monitor, offline } = mockMonitor(await InternetMonitor.create({ historyCapacity: 2 }))
+  // nothing is recorded until the monitor is started
+  monitor.pushState({ ...offline, status: 'Satisfiable' })
+  t.deepEqual(monitor.history(), [])
+
+  monitor.start(() => {})
+  const before = Date.now()
+  for (const status of ['Satisfiable', 'Satisfied', 'Unsatisfied'] as const) {
+    monitor.pushState({ ...offline, status })
+  }
+  const history = monitor.history()
+  t.deepEqual(
+    history.map(({ info }) => info.status),
+    ['Satisfied', 'Unsatisfied'],
+  )
+  t.true(history.every(({ timestampMs }) => timestampMs >= before && timestampMs <= Date.now()))
+  monitor.close()
+})
+
+mockTest('the constructor takes the historyCapacity of create', (t) => {
+  const { monitor, offline } = mockMonitor(new InternetMonitor({ historyCapacity: 1 }))
+  monitor.start(() => {})
+  monitor.pushState({ ...offline, status: 'Satisfiable' })
+  monitor.pushState({ ...offline, status: 'Satisfied' })
+  t.deepEqual(
+    monitor.history().map(({ info }) => info.status),
+    ['Satisfied'],
+  )
+  monitor.close()
+})
+
+mockTest('startFiltered only forwards the matching changes', async (t) => {
+  const { monitor, offline } = mockMonitor()
+  t.throws(() => monitor.startFiltered(0, () => {}), { message: /not a combination of ChangeFilter flags/ })
+
+  const received: NetworkInfo[] = []
+  monitor.startFiltered(ChangeFilter.OnStatusChange | ChangeFilter.OnMeteredChange, (info) => received.push(info))
+  monitor.pushState({ ...offline, hasDns: !offline.hasDns })
+  monitor.pushState({ ...offline, status: 'Satisfied' })
+  monitor.pushState({ ...offline, status: 'Satisfied', isExpensive: true })
+  monitor.pushState({ ...offline, status: 'Satisfied', isExpensive: true, hasIpv6: !offline.hasIpv6 })
+  await flush()
+  t.deepEqual(
+    received.map(({ status, isExpensive }) => [status, isExpensive]),
+    [
+      ['Satisfied', false],
+      ['Satisfied', true],
+    ],
+  )
+  monitor.close()
+})
+
+mockTest('newWithType only reports the changes of that interface type', (t) => {
+  const { monitor, offline } = mockMonitor(InternetMonitor.newWithType(NwInterfaceType.Wifi))
+  const received: [string, NwInterfaceType | undefined][] = []
+  monitor.start(({ status, interfaceType }) => received.push([status, interfaceType]))
+  monitor.pushState({ ...offline, status: 'Satisfied', interfaceType: NwInterfaceType.Wired })
+  monitor.pushState({ ...offline, status: 'Satisfied', interfaceType: NwInterfaceType.Wifi })
+  monitor.pushState({ ...offline, status: 'Satisfied', interfaceType: NwInterfaceType.Wifi, isExpensive: true })
+  // moving away from Wi-Fi is reported once
+  monitor.pushState({ ...offline, status: 'Satisfied', interfaceType: NwInterfaceType.Cellular })
+  monitor.pushState({ ...offline, status: 'Unsatisfied', interfaceType: NwInterfaceType.Cellular })
+  t.deepEqual(received, [
+    ['Satisfied', NwInterfaceType.Wifi],
+    ['Satisfied', NwInterfaceType.Wifi],
+    ['Satisfied', NwInterfaceType.Cellular],
+  ])
+  monitor.close()
+})
+
+mockTest('onPrimaryInterfaceChange ignores going offline in between', async (t) => {
+  const { monitor, offline } = mockMonitor()
+  const received: NwInterfaceType[] = []
+  monitor.onPrimaryInterfaceChange((interfaceType) => received.push(interfaceType))
+  const online = { ...offline, status: 'Satisfied' } as const
+  monitor.pushState({ ...online, interfaceType: NwInterfaceType.Wifi })
+  monitor.pushState(offline)
+  monitor.pushState({ ...online, interfaceType: NwInterfaceType.Wifi })
+  monitor.pushState({ ...online, interfaceType: NwInterfaceType.Cellular })
+  monitor.pushState({ ...online, interfaceType: NwInterfaceType.Cellular, isExpensive: true })
+  await flush()
+  t.deepEqual(received, [NwInterfaceType.Cellular])
+  monitor.close()
+})
+
+mockTest('onReconnect reports the duration of every offline episode', async (t) => {
+  const { monitor, offline } = mockMonitor()
+  const online = { ...offline, status: 'Satisfied' } as const
+  monitor.pushState(online)
+  const received: number[] = []
+  monitor.onReconnect((durationMs) => received.push(durationMs))
+  monitor.pushState({ ...online, isExpensive: true })
+  monitor.pushState(offline)
+  // the first offline episode lasts 100ms
+  await new Promise((resolve) => setTimeout(resolve, 100))
+  monitor.pushState({ ...offline, status: 'Satisfiable' })
+  monitor.pushState(online)
+  monitor.pushState(offline)
+  monitor.pushState(online)
+  await flush()
+  t.is(received.length, 2)
+  t.true(received[0] >= 90)
+  t.true(received[1] < received[0])
+  monitor.close()
+})
+
+mockTest('connected resolves on the first Satisfied state and stays resolved', async (t) => {
+  const { monitor, offline } = mockMonitor()
+  let resolved = false
+  const connected = monitor.connected().then(() => {
+    resolved = true
+  })
+  monitor.pushState({ ...offline, status: 'Satisfiable' })
+  await flush()
+  t.false(resolved)
+
+  monitor.pushState({ ...offline, status: 'Satisfied' })
+  await connected
+  // going offline doesn't reset it, nor its `ready` alias
+  monitor.pushState(offline)
+  await t.notThrowsAsync(monitor.connected())
+  await t.notThrowsAsync(monitor.ready())
+
+  const stopped = new InternetMonitor()
+  const pending = stopped.connected()
+  stopped.stop()
+  await t.throwsAsync(pending, { message: /stopped before it was online/ })
+  monitor.close()
+})
+
+mockTest('the mock monitor replays the pushed states synchronously', (t) => {
+  const { monitor, offline } = mockMonitor()
+  t.is(offline.status, 'Unsatisfied')
+
+  const statuses: string[] = []
+  monitor.start(({ status }) => statuses.push(status))
+  for (const status of ['Satisfiable', 'Satisfied'] as const) {
+    monitor.pushState({ ...offline, status })
+  }
+  // the callback already ran when `pushState` returns
+  t.deepEqual(statuses, ['Satisfiable', 'Satisfied'])
+  t.is(monitor.connectionClass(), 'WiredFast')
+  t.is(getGlobalNetworkInfo().status, 'Satisfied')
+  monitor.close()
+})
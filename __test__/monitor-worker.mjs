@@ -0,0 +1,24 @@
+import { parentPort, workerData } from 'node:worker_threads'
+
+import { InternetMonitor } from '../index.js'
+
+const monitor = new InternetMonitor()
+
+const updates = []
+monitor.start((info) => updates.push(info))
+const info = monitor.current()
+// only the mock backend can drive an update, its callback is called synchronously
+if (workerData?.status) {
+  monitor.pushState({ ...info, status: workerData.status })
+}
+// leave the monitor running so the environment cleanup hooks have to detach it
+if (!workerData?.leak) {
+  monitor.stop()
+}
+
+parentPort.postMessage({ info, updates })
+
+// exit with the strong listener still registered, the environment teardown has to release it
+if (workerData?.exit) {
+  process.exit(0)
+}
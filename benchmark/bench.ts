@@ -1,21 +1,23 @@
 import { Bench } from 'tinybench'
 
-import { plus100 } from '../index.js'
+import { InternetMonitor } from '../index.js'
 
-function add(a: number) {
-  return a + 100
-}
+// `refresh` evaluates a full snapshot synchronously, the same work as a connectivity event of the platform,
+// e.g. the adapters walk on Windows
+const monitor = new InternetMonitor()
 
 const b = new Bench()
 
-b.add('Native a + 100', () => {
-  plus100(10)
+b.add('InternetMonitor#refresh', () => {
+  monitor.refresh()
 })
 
-b.add('JavaScript a + 100', () => {
-  add(10)
+b.add('InternetMonitor#current', () => {
+  monitor.current()
 })
 
 await b.run()
 
 console.table(b.table())
+
+monitor.close()
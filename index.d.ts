@@ -1,27 +1,499 @@
 /* auto-generated by NAPI-RS */
 /* eslint-disable */
 export declare class InternetMonitor {
-  constructor()
+  /** `timeoutMs` is ignored by the constructor, it doesn't time out. */
+  constructor(options?: CreateOptions | undefined | null)
+  /**
+   * Create an InternetMonitor which only reports the changes while `interfaceType` carries the traffic,
+   * and the one moving the traffic away from it, e.g. to only follow the Wi-Fi.
+   *
+   * The same as `NWPathMonitor.newWithType` on macOS. `current` and `history` still describe every interface.
+   */
+  static newWithType(interfaceType: NwInterfaceType): InternetMonitor
+  /**
+   * Create an InternetMonitor whose `start` uses a weak reference, it will not keep the Node.js alive.
+   *
+   * Use `startStrong` to opt out for a single listener.
+   */
+  static newWeakByDefault(): InternetMonitor
+  /** Create the InternetMonitor on the libuv thread pool, so connecting to NetworkManager won't block the JavaScript thread. */
+  static create(options?: CreateOptions | undefined | null): Promise<InternetMonitor>
+  /**
+   * The last snapshots delivered to the callback, oldest first, `historyCapacity` of them at most.
+   *
+   * They are recorded while the monitor is started, whichever callback it's started with.
+   */
+  history(): Array<NetworkHistoryEntry>
   current(): NetworkInfo
-  /** Start the InternetMonitor, it will keep the Node.js alive unless you call stop on it. */
+  /**
+   * Evaluate every field again instead of waiting for the next event, and return the new snapshot,
+   * e.g. after a VPN was connected by another library and the events are late.
+   *
+   * The callback passed to `start` is not called.
+   */
+  refresh(): NetworkInfo
+  /** The state of every NetworkManager device, `current` only describes the primary connection. */
+  currentAll(): Array<InterfaceNetworkInfo>
+  /**
+   * The category of the connected network, Windows is the only platform which has one,
+   * the other platforms always return `Unknown`.
+   */
+  networkCategory(): NetworkCategory
+  /**
+   * Every connection profile saved in NetworkManager, active or not, e.g. for a "known networks" UI.
+   *
+   * Not available on macOS, Network.framework only describes the current path.
+   */
+  listNetworks(): Array<KnownNetwork>
+  /** A coarse quality tier of the current connection, see `ConnectionClass` for the thresholds. */
+  connectionClass(): ConnectionClass
+  /** It's safe to do a large download right now: the network is `Satisfied`, not expensive, not in Low Data Mode and not roaming. */
+  canUseUnmetered(): boolean
+  /** Whether the machine runs on battery, only reported on Windows for now, `null` on the other platforms. */
+  powerState(): PowerState | null
+  /** Serialize the current `NetworkInfo` to JSON with the schema shared by every platform, for logging and telemetry. */
+  snapshotJson(): string
+  /**
+   * Start the InternetMonitor, it will keep the Node.js alive unless you call stop on it.
+   *
+   * Monitors created with `newWeakByDefault` don't keep the Node.js alive, the same as `startWeak`.
+   */
   start(onUpdate: (arg: NetworkInfo) => void): void
+  /** Start with a strong reference whatever the monitor was created with, it will keep the Node.js alive unless you call stop on it. */
+  startStrong(onUpdate: (arg: NetworkInfo) => void): void
   /** Start the InternetMonitor with weak reference, it will not keep the Node.js alive. */
   startWeak(onUpdate: (arg: NetworkInfo) => void): void
+  /**
+   * Emit `RetrySuggested` events with an exponential backoff while the network is down,
+   * and a single `Reconnected` event once it's back.
+   *
+   * It replaces the callback passed to `start`.
+   */
+  startWithBackoff(options: BackoffOptions | undefined | null, onEvent: (arg: BackoffEvent) => void): void
+  /**
+   * Call `onChange` only when `isExpensive` or `isLowDataMode` flips, the connectivity changes are ignored.
+   *
+   * It replaces the callback passed to `start`.
+   */
+  onMeteredChange(onChange: (arg: MeteredStatus) => void): void
+  /**
+   * Call `onChange` with the new `canUseUnmetered` when it flips, e.g. to pause the large downloads when the network
+   * becomes expensive, Low Data Mode is turned on, roaming starts or the connection is lost.
+   *
+   * It replaces the callback passed to `start`.
+   */
+  onUnmeteredChange(onChange: (arg: boolean) => void): void
+  /**
+   * Call `onChange` with the new interface type when the traffic moves to another one, e.g. from Wi-Fi to cellular
+   * or from Ethernet to Wi-Fi, to reset the connections even though the status stays `Satisfied`.
+   *
+   * It replaces the callback passed to `start`.
+   */
+  onPrimaryInterfaceChange(onChange: (arg: NwInterfaceType) => void): void
+  /**
+   * Call `onReconnect` with how long the network was offline, in milliseconds, when the status becomes `Satisfied` again,
+   * e.g. to size the retry windows. Every offline episode starts the timer over.
+   *
+   * It replaces the callback passed to `start`.
+   */
+  onReconnect(onReconnect: (arg: number) => void): void
+  /**
+   * Call `onOnline` once the network stayed `Satisfied` for `minStableMs` in a row, any other status in between
+   * starts the wait over. Unlike the first `Satisfied` update, it's not fired while the connectivity flaps.
+   *
+   * It replaces the callback passed to `start`.
+   */
+  onStableOnline(minStableMs: number, onOnline: (arg: NetworkInfo) => void): void
+  /**
+   * Call `onUpdate` only with the snapshots matching `filter`, a combination of `ChangeFilter` flags,
+   * the other ones are dropped before they cross into JavaScript.
+   *
+   * It replaces the callback passed to `start`.
+   */
+  startFiltered(filter: number, onUpdate: (arg: NetworkInfo) => void): void
+  /**
+   * Add a listener for `event`, like `EventEmitter.on`. The `change`, `online` and `offline` listeners
+   * receive a `NetworkInfo`, the `metered` ones a `MeteredStatus` and the `error` ones an `Error`.
+   *
+   * It replaces the callback passed to `start`.
+   */
+  on(event: MonitorEvent, listener: (arg: any) => void): void
+  /** Remove a listener added by `on`, the monitor is stopped once the last one is removed. */
+  off(event: MonitorEvent, listener: (arg: any) => void): void
+  /**
+   * Resolve once the monitor saw a `Satisfied` status, right away when it already did,
+   * so it can be awaited before every request cheaply.
+   *
+   * It never resets, going offline afterwards doesn't make it wait again, listen to `offline` with `on` for that.
+   * A monitor which is not started is started without a callback, `stop` rejects the pending promises.
+   */
+  connected(): Promise<void>
+  /** Alias of `connected`. */
+  ready(): Promise<void>
+  /**
+   * Sample the byte counters of the physical interfaces every `intervalMs` on a separate thread,
+   * `throughput` returns the rate between the last two samples. Starting it again restarts the sampling.
+   *
+   * It measures the total traffic of the machine, not the traffic of the app.
+   * The sampling is independent of `start` and `stop`, it runs until `stopThroughput` or `close`.
+   */
+  startThroughput(intervalMs: number): void
+  /** The received and sent bytes per second, `null` until `startThroughput` sampled twice. */
+  throughput(): Throughput | null
+  /** Stop the sampling started by `startThroughput`. */
+  stopThroughput(): void
   /**
    * Stop the InternetMonitor.
    *
    * If you don't call this method and leave the monitor alone, it will be stopped automatically when it is GC.
    */
   stop(): void
+  /**
+   * Stop the InternetMonitor and release its callback, every method throws once it's closed.
+   *
+   * Calling `close` again is a no-op.
+   */
+  close(): void
+}
+
+/** The `code` of the error `InternetMonitor.create` rejects with when the platform backend is not ready in `timeoutMs`. */
+export declare const BACKEND_TIMEOUT: string
+
+export interface BackoffEvent {
+  kind: BackoffEventKind
+  /** The number of `RetrySuggested` events since the network went down */
+  attempt: number
+  /** How long the monitor waited before this `RetrySuggested` event, 0 for `Reconnected` */
+  delayMs: number
+  info: NetworkInfo
+}
+
+export type BackoffEventKind = /** The network is still down| it's a good time to retry */
+'RetrySuggested'|
+/** The network is back after being down */
+'Reconnected';
+
+export interface BackoffOptions {
+  /** The delay before the first `RetrySuggested` event, 1 second by default. */
+  baseMs?: number
+  /** The delay doubles after every `RetrySuggested` event up to this value, 1 minute by default. */
+  maxMs?: number
+}
+
+/** The flags of `startFiltered`, combine them with `|`. */
+export declare enum ChangeFilter {
+  /** The `status` changed */
+  OnStatusChange = 1,
+  /** `isExpensive` or `isLowDataMode` flipped */
+  OnMeteredChange = 2,
+  /** The interface carrying the traffic changed, e.g. Wi-Fi to Ethernet or a VPN came up */
+  OnInterfaceChange = 4,
+  /** Every snapshot, the same as `start` */
+  OnAny = 8
+}
+
+/**
+ * A coarse quality tier of a connection.
+ *
+ * The tiers are decided in this order:
+ * - `Offline` unless the status is `Satisfied`
+ * - `CellularMetered` for cellular interfaces and expensive paths, e.g. a personal hotspot
+ * - `Slow` when the link speed is known and below 10 Mbit/s
+ * - `WifiFast` for Wi-Fi, `WiredFast` for Ethernet and the other interfaces (VPN, virtual adapters)
+ */
+export type ConnectionClass = /** Ethernet or another unmetered link which is fast enough */
+'WiredFast'|
+/** Wi-Fi which is fast enough */
+'WifiFast'|
+/** Cellular or another expensive link| save data on it */
+'CellularMetered'|
+/** The link speed is below 10 Mbit/s */
+'Slow'|
+/** There is no usable path */
+'Offline';
+
+/** The `NLM_CONNECTION_COST` bits of `costFlags`, test them with `&`. */
+export declare enum ConnectionCostFlag {
+  /** NLM_CONNECTION_COST_UNRESTRICTED The connection is unlimited */
+  Unrestricted = 1,
+  /** NLM_CONNECTION_COST_FIXED The usage is unrestricted up to a data limit */
+  Fixed = 2,
+  /** NLM_CONNECTION_COST_VARIABLE The connection is billed by the byte */
+  Variable = 4,
+  /** NLM_CONNECTION_COST_OVERDATALIMIT The data limit is exceeded */
+  OverDataLimit = 65536,
+  /** NLM_CONNECTION_COST_CONGESTED The network is congested */
+  Congested = 131072,
+  /** NLM_CONNECTION_COST_ROAMING The connection is roaming outside of the home network */
+  Roaming = 262144,
+  /** NLM_CONNECTION_COST_APPROACHINGDATALIMIT The data limit is close */
+  ApproachingDataLimit = 524288
+}
+
+/** The `NLM_CONNECTIVITY` bits of `connectivityFlags`, one boolean per bit. */
+export interface ConnectivityFlags {
+  /** NLM_CONNECTIVITY_IPV4_NOTRAFFIC Connected without any IPv4 traffic, e.g. no DHCP lease */
+  ipv4NoTraffic: boolean
+  /** NLM_CONNECTIVITY_IPV6_NOTRAFFIC Connected without any IPv6 traffic */
+  ipv6NoTraffic: boolean
+  /** NLM_CONNECTIVITY_IPV4_SUBNET IPv4 only reaches the local subnet */
+  hasIpv4Subnet: boolean
+  /** NLM_CONNECTIVITY_IPV4_LOCALNETWORK IPv4 reaches a routed local network, but not the Internet */
+  hasIpv4LocalNetwork: boolean
+  /** NLM_CONNECTIVITY_IPV4_INTERNET IPv4 reaches the Internet */
+  hasIpv4Internet: boolean
+  /** NLM_CONNECTIVITY_IPV6_SUBNET IPv6 only reaches the local subnet */
+  hasIpv6Subnet: boolean
+  /** NLM_CONNECTIVITY_IPV6_LOCALNETWORK IPv6 reaches a routed local network, but not the Internet */
+  hasIpv6LocalNetwork: boolean
+  /** NLM_CONNECTIVITY_IPV6_INTERNET IPv6 reaches the Internet */
+  hasIpv6Internet: boolean
+}
+
+/** How far an IP family reaches| from the `NLM_CONNECTIVITY` bits. */
+export type ConnectivityLevel = /** Not connected| or connected without any traffic| e.g. no DHCP lease */
+'None'|
+/** Only the local subnet or a routed local network| e.g. a LAN without an Internet uplink */
+'LocalOnly'|
+/** The Internet is reachable */
+'Internet';
+
+export interface CreateOptions {
+  /**
+   * Reject with a `BackendTimeout` error if the platform backend is not ready in time,
+   * e.g. when the NetworkManager D-Bus service or the Windows netprofm service hangs.
+   * Its `code` is `BACKEND_TIMEOUT`, 0 always times out.
+   */
+  timeoutMs?: number
+  /**
+   * Ignore the adapters whose name or description contains one of these strings, case-insensitively,
+   * on top of a built-in list of the host-only adapters of Hyper-V, WSL, VMware, VirtualBox and Npcap.
+   *
+   * They are up without reaching the Internet and skew `hasDns`, the status and `currentAll`. Only used on Windows.
+   */
+  excludeAdapters?: Array<string>
+  /** How many snapshots `history` keeps, 32 by default, 0 turns the history off. */
+  historyCapacity?: number
+  /**
+   * Allow `onInterfaceChange`, the per-interface events of IP Helper are immediate but much noisier
+   * than the connectivity events. Only used on Windows.
+   */
+  ipInterfaceEvents?: boolean
+}
+
+/** Name the bits of `connectivityFlags`, every flag is `false` for the 0 of the other platforms. */
+export declare function decodeConnectivityFlags(flags: number): ConnectivityFlags
+
+/**
+ * Read the network state once, for CLI tools and health checks which don't need a long-lived monitor.
+ *
+ * A transient NetworkManager client is created and released before returning.
+ */
+export declare function getGlobalNetworkInfo(): NetworkInfo
+
+/** The state of a single interface, for machines with several of them, e.g. Ethernet + Wi-Fi + VPN. */
+export interface InterfaceNetworkInfo {
+  /** The name of the interface, e.g. `en0` on macOS, `eth0` on Linux or `Wi-Fi` on Windows */
+  name: string
+  type: NwInterfaceType
+  /**
+   * `Satisfied` when the interface reaches the Internet, `Satisfiable` when it's up, `Unsatisfied` when it's down.
+   *
+   * On macOS every interface of the path shares the status of the path.
+   */
+  status: NetworkStatus
+  /** The IPv4 and IPv6 addresses assigned to the interface */
+  addresses: Array<string>
+  /** The interface is metered, e.g. a mobile broadband modem or a connection the user marked as metered. */
+  isMetered: boolean
+}
+
+/** The IP stacks which can be used on the path. */
+export type IpStackType = /** Neither IPv4 nor IPv6 is available */
+'None'|
+/** Only IPv4 is available */
+'Ipv4Only'|
+/** Only IPv6 is available| e.g. a NAT64 cellular network */
+'Ipv6Only'|
+/** Both IPv4 and IPv6 are available */
+'DualStack';
+
+/** A network the system remembers, connected or not. */
+export interface KnownNetwork {
+  /** The name shown to the user, e.g. the Wi-Fi SSID on Windows or the connection profile name on Linux */
+  name: string
+  isConnected: boolean
+  /**
+   * `Satisfied` when the network reaches the Internet, `Satisfiable` when it's only connected locally,
+   * `Unsatisfied` when it's not connected.
+   */
+  connectivity: NetworkStatus
+  /** Always `Unknown` on Linux */
+  category: NetworkCategory
+}
+
+/** The billing related part of a `NetworkInfo`. */
+export interface MeteredStatus {
+  isExpensive: boolean
+  isLowDataMode: boolean
+}
+
+/**
+ * Merge the `NetworkInfo` of several monitors into the best available one, e.g. a Wi-Fi and a cellular monitor.
+ *
+ * The best path is the one with the highest status, `Satisfied` then `Satisfiable` then `Unsatisfied`,
+ * the earlier one in `infos` wins a tie. The capabilities of the paths sharing its status are unioned:
+ * `hasIpv4`, `hasIpv6`, `hasDns`, the `uses*` flags, `interfaces` and `gateways`.
+ * Everything else, e.g. `isExpensive` or `primaryInterface`, is the one of the best path.
+ *
+ * `null` when `infos` is empty.
+ */
+export declare function mergeNetworkInfo(infos: Array<NetworkInfo>): NetworkInfo | null
+
+/**
+ * The `MeteredStatus` Windows derives from `costFlags`: the connection is expensive when it's metered (a fixed
+ * or a variable plan), roaming or over its data limit, the same as "Metered connection" in the Settings app.
+ * It's in Low Data Mode when it's over or close to the data limit, or congested.
+ *
+ * Use it with the `costFlags` of another source, or read `costFlags` and `dataLimitMb` to apply another policy.
+ */
+export declare function meteredStatusFromCostFlags(costFlags: number): MeteredStatus
+
+/** The named events of `on` and `off`. */
+export type MonitorEvent = /** Every new `NetworkInfo`| the same as the callback of `start` */
+'change'|
+/** The status became `Satisfied`| with the new `NetworkInfo` */
+'online'|
+/** The status is no longer `Satisfied`| with the new `NetworkInfo` */
+'offline'|
+/** `isExpensive` or `isLowDataMode` flipped| with the new `MeteredStatus` */
+'metered'|
+/** The platform backend failed to read the network state after an event| with an `Error` */
+'error';
+
+/** The trust level Windows assigns to a network, it decides which firewall profile applies. */
+export type NetworkCategory = /** NLM_NETWORK_CATEGORY_PUBLIC The network is not trusted| e.g. a coffee shop Wi-Fi */
+'Public'|
+/** NLM_NETWORK_CATEGORY_PRIVATE The network is trusted by the user| e.g. the home network */
+'Private'|
+/** NLM_NETWORK_CATEGORY_DOMAIN_AUTHENTICATED The network is authenticated against an Active Directory domain */
+'DomainAuthenticated'|
+/** Not connected| or not on Windows */
+'Unknown';
+
+/** A `NetworkInfo` delivered to the callback of the monitor, and when it was. */
+export interface NetworkHistoryEntry {
+  /** Milliseconds since the Unix epoch, comparable with `Date.now()` */
+  timestampMs: number
+  info: NetworkInfo
 }
 
 export interface NetworkInfo {
   status: NetworkStatus
+  /**
+   * The path uses an interface that is considered expensive, such as cellular or a personal hotspot.
+   *
+   * On macOS this is `nw_path_is_expensive`, on Windows the connection is metered, roaming or over its data limit,
+   * see `meteredStatusFromCostFlags`.
+   */
   isExpensive: boolean
+  /**
+   * The path uses an interface in Low Data Mode, the user asked to save data on it.
+   *
+   * On macOS this is `nw_path_is_constrained`, which is independent from `is_expensive`.
+   * On Windows the connection is over or close to its data limit, or congested.
+   */
   isLowDataMode: boolean
+  /** The connection is roaming outside of the home network of its operator, only reported on Windows. */
+  isRoaming: boolean
   hasIpv4: boolean
   hasIpv6: boolean
   hasDns: boolean
+  /** Derived from `has_ipv4` and `has_ipv6` */
+  stack: IpStackType
+  /** The type of the interface carrying the traffic, `undefined` when there is no usable interface. */
+  interfaceType?: NwInterfaceType
+  /** Why the path is not usable, only reported by Network.framework on macOS 11+ when the status is `Unsatisfied`. */
+  unsatisfiedReason?: UnsatisfiedReason
+  /** The category of the connected network, only reported on Windows. */
+  networkCategory?: NetworkCategory
+  /**
+   * The link speed of the primary interface in Mbit/s, when the platform knows it.
+   *
+   * It's the negotiated speed of Ethernet and the bitrate of Wi-Fi, not the throughput to the Internet.
+   */
+  linkSpeedMbps?: number
+  /** The interfaces used by the path, e.g. a `utun` VPN interface on top of Wi-Fi, only reported on macOS. */
+  interfaces: Array<NetworkInterface>
+  /**
+   * The preferred interface of the path, e.g. to bind a socket to it with `IP_BOUND_IF`, only reported on macOS.
+   *
+   * `undefined` when the path is not usable.
+   */
+  primaryInterface?: NetworkInterface
+  /**
+   * The first-hop gateways of the path, e.g. `192.168.1.1` or `fe80::1%en0`, only reported on macOS.
+   *
+   * Empty when the path is not satisfied.
+   */
+  gateways: Array<string>
+  /**
+   * Bumped every time the system resolver configuration changes, e.g. a VPN pushing split DNS,
+   * it's the moment to flush an in-app DNS cache. Only reported on macOS.
+   */
+  dnsGeneration?: number
+  /**
+   * The path goes over Wi-Fi.
+   *
+   * On macOS this is `nw_path_uses_interface_type` and covers every interface of the path,
+   * other platforms only check the primary interface.
+   */
+  usesWifi: boolean
+  /** The path goes over cellular, including an iPhone tethered over USB on macOS. */
+  usesCellular: boolean
+  /** The path goes over wired Ethernet. */
+  usesWired: boolean
+  /** The path goes over the loopback interface. */
+  usesLoopback: boolean
+  /**
+   * The raw `NLM_CONNECTIVITY` bits of the network list manager, `decodeConnectivityFlags` names them.
+   *
+   * They tell an isolated LAN from a machine which is fully offline. Only reported on Windows, 0 on the other platforms.
+   */
+  connectivityFlags: number
+  /**
+   * How far IPv4 reaches, e.g. `LocalOnly` on a LAN without Internet and `None` without any connectivity.
+   *
+   * Only reported on Windows, `None` on the other platforms.
+   */
+  ipv4Connectivity: ConnectivityLevel
+  /** How far IPv6 reaches, only reported on Windows, `None` on the other platforms. */
+  ipv6Connectivity: ConnectivityLevel
+  /**
+   * The raw `NLM_CONNECTION_COST` bits of the connection, `ConnectionCostFlag` names them,
+   * e.g. to tell a fixed data plan from a variable one or roaming. Only reported on Windows, 0 on the other platforms.
+   */
+  costFlags: number
+  /**
+   * The data limit of the plan of the connection in megabytes, only reported on Windows.
+   *
+   * `undefined` without a limit, a metered connection may have none.
+   */
+  dataLimitMb?: number
+  /**
+   * Airplane mode is on, e.g. to explain an `Unsatisfied` status by it rather than by the coverage.
+   *
+   * Only reported on Windows, `false` on the other platforms and on the machines without radios.
+   */
+  airplaneMode: boolean
+}
+
+export interface NetworkInterface {
+  /** The BSD name of the interface, e.g. `en0` */
+  name: string
+  /** The system interface index */
+  index: number
+  type: NwInterfaceType
 }
 
 /** A network path status indicates if there is a usable route available upon which to send and receive data. */
@@ -35,3 +507,55 @@ export type NetworkStatus = /** nw_path_status_invalid The path is not valid */
 'Satisfiable'|
 /** Reserved for future use */
 'Unknown';
+
+/** The stable numeric code of a `NetworkStatus`, a compact representation for storage and IPC. */
+export declare function networkStatusCode(status: NetworkStatus): number
+
+/** The `NetworkStatus` of a code returned by `networkStatusCode`. */
+export declare function networkStatusFromCode(code: number): NetworkStatus
+
+/** Interface types represent the underlying media for a network link, such as Wi-Fi or Cellular. */
+export declare enum NwInterfaceType {
+  /** nw_interface_type_other A virtual or otherwise unknown interface type */
+  Other = 0,
+  /** nw_interface_type_wifi A Wi-Fi link */
+  Wifi = 1,
+  /** nw_interface_type_wifi A Cellular link */
+  Cellular = 2,
+  /** nw_interface_type_wired A Wired Ethernet link */
+  Wired = 3,
+  /** nw_interface_type_loopback A Loopback link */
+  Loopback = 4
+}
+
+/**
+ * Whether the machine runs on battery, to combine with the cost of the network in a data saving decision.
+ *
+ * It's not part of the `NetworkInfo`, no change event is emitted when it changes.
+ */
+export interface PowerState {
+  /** The AC power is disconnected, `false` when it's unknown */
+  onBattery: boolean
+  /** The remaining charge from 0 to 100, `undefined` without a battery or when it's unknown */
+  batteryPercent?: number
+}
+
+/** The traffic of the physical interfaces between the last two samples. */
+export interface Throughput {
+  /** Received bytes per second */
+  rxBps: number
+  /** Sent bytes per second */
+  txBps: number
+}
+
+/** Why a path is not satisfied. */
+export type UnsatisfiedReason = /** nw_path_unsatisfied_reason_not_available No reason is given */
+'NotAvailable'|
+/** nw_path_unsatisfied_reason_cellular_denied The user has disabled cellular */
+'CellularDenied'|
+/** nw_path_unsatisfied_reason_wifi_denied The user has disabled Wi-Fi */
+'WifiDenied'|
+/** nw_path_unsatisfied_reason_local_network_denied The user has disabled local network access */
+'LocalNetworkDenied'|
+/** nw_path_unsatisfied_reason_vpn_inactive A required VPN is not active| reported since macOS 14 */
+'VpnInactive';
@@ -362,4 +362,21 @@ if (!nativeBinding) {
 }
 
 module.exports.InternetMonitor = nativeBinding.InternetMonitor
+module.exports.BACKEND_TIMEOUT = nativeBinding.BACKEND_TIMEOUT
+module.exports.BackoffEventKind = nativeBinding.BackoffEventKind
+module.exports.ChangeFilter = nativeBinding.ChangeFilter
+module.exports.ConnectionClass = nativeBinding.ConnectionClass
+module.exports.ConnectionCostFlag = nativeBinding.ConnectionCostFlag
+module.exports.ConnectivityLevel = nativeBinding.ConnectivityLevel
+module.exports.decodeConnectivityFlags = nativeBinding.decodeConnectivityFlags
+module.exports.getGlobalNetworkInfo = nativeBinding.getGlobalNetworkInfo
+module.exports.IpStackType = nativeBinding.IpStackType
+module.exports.mergeNetworkInfo = nativeBinding.mergeNetworkInfo
+module.exports.meteredStatusFromCostFlags = nativeBinding.meteredStatusFromCostFlags
+module.exports.MonitorEvent = nativeBinding.MonitorEvent
+module.exports.NetworkCategory = nativeBinding.NetworkCategory
 module.exports.NetworkStatus = nativeBinding.NetworkStatus
+module.exports.networkStatusCode = nativeBinding.networkStatusCode
+module.exports.networkStatusFromCode = nativeBinding.networkStatusFromCode
+module.exports.NwInterfaceType = nativeBinding.NwInterfaceType
+module.exports.UnsatisfiedReason = nativeBinding.UnsatisfiedReason
@@ -0,0 +1,64 @@
+#[cfg(feature = "napi")]
+use napi_derive::napi;
+
+use crate::{IpStackType, NetworkInfo, NetworkStatus};
+
+#[cfg_attr(feature = "napi", napi)]
+/// Merge the `NetworkInfo` of several monitors into the best available one, e.g. a Wi-Fi and a cellular monitor.
+///
+/// The best path is the one with the highest status, `Satisfied` then `Satisfiable` then `Unsatisfied`,
+/// the earlier one in `infos` wins a tie. The capabilities of the paths sharing its status are unioned:
+/// `hasIpv4`, `hasIpv6`, `hasDns`, the `uses*` flags, `interfaces` and `gateways`.
+/// Everything else, e.g. `isExpensive` or `primaryInterface`, is the one of the best path.
+///
+/// `null` when `infos` is empty.
+pub fn merge_network_info(infos: Vec<NetworkInfo>) -> Option<NetworkInfo> {
+  merge(infos.iter())
+}
+
+pub(crate) fn merge<'a>(
+  infos: impl Iterator<Item = &'a NetworkInfo> + Clone,
+) -> Option<NetworkInfo> {
+  // `max_by_key` keeps the last of the equal elements, reverse the rank of the position to keep the first
+  let best = infos
+    .clone()
+    .enumerate()
+    .max_by_key(|(position, info)| (rank(info.status), std::cmp::Reverse(*position)))?
+    .1;
+  let mut merged = best.clone();
+  for info in infos.filter(|info| rank(info.status) == rank(best.status)) {
+    merged.has_ipv4 |= info.has_ipv4;
+    merged.has_ipv6 |= info.has_ipv6;
+    merged.has_dns |= info.has_dns;
+    merged.uses_wifi |= info.uses_wifi;
+    merged.uses_cellular |= info.uses_cellular;
+    merged.uses_wired |= info.uses_wired;
+    merged.uses_loopback |= info.uses_loopback;
+    for interface in &info.interfaces {
+      if !merged
+        .interfaces
+        .iter()
+        .any(|merged| merged.name == interface.name)
+      {
+        merged.interfaces.push(interface.clone());
+      }
+    }
+    for gateway in &info.gateways {
+      if !merged.gateways.contains(gateway) {
+        merged.gateways.push(gateway.clone());
+      }
+    }
+  }
+  merged.stack = IpStackType::new(merged.has_ipv4, merged.has_ipv6);
+  Some(merged)
+}
+
+/// How usable a path is, `Invalid` and the reserved `Unknown` are the least.
+fn rank(status: NetworkStatus) -> u8 {
+  match status {
+    NetworkStatus::Satisfied => 3,
+    NetworkStatus::Satisfiable => 2,
+    NetworkStatus::Unsatisfied => 1,
+    NetworkStatus::Invalid | NetworkStatus::Unknown => 0,
+  }
+}
@@ -0,0 +1,180 @@
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{ChangeHandler, NetworkInfo, NetworkStatus};
+
+const DEFAULT_BASE_MS: u32 = 1_000;
+const DEFAULT_MAX_MS: u32 = 60_000;
+
+#[napi(object)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BackoffOptions {
+  /// The delay before the first `RetrySuggested` event, 1 second by default.
+  pub base_ms: Option<u32>,
+  /// The delay doubles after every `RetrySuggested` event up to this value, 1 minute by default.
+  pub max_ms: Option<u32>,
+}
+
+#[napi(string_enum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackoffEventKind {
+  /// The network is still down, it's a good time to retry
+  RetrySuggested,
+  /// The network is back after being down
+  Reconnected,
+}
+
+#[napi(object, object_from_js = false)]
+#[derive(Debug, Clone)]
+pub struct BackoffEvent {
+  pub kind: BackoffEventKind,
+  /// The number of `RetrySuggested` events since the network went down
+  pub attempt: u32,
+  /// How long the monitor waited before this `RetrySuggested` event, 0 for `Reconnected`
+  pub delay_ms: u32,
+  pub info: NetworkInfo,
+}
+
+type EventHandler = Box<dyn Fn(BackoffEvent) + Send + Sync>;
+
+struct BackoffState {
+  info: Option<NetworkInfo>,
+  offline: bool,
+  attempt: u32,
+  /// Bumped on every online/offline transition, so a sleeping timer knows its outage is over
+  generation: u64,
+  shutdown: bool,
+}
+
+struct Shared {
+  state: Mutex<BackoffState>,
+  wakeup: Condvar,
+  base: Duration,
+  max: Duration,
+  emit: EventHandler,
+}
+
+/// Turns the snapshots of a monitor into `RetrySuggested` and `Reconnected` events.
+///
+/// The timer runs on its own thread, it's stopped and joined when the `Backoff` is dropped.
+struct Backoff {
+  shared: Arc<Shared>,
+  timer: Option<JoinHandle<()>>,
+}
+
+impl Backoff {
+  fn new(options: BackoffOptions, emit: EventHandler) -> Self {
+    let base_ms = options.base_ms.unwrap_or(DEFAULT_BASE_MS).max(1);
+    let max_ms = options.max_ms.unwrap_or(DEFAULT_MAX_MS).max(base_ms);
+    let shared = Arc::new(Shared {
+      state: Mutex::new(BackoffState {
+        info: None,
+        offline: false,
+        attempt: 0,
+        generation: 0,
+        shutdown: false,
+      }),
+      wakeup: Condvar::new(),
+      base: Duration::from_millis(base_ms.into()),
+      max: Duration::from_millis(max_ms.into()),
+      emit,
+    });
+    let timer_shared = shared.clone();
+    let timer = std::thread::spawn(move || run_timer(&timer_shared));
+    Self {
+      shared,
+      timer: Some(timer),
+    }
+  }
+
+  fn update(&self, info: NetworkInfo) {
+    let online = matches!(info.status, NetworkStatus::Satisfied);
+    let mut state = self.shared.state.lock().unwrap();
+    let reconnected = state.offline && online;
+    let attempt = state.attempt;
+    if state.offline == online {
+      state.offline = !online;
+      state.attempt = 0;
+      state.generation += 1;
+    }
+    state.info = Some(info.clone());
+    drop(state);
+    self.shared.wakeup.notify_all();
+
+    if reconnected {
+      (self.shared.emit)(BackoffEvent {
+        kind: BackoffEventKind::Reconnected,
+        attempt,
+        delay_ms: 0,
+        info,
+      });
+    }
+  }
+}
+
+impl Drop for Backoff {
+  fn drop(&mut self) {
+    self.shared.state.lock().unwrap().shutdown = true;
+    self.shared.wakeup.notify_all();
+    if let Some(timer) = self.timer.take() {
+      let _ = timer.join();
+    }
+  }
+}
+
+fn run_timer(shared: &Shared) {
+  let mut state = shared.state.lock().unwrap();
+  loop {
+    if state.shutdown {
+      return;
+    }
+    if !state.offline {
+      state = shared.wakeup.wait(state).unwrap();
+      continue;
+    }
+
+    let generation = state.generation;
+    let delay = shared
+      .base
+      .saturating_mul(2u32.saturating_pow(state.attempt))
+      .min(shared.max);
+    let deadline = Instant::now() + delay;
+    loop {
+      let now = Instant::now();
+      if state.shutdown || state.generation != generation || now >= deadline {
+        break;
+      }
+      state = shared.wakeup.wait_timeout(state, deadline - now).unwrap().0;
+    }
+    if state.shutdown || state.generation != generation {
+      continue;
+    }
+
+    state.attempt += 1;
+    let event = BackoffEvent {
+      kind: BackoffEventKind::RetrySuggested,
+      attempt: state.attempt,
+      delay_ms: delay.as_millis() as u32,
+      info: state.info.clone().expect("offline without a snapshot"),
+    };
+    // don't block `update` while the event is queued
+    drop(state);
+    (shared.emit)(event);
+    state = shared.state.lock().unwrap();
+  }
+}
+
+/// Build the `ChangeHandler` behind `start_with_backoff`, the backoff timer lives as long as the handler.
+pub(crate) fn change_handler(
+  options: BackoffOptions,
+  on_event: Function<BackoffEvent, ()>,
+  weak: bool,
+) -> Result<ChangeHandler> {
+  let emit: EventHandler = crate::callback(on_event, weak)?;
+  let backoff = Backoff::new(options, emit);
+  Ok(Box::new(move |info| backoff.update(info)))
+}
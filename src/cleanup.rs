@@ -0,0 +1,47 @@
+use napi::{check_status, sys, Env, Result};
+
+type CleanupHook = Option<Box<dyn FnOnce()>>;
+
+/// A cleanup hook of the environment, removed when it's dropped before the environment is torn down.
+///
+/// Drop it on the JavaScript thread, e.g. from `close` or the finalizer of the class holding it.
+pub(crate) struct EnvCleanupHook {
+  env: sys::napi_env,
+  /// `None` once the environment ran the hook
+  hook: *mut CleanupHook,
+}
+
+impl EnvCleanupHook {
+  pub(crate) fn new(env: &Env, hook: impl FnOnce() + 'static) -> Result<Self> {
+    let hook: CleanupHook = Some(Box::new(hook));
+    let hook = Box::into_raw(Box::new(hook));
+    let status =
+      unsafe { sys::napi_add_env_cleanup_hook(env.raw(), Some(run_env_cleanup_hook), hook.cast()) };
+    if let Err(err) = check_status!(status) {
+      drop(unsafe { Box::from_raw(hook) });
+      return Err(err);
+    }
+    Ok(Self {
+      env: env.raw(),
+      hook,
+    })
+  }
+}
+
+impl Drop for EnvCleanupHook {
+  fn drop(&mut self) {
+    // SAFETY: the box is only freed here, the environment runs the hook on this thread as well
+    unsafe {
+      if (*self.hook).is_some() {
+        sys::napi_remove_env_cleanup_hook(self.env, Some(run_env_cleanup_hook), self.hook.cast());
+      }
+      drop(Box::from_raw(self.hook));
+    }
+  }
+}
+
+unsafe extern "C" fn run_env_cleanup_hook(hook: *mut core::ffi::c_void) {
+  if let Some(hook) = unsafe { &mut *hook.cast::<CleanupHook>() }.take() {
+    hook();
+  }
+}
@@ -0,0 +1,84 @@
+#[cfg(feature = "napi")]
+use napi_derive::napi;
+use objc2::rc::autoreleasepool;
+use objc2_core_wlan::{CWInterface, CWWiFiClient};
+
+#[cfg_attr(feature = "napi", napi(string_enum))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The 802.11 PHY mode of the Wi-Fi association.
+pub enum WifiPhyMode {
+  /// kCWPHYMode11a
+  Ieee80211a,
+  /// kCWPHYMode11b
+  Ieee80211b,
+  /// kCWPHYMode11g
+  Ieee80211g,
+  /// kCWPHYMode11n Wi-Fi 4
+  Ieee80211n,
+  /// kCWPHYMode11ac Wi-Fi 5
+  Ieee80211ac,
+  /// kCWPHYMode11ax Wi-Fi 6
+  Ieee80211ax,
+  /// kCWPHYModeNone or a mode this version doesn't know about
+  Unknown,
+}
+
+impl From<isize> for WifiPhyMode {
+  fn from(mode: isize) -> Self {
+    match mode {
+      1 => WifiPhyMode::Ieee80211a,
+      2 => WifiPhyMode::Ieee80211b,
+      3 => WifiPhyMode::Ieee80211g,
+      4 => WifiPhyMode::Ieee80211n,
+      5 => WifiPhyMode::Ieee80211ac,
+      6 => WifiPhyMode::Ieee80211ax,
+      _ => WifiPhyMode::Unknown,
+    }
+  }
+}
+
+#[cfg_attr(feature = "napi", napi(object, object_from_js = false))]
+#[derive(Debug, Clone)]
+/// The current Wi-Fi association of the default CoreWLAN interface.
+pub struct WifiInfo {
+  /// The name of the network.
+  ///
+  /// Since macOS 14 CoreWLAN hides it from the processes without the Location Services permission,
+  /// it's `undefined` then.
+  pub ssid: Option<String>,
+  /// The MAC address of the access point, hidden the same way as `ssid`
+  pub bssid: Option<String>,
+  /// The received signal strength in dBm, e.g. -50 is excellent and -80 is weak
+  pub rssi: i32,
+  /// The noise in dBm
+  pub noise: i32,
+  pub channel: u32,
+  pub phy_mode: WifiPhyMode,
+}
+
+#[cfg_attr(feature = "napi", napi)]
+/// The SSID, BSSID, RSSI, channel and PHY mode of the current Wi-Fi association, e.g. to warn about a weak Wi-Fi.
+///
+/// `null` when the machine has no Wi-Fi interface or it's not associated. Only available on macOS.
+pub fn get_wifi_info() -> Option<WifiInfo> {
+  // CoreWLAN returns autoreleased objects, drain them here instead of leaking them into a pool
+  // which is never drained in a long running process
+  autoreleasepool(|_| {
+    let client = unsafe { CWWiFiClient::sharedWiFiClient() };
+    let interface = unsafe { client.interface() }?;
+    wifi_info(&interface)
+  })
+}
+
+fn wifi_info(interface: &CWInterface) -> Option<WifiInfo> {
+  // the channel is nil while the interface is not associated, unlike the SSID it's not hidden
+  let channel = unsafe { interface.wlanChannel() }?;
+  Some(WifiInfo {
+    ssid: unsafe { interface.ssid() }.map(|ssid| ssid.to_string()),
+    bssid: unsafe { interface.bssid() }.map(|bssid| bssid.to_string()),
+    rssi: unsafe { interface.rssiValue() } as i32,
+    noise: unsafe { interface.noiseMeasurement() } as i32,
+    channel: unsafe { channel.channelNumber() } as u32,
+    phy_mode: unsafe { interface.activePHYMode() }.0.into(),
+  })
+}
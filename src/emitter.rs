@@ -0,0 +1,177 @@
+use std::sync::{Arc, Mutex};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{ChangeHandler, ErrorHandler, MeteredStatus, NetworkInfo, NetworkStatus};
+
+#[napi(string_enum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The named events of `on` and `off`.
+pub enum MonitorEvent {
+  /// Every new `NetworkInfo`, the same as the callback of `start`
+  #[napi(value = "change")]
+  Change,
+  /// The status became `Satisfied`, with the new `NetworkInfo`
+  #[napi(value = "online")]
+  Online,
+  /// The status is no longer `Satisfied`, with the new `NetworkInfo`
+  #[napi(value = "offline")]
+  Offline,
+  /// `isExpensive` or `isLowDataMode` flipped, with the new `MeteredStatus`
+  #[napi(value = "metered")]
+  Metered,
+  /// The platform backend failed to read the network state after an event, with an `Error`
+  #[napi(value = "error")]
+  Error,
+}
+
+/// The argument of a listener, its type depends on the event.
+pub enum EventPayload {
+  Info(NetworkInfo),
+  Metered(MeteredStatus),
+  Error(String),
+}
+
+impl ToNapiValue for EventPayload {
+  unsafe fn to_napi_value(env: napi::sys::napi_env, val: Self) -> Result<napi::sys::napi_value> {
+    match val {
+      EventPayload::Info(info) => NetworkInfo::to_napi_value(env, info),
+      EventPayload::Metered(status) => MeteredStatus::to_napi_value(env, status),
+      EventPayload::Error(message) => {
+        Ok(JsError::from(Error::new(Status::GenericFailure, message)).into_value(env))
+      }
+    }
+  }
+}
+
+type Emit = Box<dyn Fn(EventPayload) + Send + Sync>;
+
+struct Listener {
+  id: u32,
+  event: MonitorEvent,
+  emit: Emit,
+}
+
+/// The listeners added by `on`, they share a single `ChangeHandler` installed on the monitor.
+#[derive(Default)]
+pub(crate) struct Emitter {
+  listeners: Arc<Mutex<Vec<Listener>>>,
+  /// The JavaScript functions of the listeners, only touched on the JavaScript thread to find them again in `off`
+  functions: Vec<(u32, MonitorEvent, FunctionRef<EventPayload, ()>)>,
+  next_id: u32,
+}
+
+impl Emitter {
+  pub(crate) fn on(
+    &mut self,
+    event: MonitorEvent,
+    listener: Function<EventPayload, ()>,
+    weak: bool,
+  ) -> Result<()> {
+    let function = listener.create_ref()?;
+    let emit: Emit = crate::callback(listener, weak)?;
+    let id = self.next_id;
+    self.next_id += 1;
+    self.functions.push((id, event, function));
+    self
+      .listeners
+      .lock()
+      .unwrap()
+      .push(Listener { id, event, emit });
+    Ok(())
+  }
+
+  /// Remove the latest listener added with the same function for `event`, like `EventEmitter.off`.
+  ///
+  /// Returns whether a listener was removed.
+  pub(crate) fn off(
+    &mut self,
+    env: &Env,
+    event: MonitorEvent,
+    listener: Function<EventPayload, ()>,
+  ) -> Result<bool> {
+    let mut found = None;
+    for (index, (_, listener_event, function)) in self.functions.iter().enumerate().rev() {
+      if *listener_event == event && env.strict_equals(function.borrow_back(env)?, listener)? {
+        found = Some(index);
+        break;
+      }
+    }
+    if let Some(index) = found {
+      let (id, ..) = self.functions.remove(index);
+      // dropping the threadsafe function releases the listener
+      self
+        .listeners
+        .lock()
+        .unwrap()
+        .retain(|listener| listener.id != id);
+    }
+    Ok(found.is_some())
+  }
+
+  pub(crate) fn is_empty(&self) -> bool {
+    self.functions.is_empty()
+  }
+
+  pub(crate) fn clear(&mut self) {
+    self.listeners.lock().unwrap().clear();
+    self.functions.clear();
+  }
+
+  /// Build the `ChangeHandler` dispatching the snapshots to the named events.
+  ///
+  /// `current` is the baseline of the `online`, `offline` and `metered` transitions, without it the first snapshot is.
+  pub(crate) fn change_handler(&self, current: Option<&NetworkInfo>) -> ChangeHandler {
+    let listeners = self.listeners.clone();
+    let last = Mutex::new(current.map(|info| (is_online(info), MeteredStatus::from(info))));
+    Box::new(move |info| {
+      let online = is_online(&info);
+      let metered = MeteredStatus::from(&info);
+      let previous = last.lock().unwrap().replace((online, metered));
+      let listeners = listeners.lock().unwrap();
+      let emit = |event: MonitorEvent, payload: &dyn Fn() -> EventPayload| {
+        for listener in listeners.iter().filter(|listener| listener.event == event) {
+          (listener.emit)(payload());
+        }
+      };
+      emit(MonitorEvent::Change, &|| EventPayload::Info(info.clone()));
+      let Some((was_online, was_metered)) = previous else {
+        return;
+      };
+      if online != was_online {
+        let event = if online {
+          MonitorEvent::Online
+        } else {
+          MonitorEvent::Offline
+        };
+        emit(event, &|| EventPayload::Info(info.clone()));
+      }
+      if metered != was_metered {
+        emit(MonitorEvent::Metered, &|| EventPayload::Metered(metered));
+      }
+    })
+  }
+
+  /// Build the `ErrorHandler` dispatching the backend failures to the `error` listeners.
+  ///
+  /// Only the Windows backend has failures to report, the other ones read the state pushed by the system.
+  #[cfg_attr(any(not(target_os = "windows"), feature = "mock"), allow(dead_code))]
+  pub(crate) fn error_handler(&self) -> ErrorHandler {
+    let listeners = self.listeners.clone();
+    Box::new(move |message| {
+      for listener in listeners
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|listener| listener.event == MonitorEvent::Error)
+      {
+        (listener.emit)(EventPayload::Error(message.clone()));
+      }
+    })
+  }
+}
+
+fn is_online(info: &NetworkInfo) -> bool {
+  matches!(info.status, NetworkStatus::Satisfied)
+}
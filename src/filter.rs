@@ -0,0 +1,105 @@
+use std::sync::Mutex;
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::{ChangeHandler, MeteredStatus, NWInterfaceType, NetworkInfo};
+
+#[napi]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The flags of `startFiltered`, combine them with `|`.
+pub enum ChangeFilter {
+  /// The `status` changed
+  OnStatusChange = 1,
+  /// `isExpensive` or `isLowDataMode` flipped
+  OnMeteredChange = 2,
+  /// The interface carrying the traffic changed, e.g. Wi-Fi to Ethernet or a VPN came up
+  OnInterfaceChange = 4,
+  /// Every snapshot, the same as `start`
+  OnAny = 8,
+}
+
+const ALL_FLAGS: u32 = ChangeFilter::OnStatusChange as u32
+  | ChangeFilter::OnMeteredChange as u32
+  | ChangeFilter::OnInterfaceChange as u32
+  | ChangeFilter::OnAny as u32;
+
+/// Build the `ChangeHandler` behind `startFiltered`, the snapshots which don't match `filter` never reach the threadsafe function.
+///
+/// Without a `current` snapshot the first one received is the baseline and is always reported.
+pub(crate) fn change_handler(
+  filter: u32,
+  current: Option<&NetworkInfo>,
+  on_update: Function<NetworkInfo, ()>,
+  weak: bool,
+) -> Result<ChangeHandler> {
+  if filter == 0 || filter & !ALL_FLAGS != 0 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("{filter} is not a combination of ChangeFilter flags"),
+    ));
+  }
+  let emit: ChangeHandler = crate::callback(on_update, weak)?;
+  let last = Mutex::new(current.cloned());
+  Ok(Box::new(move |info| {
+    let previous = last.lock().unwrap().replace(info.clone());
+    if previous.is_none_or(|previous| matches(filter, &previous, &info)) {
+      emit(info);
+    }
+  }))
+}
+
+fn matches(filter: u32, previous: &NetworkInfo, info: &NetworkInfo) -> bool {
+  let has = |flag: ChangeFilter| filter & flag as u32 != 0;
+  has(ChangeFilter::OnAny)
+    || (has(ChangeFilter::OnStatusChange) && previous.status != info.status)
+    || (has(ChangeFilter::OnMeteredChange)
+      && MeteredStatus::from(previous) != MeteredStatus::from(info))
+    || (has(ChangeFilter::OnInterfaceChange)
+      && (previous.interface_type != info.interface_type
+        || previous
+          .primary_interface
+          .as_ref()
+          .map(|interface| &interface.name)
+          != info
+            .primary_interface
+            .as_ref()
+            .map(|interface| &interface.name)))
+}
+
+/// The filter of the monitors created with `newWithType`: a snapshot is reported while `interface_type` carries the traffic,
+/// and once more when the traffic moves away from it.
+///
+/// macOS filters natively, see `NWPathMonitor.newWithType`.
+#[cfg_attr(any(target_os = "macos", target_os = "ios"), allow(dead_code))]
+pub(crate) struct InterfaceTypeFilter {
+  interface_type: NWInterfaceType,
+  last: Mutex<Option<NWInterfaceType>>,
+}
+
+impl InterfaceTypeFilter {
+  pub(crate) fn new(interface_type: NWInterfaceType, current: &NetworkInfo) -> Self {
+    Self {
+      interface_type,
+      last: Mutex::new(current.interface_type),
+    }
+  }
+
+  pub(crate) fn matches(&self, info: &NetworkInfo) -> bool {
+    let previous = std::mem::replace(&mut *self.last.lock().unwrap(), info.interface_type);
+    info.interface_type == Some(self.interface_type) || previous == Some(self.interface_type)
+  }
+
+  /// Drop the snapshots which don't match before they reach `handler`.
+  #[cfg_attr(
+    any(feature = "mock", target_os = "macos", target_os = "ios"),
+    allow(dead_code)
+  )]
+  pub(crate) fn watch(self, handler: ChangeHandler) -> ChangeHandler {
+    Box::new(move |info| {
+      if self.matches(&info) {
+        handler(info);
+      }
+    })
+  }
+}
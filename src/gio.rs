@@ -0,0 +1,805 @@
+// The `gio` feature replaces the NetworkManager backend with GLib's `GNetworkMonitor`.
+//
+// gio is available almost everywhere libnm is not, e.g. minimal containers, but it only knows the
+// connectivity and the metered state: the interfaces, the addresses and the saved networks are not reported.
+
+#[cfg(feature = "napi")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "napi")]
+use std::sync::Arc;
+use std::sync::{mpsc, Mutex};
+
+use crate::native;
+use crate::NetworkInfo;
+use crate::NetworkStatus;
+#[cfg(feature = "napi")]
+use crate::{
+  emitter::{Emitter, EventPayload},
+  filter::InterfaceTypeFilter,
+  history::{History, NetworkHistoryEntry},
+  ready::Ready,
+  throughput::{Throughput, ThroughputSampler},
+  BackoffEvent, BackoffOptions, ChangeHandler, ConnectionClass, CreateOptions, EnvCleanupHook,
+  InterfaceNetworkInfo, KnownNetwork, MeteredStatus, MonitorEvent, NWInterfaceType,
+  NetworkCategory, PowerState,
+};
+#[cfg(feature = "napi")]
+use napi::bindgen_prelude::*;
+#[cfg(feature = "napi")]
+use napi::threadsafe_function::{
+  ThreadsafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+};
+#[cfg(feature = "napi")]
+use napi::JsObject;
+#[cfg(feature = "napi")]
+use napi_derive::napi;
+
+#[cfg(feature = "napi")]
+/// `network-changed` covers the routing changes, the connectivity and the metered state have their own notifications.
+const SIGNAL_NAMES: [&std::ffi::CStr; 3] = [
+  c"network-changed",
+  c"notify::connectivity",
+  c"notify::network-metered",
+];
+
+#[cfg(feature = "napi")]
+/// State owned by a single `InternetMonitor`, handed to the `GNetworkMonitor` signals as `user_data`.
+struct MonitorContext {
+  network_info: Mutex<NetworkInfo>,
+  handler: Mutex<Option<ChangeHandler>>,
+}
+
+/// The process wide default `GNetworkMonitor`, it's never released.
+#[derive(Clone, Copy)]
+pub struct DefaultMonitor {
+  monitor: *mut ffi::GNetworkMonitor,
+  /// The main context of the thread which created the monitor, it's never released either
+  main_context: *mut ffi::GMainContext,
+}
+
+// SAFETY: GObject signals can be connected and disconnected from any thread, the monitor emits them on `main_context`
+// and it's only read on the thread owning it, by the signal handlers and by `invoke`.
+unsafe impl Send for DefaultMonitor {}
+
+impl DefaultMonitor {
+  /// Run `read` on the main loop thread and wait for it, the monitor updates its state there.
+  fn invoke<T: Send + 'static>(
+    self,
+    read: impl FnOnce(*mut ffi::GNetworkMonitor) -> T + Send + 'static,
+  ) -> native::Result<T> {
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let job: Job = Box::new(move || {
+      // capture the whole `DefaultMonitor`, the raw pointer alone isn't `Send`
+      let monitor = self;
+      let _ = sender.send(read(monitor.monitor));
+    });
+    unsafe {
+      ffi::g_main_context_invoke_full(
+        self.main_context,
+        ffi::G_PRIORITY_DEFAULT,
+        run_job,
+        Box::into_raw(Box::new(Some(job))).cast(),
+        Some(drop_job),
+      )
+    };
+    receiver
+      .recv()
+      .map_err(|_| native::Error::new("The GLib main loop thread is not running"))
+  }
+}
+
+/// A closure run by `g_main_context_invoke_full`, taken by `run_job` and freed by `drop_job`.
+type Job = Box<dyn FnOnce() + Send>;
+
+extern "C" fn run_job(job: *mut core::ffi::c_void) -> ffi::gboolean {
+  // SAFETY: `job` was created by `invoke` and is alive until `drop_job`
+  if let Some(job) = unsafe { &mut *job.cast::<Option<Job>>() }.take() {
+    job();
+  }
+  // G_SOURCE_REMOVE
+  0
+}
+
+extern "C" fn drop_job(job: *mut core::ffi::c_void) {
+  // SAFETY: `job` was created by `Box::into_raw` in `invoke`
+  drop(unsafe { Box::from_raw(job.cast::<Option<Job>>()) });
+}
+
+static DEFAULT_MONITOR: Mutex<Option<DefaultMonitor>> = Mutex::new(None);
+
+/// Create the default `GNetworkMonitor` on a dedicated thread which runs its main context,
+/// the JavaScript thread doesn't run a GLib main loop to dispatch the signals.
+fn default_monitor() -> native::Result<DefaultMonitor> {
+  let mut default_monitor = DEFAULT_MONITOR.lock().unwrap();
+  if let Some(monitor) = *default_monitor {
+    return Ok(monitor);
+  }
+  let (sender, receiver) = mpsc::sync_channel(1);
+  std::thread::Builder::new()
+    .name("network-change-gio".to_owned())
+    .spawn(move || unsafe {
+      let main_context = ffi::g_main_context_new();
+      ffi::g_main_context_push_thread_default(main_context);
+      // the monitor emits its signals on the thread default main context it's created with
+      let _ = sender.send(DefaultMonitor {
+        monitor: ffi::g_network_monitor_get_default(),
+        main_context,
+      });
+      ffi::g_main_loop_run(ffi::g_main_loop_new(main_context, 0));
+    })
+    .map_err(|err| {
+      native::Error::new(format!("Failed to spawn the GLib main loop thread: {err}"))
+    })?;
+  let monitor = receiver
+    .recv()
+    .map_err(|_| native::Error::new("Error initializing GNetworkMonitor."))?;
+  *default_monitor = Some(monitor);
+  Ok(monitor)
+}
+
+#[cfg(feature = "napi")]
+pub struct CreateInternetMonitor {
+  timeout_ms: Option<u32>,
+  history_capacity: Option<u32>,
+}
+
+#[cfg(feature = "napi")]
+impl Task for CreateInternetMonitor {
+  type Output = DefaultMonitor;
+  type JsValue = InternetMonitor;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    crate::init_with_timeout(self.timeout_ms, || Ok(default_monitor()?))
+  }
+
+  fn resolve(&mut self, env: Env, monitor: Self::Output) -> Result<Self::JsValue> {
+    InternetMonitor::from_monitor(env, monitor)
+  }
+
+  fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+    crate::reject_create(&env, err)
+  }
+}
+
+#[cfg(feature = "napi")]
+#[napi]
+pub struct InternetMonitor {
+  monitor: DefaultMonitor,
+  context: Arc<MonitorContext>,
+  /// Releases the callback when the environment is torn down first, removed by `close` or the GC
+  cleanup_hook: Option<EnvCleanupHook>,
+  signal_ids: Vec<ffi::gulong>,
+  weak_by_default: bool,
+  /// Set by `close`, every method throws afterwards
+  is_disposed: AtomicBool,
+  /// The sampling thread of `startThroughput`
+  throughput_sampler: Option<ThroughputSampler>,
+  /// The listeners of `on`
+  emitter: Emitter,
+  history: History,
+  /// The pending promises of `connected`
+  ready: Ready,
+  /// Set by `newWithType`, the callbacks only see the snapshots of this interface type
+  interface_type: Option<NWInterfaceType>,
+}
+
+#[cfg(feature = "napi")]
+impl Drop for InternetMonitor {
+  fn drop(&mut self) {
+    self.unsubscribe();
+  }
+}
+
+#[cfg(feature = "napi")]
+#[napi]
+impl InternetMonitor {
+  #[napi(constructor)]
+  /// `timeoutMs` is ignored by the constructor, it doesn't time out.
+  pub fn new(env: Env, options: Option<CreateOptions>) -> Result<Self> {
+    let options = options.unwrap_or_default();
+    let mut monitor = Self::from_monitor(env, default_monitor()?)?;
+    monitor.history = History::new(options.history_capacity);
+    Ok(monitor)
+  }
+
+  #[napi(factory)]
+  /// Create an InternetMonitor which only reports the changes while `interfaceType` carries the traffic,
+  /// and the one moving the traffic away from it, e.g. to only follow the Wi-Fi.
+  ///
+  /// The same as `NWPathMonitor.newWithType` on macOS. `current` and `history` still describe every interface.
+  pub fn new_with_type(env: Env, interface_type: NWInterfaceType) -> Result<Self> {
+    let mut monitor = Self::new(env, None)?;
+    monitor.interface_type = Some(interface_type);
+    Ok(monitor)
+  }
+
+  #[napi(factory)]
+  /// Create an InternetMonitor whose `start` uses a weak reference, it will not keep the Node.js alive.
+  ///
+  /// Use `startStrong` to opt out for a single listener.
+  pub fn new_weak_by_default(env: Env) -> Result<Self> {
+    let mut monitor = Self::new(env, None)?;
+    monitor.weak_by_default = true;
+    Ok(monitor)
+  }
+
+  #[napi(ts_return_type = "Promise<InternetMonitor>")]
+  /// Create the InternetMonitor on the libuv thread pool, so creating the GNetworkMonitor won't block the JavaScript thread.
+  pub fn create(options: Option<CreateOptions>) -> AsyncTask<CreateInternetMonitor> {
+    let options = options.unwrap_or_default();
+    AsyncTask::new(CreateInternetMonitor {
+      timeout_ms: options.timeout_ms,
+      history_capacity: options.history_capacity,
+    })
+  }
+
+  fn from_monitor(env: Env, monitor: DefaultMonitor) -> Result<Self> {
+    let context = Arc::new(MonitorContext {
+      network_info: Mutex::new(monitor.invoke(get_network_info)?),
+      handler: Mutex::new(None),
+    });
+    // The environment (Electron renderer, worker thread) may be torn down before the monitor is dropped,
+    // the signal handlers must not call into its threadsafe functions after that.
+    let cleanup_hook = EnvCleanupHook::new(&env, {
+      let context = context.clone();
+      move || {
+        context.handler.lock().unwrap().take();
+      }
+    })?;
+
+    Ok(Self {
+      monitor,
+      cleanup_hook: Some(cleanup_hook),
+      context,
+      signal_ids: Vec::new(),
+      weak_by_default: false,
+      is_disposed: AtomicBool::new(false),
+      throughput_sampler: None,
+      emitter: Emitter::default(),
+      history: History::default(),
+      ready: Ready::default(),
+      interface_type: None,
+    })
+  }
+
+  #[napi]
+  /// The last snapshots delivered to the callback, oldest first, `historyCapacity` of them at most.
+  ///
+  /// They are recorded while the monitor is started, whichever callback it's started with.
+  pub fn history(&self) -> Result<Vec<NetworkHistoryEntry>> {
+    self.check_open()?;
+    Ok(self.history.entries())
+  }
+
+  #[napi]
+  pub fn current(&self) -> Result<NetworkInfo> {
+    self.check_open()?;
+    Ok(self.context.network_info.lock().unwrap().clone())
+  }
+
+  #[napi]
+  /// Evaluate every field again instead of waiting for the next event, and return the new snapshot,
+  /// e.g. after a VPN was connected by another library and the events are late.
+  ///
+  /// The callback passed to `start` is not called.
+  pub fn refresh(&self) -> Result<NetworkInfo> {
+    self.check_open()?;
+    let info = self.monitor.invoke(get_network_info)?;
+    *self.context.network_info.lock().unwrap() = info.clone();
+    Ok(info)
+  }
+
+  #[napi]
+  /// Always empty, GNetworkMonitor doesn't describe the interfaces.
+  pub fn current_all(&self) -> Result<Vec<InterfaceNetworkInfo>> {
+    self.check_open()?;
+    Ok(Vec::new())
+  }
+
+  #[napi]
+  /// The category of the connected network, Windows is the only platform which has one,
+  /// the other platforms always return `Unknown`.
+  pub fn network_category(&self) -> Result<NetworkCategory> {
+    self.check_open()?;
+    Ok(NetworkCategory::Unknown)
+  }
+
+  #[napi]
+  /// Always empty, GNetworkMonitor doesn't know the saved networks.
+  pub fn list_networks(&self) -> Result<Vec<KnownNetwork>> {
+    self.check_open()?;
+    Ok(Vec::new())
+  }
+
+  #[napi]
+  /// A coarse quality tier of the current connection, see `ConnectionClass` for the thresholds.
+  pub fn connection_class(&self) -> Result<ConnectionClass> {
+    self.check_open()?;
+    Ok(ConnectionClass::new(
+      &self.context.network_info.lock().unwrap(),
+    ))
+  }
+
+  #[napi]
+  /// It's safe to do a large download right now: the network is `Satisfied`, not expensive, not in Low Data Mode and not roaming.
+  pub fn can_use_unmetered(&self) -> Result<bool> {
+    self.check_open()?;
+    let network_info = self.context.network_info.lock().unwrap();
+    Ok(network_info.can_use_unmetered())
+  }
+
+  #[napi]
+  /// Whether the machine runs on battery, only reported on Windows for now, `null` on the other platforms.
+  pub fn power_state(&self) -> Result<Option<PowerState>> {
+    self.check_open()?;
+    Ok(None)
+  }
+
+  #[napi]
+  /// Serialize the current `NetworkInfo` to JSON with the schema shared by every platform, for logging and telemetry.
+  pub fn snapshot_json(&self) -> Result<String> {
+    self.check_open()?;
+    crate::snapshot_json(&self.context.network_info.lock().unwrap())
+  }
+
+  #[napi]
+  /// Start the InternetMonitor, it will keep the Node.js alive unless you call stop on it.
+  ///
+  /// Monitors created with `newWeakByDefault` don't keep the Node.js alive, the same as `startWeak`.
+  pub fn start(&mut self, on_update: Function<NetworkInfo, ()>) -> Result<()> {
+    if self.weak_by_default {
+      self.start_weak(on_update)
+    } else {
+      self.start_strong(on_update)
+    }
+  }
+
+  #[napi]
+  /// Start with a strong reference whatever the monitor was created with, it will keep the Node.js alive unless you call stop on it.
+  pub fn start_strong(&mut self, on_update: Function<NetworkInfo, ()>) -> Result<()> {
+    self.check_open()?;
+    let change_handler = Arc::new(
+      on_update
+        .build_threadsafe_function()
+        .callee_handled::<false>()
+        .weak::<false>()
+        .build_callback(ctx_to_path)?,
+    );
+    self.start_inner::<false>(change_handler)
+  }
+
+  #[napi]
+  /// Start the InternetMonitor with weak reference, it will not keep the Node.js alive.
+  pub fn start_weak(&mut self, on_update: Function<NetworkInfo, ()>) -> Result<()> {
+    self.check_open()?;
+    let change_handler = Arc::new(
+      on_update
+        .build_threadsafe_function()
+        .callee_handled::<false>()
+        .weak::<true>()
+        .build_callback(ctx_to_path)?,
+    );
+    self.start_inner::<true>(change_handler)
+  }
+
+  #[napi]
+  /// Emit `RetrySuggested` events with an exponential backoff while the network is down,
+  /// and a single `Reconnected` event once it's back.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn start_with_backoff(
+    &mut self,
+    options: Option<BackoffOptions>,
+    on_event: Function<BackoffEvent, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::backoff::change_handler(options.unwrap_or_default(), on_event, self.weak_by_default)?;
+    // the monitor may already be offline, start counting from now
+    handler(self.current()?);
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onChange` only when `isExpensive` or `isLowDataMode` flips, the connectivity changes are ignored.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_metered_change(&mut self, on_change: Function<MeteredStatus, ()>) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::metered::change_handler(Some(&self.current()?), on_change, self.weak_by_default)?;
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onChange` with the new `canUseUnmetered` when it flips, e.g. to pause the large downloads when the network
+  /// becomes expensive, Low Data Mode is turned on, roaming starts or the connection is lost.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_unmetered_change(&mut self, on_change: Function<bool, ()>) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::unmetered::change_handler(Some(&self.current()?), on_change, self.weak_by_default)?;
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onChange` with the new interface type when the traffic moves to another one, e.g. from Wi-Fi to cellular
+  /// or from Ethernet to Wi-Fi, to reset the connections even though the status stays `Satisfied`.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_primary_interface_change(
+    &mut self,
+    on_change: Function<NWInterfaceType, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::primary::change_handler(Some(&self.current()?), on_change, self.weak_by_default)?;
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onReconnect` with how long the network was offline, in milliseconds, when the status becomes `Satisfied` again,
+  /// e.g. to size the retry windows. Every offline episode starts the timer over.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_reconnect(&mut self, on_reconnect: Function<f64, ()>) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::reconnect::change_handler(Some(&self.current()?), on_reconnect, self.weak_by_default)?;
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onOnline` once the network stayed `Satisfied` for `minStableMs` in a row, any other status in between
+  /// starts the wait over. Unlike the first `Satisfied` update, it's not fired while the connectivity flaps.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_stable_online(
+    &mut self,
+    min_stable_ms: u32,
+    on_online: Function<NetworkInfo, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler = crate::stable::change_handler(min_stable_ms, on_online, self.weak_by_default)?;
+    // the monitor may already be online, start counting from now
+    handler(self.current()?);
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onUpdate` only with the snapshots matching `filter`, a combination of `ChangeFilter` flags,
+  /// the other ones are dropped before they cross into JavaScript.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn start_filtered(
+    &mut self,
+    filter: u32,
+    on_update: Function<NetworkInfo, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler = crate::filter::change_handler(
+      filter,
+      Some(&self.current()?),
+      on_update,
+      self.weak_by_default,
+    )?;
+    self.start_with_handler(handler)
+  }
+
+  #[napi(ts_args_type = "event: MonitorEvent, listener: (arg: any) => void")]
+  /// Add a listener for `event`, like `EventEmitter.on`. The `change`, `online` and `offline` listeners
+  /// receive a `NetworkInfo`, the `metered` ones a `MeteredStatus` and the `error` ones an `Error`.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on(&mut self, event: MonitorEvent, listener: Function<EventPayload, ()>) -> Result<()> {
+    self.check_open()?;
+    self.emitter.on(event, listener, self.weak_by_default)?;
+    let handler = self.emitter.change_handler(Some(&self.current()?));
+    self.start_with_handler(handler)
+  }
+
+  #[napi(ts_args_type = "event: MonitorEvent, listener: (arg: any) => void")]
+  /// Remove a listener added by `on`, the monitor is stopped once the last one is removed.
+  pub fn off(
+    &mut self,
+    env: Env,
+    event: MonitorEvent,
+    listener: Function<EventPayload, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    if self.emitter.off(&env, event, listener)? && self.emitter.is_empty() {
+      self.unsubscribe();
+    }
+    Ok(())
+  }
+
+  #[napi(ts_return_type = "Promise<void>")]
+  /// Resolve once the monitor saw a `Satisfied` status, right away when it already did,
+  /// so it can be awaited before every request cheaply.
+  ///
+  /// It never resets, going offline afterwards doesn't make it wait again, listen to `offline` with `on` for that.
+  /// A monitor which is not started is started without a callback, `stop` rejects the pending promises.
+  pub fn connected(&mut self, env: Env) -> Result<JsObject> {
+    self.check_open()?;
+    let promise = self.ready.wait(&env, Some(&self.current()?))?;
+    if self.ready.is_pending() && self.context.handler.lock().unwrap().is_none() {
+      // the snapshots only resolve the promises
+      self.start_with_handler(Box::new(|_| {}))?;
+    }
+    Ok(promise)
+  }
+
+  #[napi(ts_return_type = "Promise<void>")]
+  /// Alias of `connected`.
+  pub fn ready(&mut self, env: Env) -> Result<JsObject> {
+    self.connected(env)
+  }
+
+  fn start_inner<const WEAK: bool>(
+    &mut self,
+    change_handler: Arc<ThreadsafeFunction<NetworkInfo, (), NetworkInfo, false, { WEAK }>>,
+  ) -> Result<()> {
+    self.start_with_handler(Box::new(move |info| {
+      change_handler.call(info, ThreadsafeFunctionCallMode::Blocking);
+    }))
+  }
+
+  fn start_with_handler(&mut self, handler: ChangeHandler) -> Result<()> {
+    let handler = match self.interface_type {
+      Some(interface_type) => {
+        InterfaceTypeFilter::new(interface_type, &self.context.network_info.lock().unwrap())
+          .watch(handler)
+      }
+      None => handler,
+    };
+    let handler = self.ready.watch(self.history.record(handler));
+    self.context.handler.lock().unwrap().replace(handler);
+
+    if self.signal_ids.is_empty() {
+      // Every signal handler owns a strong reference of the context, it's released by `release_context` once the handler is disconnected.
+      self.signal_ids = SIGNAL_NAMES
+        .iter()
+        .map(|signal_name| unsafe {
+          ffi::g_signal_connect_data(
+            self.monitor.monitor,
+            signal_name.as_ptr(),
+            network_changed_cb,
+            Arc::into_raw(self.context.clone()) as *mut core::ffi::c_void,
+            Some(release_context),
+            0,
+          )
+        })
+        .collect();
+    }
+
+    Ok(())
+  }
+
+  #[napi]
+  /// Sample the byte counters of the physical interfaces every `intervalMs` on a separate thread,
+  /// `throughput` returns the rate between the last two samples. Starting it again restarts the sampling.
+  ///
+  /// It measures the total traffic of the machine, not the traffic of the app.
+  /// The sampling is independent of `start` and `stop`, it runs until `stopThroughput` or `close`.
+  pub fn start_throughput(&mut self, interval_ms: u32) -> Result<()> {
+    self.check_open()?;
+    // join the previous sampling thread before spawning another one
+    self.throughput_sampler.take();
+    self.throughput_sampler = Some(ThroughputSampler::start(interval_ms)?);
+    Ok(())
+  }
+
+  #[napi]
+  /// The received and sent bytes per second, `null` until `startThroughput` sampled twice.
+  pub fn throughput(&self) -> Result<Option<Throughput>> {
+    self.check_open()?;
+    Ok(
+      self
+        .throughput_sampler
+        .as_ref()
+        .and_then(ThroughputSampler::throughput),
+    )
+  }
+
+  #[napi]
+  /// Stop the sampling started by `startThroughput`.
+  pub fn stop_throughput(&mut self) -> Result<()> {
+    self.check_open()?;
+    self.throughput_sampler.take();
+    Ok(())
+  }
+
+  #[napi]
+  /// Stop the InternetMonitor.
+  ///
+  /// If you don't call this method and leave the monitor alone, it will be stopped automatically when it is GC.
+  pub fn stop(&mut self) -> Result<()> {
+    self.check_open()?;
+    self.unsubscribe();
+    Ok(())
+  }
+
+  #[napi]
+  /// Stop the InternetMonitor and release its callback, every method throws once it's closed.
+  ///
+  /// Calling `close` again is a no-op.
+  pub fn close(&mut self) {
+    if self.is_disposed.swap(true, Ordering::AcqRel) {
+      return;
+    }
+    self.throughput_sampler.take();
+    self.cleanup_hook.take();
+    self.unsubscribe();
+  }
+
+  fn check_open(&self) -> Result<()> {
+    if self.is_disposed.load(Ordering::Acquire) {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "monitor has been disposed",
+      ));
+    }
+    Ok(())
+  }
+
+  /// Disconnect the signal handlers and release the callback, it's a no-op if they are already disconnected.
+  fn unsubscribe(&mut self) {
+    for signal_id in self.signal_ids.drain(..) {
+      unsafe {
+        ffi::g_signal_handler_disconnect(self.monitor.monitor, signal_id);
+      }
+    }
+    self.context.handler.lock().unwrap().take();
+    self.emitter.clear();
+    self.ready.cancel();
+  }
+}
+
+#[cfg(feature = "napi")]
+#[inline]
+fn ctx_to_path(ctx: ThreadsafeCallContext<NetworkInfo>) -> Result<NetworkInfo> {
+  Ok(ctx.value)
+}
+
+#[cfg(feature = "napi")]
+#[napi]
+/// Read the network state once, for CLI tools and health checks which don't need a long-lived monitor.
+///
+/// The default GNetworkMonitor is shared by the process, it's created on the first call.
+pub fn get_global_network_info() -> Result<NetworkInfo> {
+  Ok(current_network_info()?)
+}
+
+pub(crate) fn current_network_info() -> native::Result<NetworkInfo> {
+  default_monitor()?.invoke(get_network_info)
+}
+
+#[cfg(feature = "napi")]
+/// The second argument is the `available` flag of `network-changed` or the `GParamSpec` of the notifications, it's unused.
+extern "C" fn network_changed_cb(
+  monitor: *mut ffi::GNetworkMonitor,
+  _: *mut core::ffi::c_void,
+  user_data: *mut core::ffi::c_void,
+) {
+  // SAFETY: `user_data` is the `MonitorContext` of the monitor which connected this signal.
+  let context = unsafe { &*(user_data as *const MonitorContext) };
+  let info = get_network_info(monitor);
+  *context.network_info.lock().unwrap() = info.clone();
+
+  if let Some(f) = context.handler.lock().unwrap().as_ref() {
+    f(info)
+  }
+}
+
+#[cfg(feature = "napi")]
+extern "C" fn release_context(user_data: *mut core::ffi::c_void, _: *mut core::ffi::c_void) {
+  // SAFETY: `user_data` was created by `Arc::into_raw` when the signal was connected
+  drop(unsafe { Arc::from_raw(user_data as *const MonitorContext) });
+}
+
+fn get_network_info(monitor: *mut ffi::GNetworkMonitor) -> NetworkInfo {
+  let available = unsafe { ffi::g_network_monitor_get_network_available(monitor) } != 0;
+  // GNetworkMonitor has a single metered flag, the same as `NM_METERED_YES` of NetworkManager
+  let metered = unsafe { ffi::g_network_monitor_get_network_metered(monitor) } != 0;
+  let status = match unsafe { ffi::g_network_monitor_get_connectivity(monitor) } {
+    ffi::GNetworkConnectivity::G_NETWORK_CONNECTIVITY_FULL => NetworkStatus::Satisfied,
+    ffi::GNetworkConnectivity::G_NETWORK_CONNECTIVITY_LIMITED
+    | ffi::GNetworkConnectivity::G_NETWORK_CONNECTIVITY_PORTAL => NetworkStatus::Satisfiable,
+    // `LOCAL` is also reported while the host has a route to a local network only
+    _ if available => NetworkStatus::Satisfiable,
+    _ => NetworkStatus::Unsatisfied,
+  };
+  // GNetworkMonitor doesn't describe the IP configuration nor the interfaces
+  NetworkInfo {
+    status,
+    is_expensive: metered,
+    is_low_data_mode: metered,
+    ..Default::default()
+  }
+}
+
+#[allow(non_camel_case_types)]
+// the signals are only connected by the monitor of the bindings
+#[cfg_attr(not(feature = "napi"), allow(dead_code))]
+mod ffi {
+  pub use std::ffi::{c_char, c_int, c_ulong, c_void};
+
+  pub type gchar = c_char;
+  pub type gulong = c_ulong;
+  pub type gint = c_int;
+  pub type gboolean = gint;
+  pub type GClosureNotify = extern "C" fn(data: *mut c_void, closure: *mut c_void);
+  pub type GSourceFunc = extern "C" fn(user_data: *mut c_void) -> gboolean;
+  pub type GDestroyNotify = extern "C" fn(data: *mut c_void);
+
+  pub const G_PRIORITY_DEFAULT: gint = 0;
+
+  #[repr(transparent)]
+  #[derive(PartialEq, Eq, Clone, Copy)]
+  pub struct GNetworkConnectivity(pub c_int);
+
+  impl GNetworkConnectivity {
+    pub const G_NETWORK_CONNECTIVITY_LIMITED: GNetworkConnectivity = GNetworkConnectivity(2);
+    pub const G_NETWORK_CONNECTIVITY_PORTAL: GNetworkConnectivity = GNetworkConnectivity(3);
+    pub const G_NETWORK_CONNECTIVITY_FULL: GNetworkConnectivity = GNetworkConnectivity(4);
+  }
+
+  #[repr(C)]
+  pub struct GNetworkMonitor {
+    _unused: [u8; 0],
+  }
+
+  #[repr(C)]
+  pub struct GMainContext {
+    _unused: [u8; 0],
+  }
+
+  #[repr(C)]
+  pub struct GMainLoop {
+    _unused: [u8; 0],
+  }
+
+  #[cfg_attr(any(target_os = "linux",), link(name = "gio-2.0", kind = "dylib"))]
+  extern "C" {
+    pub fn g_network_monitor_get_default() -> *mut GNetworkMonitor;
+    pub fn g_network_monitor_get_network_available(monitor: *mut GNetworkMonitor) -> gboolean;
+    pub fn g_network_monitor_get_network_metered(monitor: *mut GNetworkMonitor) -> gboolean;
+    pub fn g_network_monitor_get_connectivity(
+      monitor: *mut GNetworkMonitor,
+    ) -> GNetworkConnectivity;
+  }
+
+  #[cfg_attr(any(target_os = "linux",), link(name = "glib-2.0", kind = "dylib"))]
+  extern "C" {
+    pub fn g_main_context_new() -> *mut GMainContext;
+    pub fn g_main_context_push_thread_default(context: *mut GMainContext);
+    pub fn g_main_context_invoke_full(
+      context: *mut GMainContext,
+      priority: gint,
+      function: GSourceFunc,
+      data: *mut c_void,
+      notify: Option<GDestroyNotify>,
+    );
+
+    pub fn g_main_loop_new(context: *mut GMainContext, is_running: gboolean) -> *mut GMainLoop;
+    pub fn g_main_loop_run(lo: *mut GMainLoop);
+  }
+
+  #[cfg_attr(any(target_os = "linux",), link(name = "gobject-2.0", kind = "dylib"))]
+  extern "C" {
+    pub fn g_signal_connect_data(
+      instance: *mut GNetworkMonitor,
+      detailed_signal: *const gchar,
+      c_handler: extern "C" fn(
+        monitor: *mut GNetworkMonitor,
+        _: *mut c_void,
+        user_data: *mut c_void,
+      ),
+      data: *mut c_void,
+      destroy_data: Option<GClosureNotify>,
+      connect_flags: c_int,
+    ) -> gulong;
+    pub fn g_signal_handler_disconnect(instance: *mut GNetworkMonitor, signal_id: gulong);
+  }
+}
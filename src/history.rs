@@ -0,0 +1,75 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use napi_derive::napi;
+
+use crate::{ChangeHandler, NetworkInfo};
+
+/// The capacity of the history when the monitor is created without `historyCapacity`.
+pub(crate) const DEFAULT_HISTORY_CAPACITY: u32 = 32;
+
+#[napi(object, object_from_js = false)]
+#[derive(Debug, Clone)]
+/// A `NetworkInfo` delivered to the callback of the monitor, and when it was.
+pub struct NetworkHistoryEntry {
+  /// Milliseconds since the Unix epoch, comparable with `Date.now()`
+  pub timestamp_ms: f64,
+  pub info: NetworkInfo,
+}
+
+/// The last `capacity` snapshots delivered to the callback, the oldest one is dropped first.
+#[derive(Clone)]
+pub(crate) struct History {
+  capacity: usize,
+  entries: Arc<Mutex<VecDeque<NetworkHistoryEntry>>>,
+}
+
+impl Default for History {
+  fn default() -> Self {
+    Self::new(None)
+  }
+}
+
+impl History {
+  pub(crate) fn new(capacity: Option<u32>) -> Self {
+    let capacity = capacity.unwrap_or(DEFAULT_HISTORY_CAPACITY) as usize;
+    Self {
+      capacity,
+      entries: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+    }
+  }
+
+  pub(crate) fn push(&self, info: &NetworkInfo) {
+    if self.capacity == 0 {
+      return;
+    }
+    let timestamp_ms = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|elapsed| elapsed.as_secs_f64() * 1000.0)
+      .unwrap_or_default();
+    let mut entries = self.entries.lock().unwrap();
+    if entries.len() == self.capacity {
+      entries.pop_front();
+    }
+    entries.push_back(NetworkHistoryEntry {
+      timestamp_ms,
+      info: info.clone(),
+    });
+  }
+
+  /// Oldest first.
+  pub(crate) fn entries(&self) -> Vec<NetworkHistoryEntry> {
+    self.entries.lock().unwrap().iter().cloned().collect()
+  }
+
+  /// Record every snapshot before it reaches `handler`.
+  #[cfg_attr(feature = "mock", allow(dead_code))]
+  pub(crate) fn record(&self, handler: ChangeHandler) -> ChangeHandler {
+    let history = self.clone();
+    Box::new(move |info| {
+      history.push(&info);
+      handler(info);
+    })
+  }
+}
@@ -1,26 +1,97 @@
+#[cfg(feature = "napi")]
 use napi_derive::napi;
+use serde::Serialize;
 
-#[cfg(target_os = "macos")]
+mod aggregate;
+#[cfg(feature = "napi")]
+mod backoff;
+#[cfg(all(
+  feature = "napi",
+  any(target_os = "windows", target_os = "linux"),
+  not(feature = "mock")
+))]
+mod cleanup;
+#[cfg(feature = "napi")]
+mod emitter;
+#[cfg(feature = "napi")]
+mod filter;
+#[cfg(feature = "napi")]
+mod history;
+mod metered;
+pub mod native;
+#[cfg(feature = "napi")]
+mod primary;
+#[cfg(feature = "napi")]
+mod ready;
+#[cfg(feature = "napi")]
+mod reconnect;
+#[cfg(feature = "napi")]
+mod stable;
+#[cfg(feature = "napi")]
+mod throughput;
+#[cfg(feature = "napi")]
+mod unmetered;
+
+pub use aggregate::merge_network_info;
+#[cfg(feature = "napi")]
+pub use backoff::*;
+#[cfg(all(
+  feature = "napi",
+  any(target_os = "windows", target_os = "linux"),
+  not(feature = "mock")
+))]
+pub(crate) use cleanup::EnvCleanupHook;
+#[cfg(feature = "napi")]
+pub use emitter::MonitorEvent;
+#[cfg(feature = "napi")]
+pub use filter::ChangeFilter;
+#[cfg(feature = "napi")]
+pub use history::NetworkHistoryEntry;
+pub use metered::*;
+#[cfg(feature = "napi")]
+pub use throughput::Throughput;
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
 mod macos;
 
-#[cfg(target_os = "macos")]
+#[cfg(any(target_os = "macos", target_os = "ios"))]
 pub use macos::*;
 
-#[cfg(target_os = "windows")]
+#[cfg(target_os = "macos")]
+mod corewlan;
+
+#[cfg(target_os = "macos")]
+pub use corewlan::*;
+
+#[cfg(all(target_os = "windows", not(feature = "mock")))]
 mod windows;
 
-#[cfg(target_os = "windows")]
+#[cfg(all(target_os = "windows", not(feature = "mock")))]
 pub use windows::*;
 
-#[cfg(target_os = "linux")]
+#[cfg(all(target_os = "linux", not(feature = "gio"), not(feature = "mock")))]
 mod linux;
 
-#[cfg(target_os = "linux")]
+#[cfg(all(target_os = "linux", not(feature = "gio"), not(feature = "mock")))]
 pub use linux::*;
 
-#[napi(string_enum)]
+#[cfg(all(target_os = "linux", feature = "gio", not(feature = "mock")))]
+mod gio;
+
+#[cfg(all(target_os = "linux", feature = "gio", not(feature = "mock")))]
+pub use gio::*;
+
+// macOS has no `InternetMonitor` to replace, its `NWPathMonitor` stays
+#[cfg(all(feature = "mock", not(any(target_os = "macos", target_os = "ios"))))]
+mod mock;
+
+#[cfg(all(feature = "mock", not(any(target_os = "macos", target_os = "ios"))))]
+pub use mock::*;
+
+#[cfg_attr(feature = "napi", napi(string_enum))]
 #[repr(u8)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
 /// A network path status indicates if there is a usable route available upon which to send and receive data.
 pub enum NetworkStatus {
   /// nw_path_status_invalid The path is not valid
@@ -35,13 +106,599 @@ pub enum NetworkStatus {
   Unknown,
 }
 
-#[napi(object, object_from_js = false)]
-#[derive(Debug, Clone)]
+impl NetworkStatus {
+  /// The stable numeric code of the status, from 0 for `Invalid` to 4 for `Unknown`.
+  pub fn status_code(self) -> u8 {
+    self as u8
+  }
+
+  pub fn from_code(code: u8) -> Option<Self> {
+    match code {
+      0 => Some(NetworkStatus::Invalid),
+      1 => Some(NetworkStatus::Satisfied),
+      2 => Some(NetworkStatus::Unsatisfied),
+      3 => Some(NetworkStatus::Satisfiable),
+      4 => Some(NetworkStatus::Unknown),
+      _ => None,
+    }
+  }
+}
+
+#[cfg_attr(feature = "napi", napi)]
+/// The stable numeric code of a `NetworkStatus`, a compact representation for storage and IPC.
+pub fn network_status_code(status: NetworkStatus) -> u8 {
+  status.status_code()
+}
+
+#[cfg(feature = "napi")]
+#[napi]
+/// The `NetworkStatus` of a code returned by `networkStatusCode`.
+pub fn network_status_from_code(code: u8) -> napi::Result<NetworkStatus> {
+  NetworkStatus::from_code(code).ok_or_else(|| {
+    napi::Error::new(
+      napi::Status::InvalidArg,
+      format!("{code} is not a NetworkStatus code"),
+    )
+  })
+}
+
+#[cfg_attr(feature = "napi", napi)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The `NLM_CONNECTION_COST` bits of `costFlags`, test them with `&`.
+pub enum ConnectionCostFlag {
+  /// NLM_CONNECTION_COST_UNRESTRICTED The connection is unlimited
+  Unrestricted = 0x1,
+  /// NLM_CONNECTION_COST_FIXED The usage is unrestricted up to a data limit
+  Fixed = 0x2,
+  /// NLM_CONNECTION_COST_VARIABLE The connection is billed by the byte
+  Variable = 0x4,
+  /// NLM_CONNECTION_COST_OVERDATALIMIT The data limit is exceeded
+  OverDataLimit = 0x10000,
+  /// NLM_CONNECTION_COST_CONGESTED The network is congested
+  Congested = 0x20000,
+  /// NLM_CONNECTION_COST_ROAMING The connection is roaming outside of the home network
+  Roaming = 0x40000,
+  /// NLM_CONNECTION_COST_APPROACHINGDATALIMIT The data limit is close
+  ApproachingDataLimit = 0x80000,
+}
+
+#[cfg_attr(feature = "napi", napi(object, object_from_js = false))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The `NLM_CONNECTIVITY` bits of `connectivityFlags`, one boolean per bit.
+pub struct ConnectivityFlags {
+  /// NLM_CONNECTIVITY_IPV4_NOTRAFFIC Connected without any IPv4 traffic, e.g. no DHCP lease
+  pub ipv4_no_traffic: bool,
+  /// NLM_CONNECTIVITY_IPV6_NOTRAFFIC Connected without any IPv6 traffic
+  pub ipv6_no_traffic: bool,
+  /// NLM_CONNECTIVITY_IPV4_SUBNET IPv4 only reaches the local subnet
+  pub has_ipv4_subnet: bool,
+  /// NLM_CONNECTIVITY_IPV4_LOCALNETWORK IPv4 reaches a routed local network, but not the Internet
+  pub has_ipv4_local_network: bool,
+  /// NLM_CONNECTIVITY_IPV4_INTERNET IPv4 reaches the Internet
+  pub has_ipv4_internet: bool,
+  /// NLM_CONNECTIVITY_IPV6_SUBNET IPv6 only reaches the local subnet
+  pub has_ipv6_subnet: bool,
+  /// NLM_CONNECTIVITY_IPV6_LOCALNETWORK IPv6 reaches a routed local network, but not the Internet
+  pub has_ipv6_local_network: bool,
+  /// NLM_CONNECTIVITY_IPV6_INTERNET IPv6 reaches the Internet
+  pub has_ipv6_internet: bool,
+}
+
+#[cfg_attr(feature = "napi", napi)]
+/// Name the bits of `connectivityFlags`, every flag is `false` for the 0 of the other platforms.
+pub fn decode_connectivity_flags(flags: u32) -> ConnectivityFlags {
+  let has = |bit: u32| flags & bit == bit;
+  ConnectivityFlags {
+    ipv4_no_traffic: has(0x1),
+    ipv6_no_traffic: has(0x2),
+    has_ipv4_subnet: has(0x10),
+    has_ipv4_local_network: has(0x20),
+    has_ipv4_internet: has(0x40),
+    has_ipv6_subnet: has(0x100),
+    has_ipv6_local_network: has(0x200),
+    has_ipv6_internet: has(0x400),
+  }
+}
+
+#[cfg_attr(feature = "napi", napi(string_enum))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
+/// How far an IP family reaches, from the `NLM_CONNECTIVITY` bits.
+pub enum ConnectivityLevel {
+  /// Not connected, or connected without any traffic, e.g. no DHCP lease
+  None,
+  /// Only the local subnet or a routed local network, e.g. a LAN without an Internet uplink
+  LocalOnly,
+  /// The Internet is reachable
+  Internet,
+}
+
+impl ConnectivityLevel {
+  #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+  pub(crate) fn from_connectivity_flags(flags: u32, ipv6: bool) -> Self {
+    let flags = decode_connectivity_flags(flags);
+    let (internet, local) = if ipv6 {
+      (
+        flags.has_ipv6_internet,
+        flags.has_ipv6_subnet || flags.has_ipv6_local_network,
+      )
+    } else {
+      (
+        flags.has_ipv4_internet,
+        flags.has_ipv4_subnet || flags.has_ipv4_local_network,
+      )
+    };
+    if internet {
+      ConnectivityLevel::Internet
+    } else if local {
+      ConnectivityLevel::LocalOnly
+    } else {
+      ConnectivityLevel::None
+    }
+  }
+}
+
+#[cfg_attr(feature = "napi", napi(string_enum))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
+/// The IP stacks which can be used on the path.
+pub enum IpStackType {
+  /// Neither IPv4 nor IPv6 is available
+  None,
+  /// Only IPv4 is available
+  Ipv4Only,
+  /// Only IPv6 is available, e.g. a NAT64 cellular network
+  Ipv6Only,
+  /// Both IPv4 and IPv6 are available
+  DualStack,
+}
+
+impl IpStackType {
+  pub fn new(has_ipv4: bool, has_ipv6: bool) -> Self {
+    match (has_ipv4, has_ipv6) {
+      (false, false) => IpStackType::None,
+      (true, false) => IpStackType::Ipv4Only,
+      (false, true) => IpStackType::Ipv6Only,
+      (true, true) => IpStackType::DualStack,
+    }
+  }
+}
+
+#[cfg_attr(feature = "napi", napi(string_enum))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
+/// Why a path is not satisfied.
+pub enum UnsatisfiedReason {
+  /// nw_path_unsatisfied_reason_not_available No reason is given
+  NotAvailable,
+  /// nw_path_unsatisfied_reason_cellular_denied The user has disabled cellular
+  CellularDenied,
+  /// nw_path_unsatisfied_reason_wifi_denied The user has disabled Wi-Fi
+  WifiDenied,
+  /// nw_path_unsatisfied_reason_local_network_denied The user has disabled local network access
+  LocalNetworkDenied,
+  /// nw_path_unsatisfied_reason_vpn_inactive A required VPN is not active, reported since macOS 14
+  VpnInactive,
+}
+
+#[cfg_attr(feature = "napi", napi(string_enum))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
+/// The trust level Windows assigns to a network, it decides which firewall profile applies.
+pub enum NetworkCategory {
+  /// NLM_NETWORK_CATEGORY_PUBLIC The network is not trusted, e.g. a coffee shop Wi-Fi
+  Public,
+  /// NLM_NETWORK_CATEGORY_PRIVATE The network is trusted by the user, e.g. the home network
+  Private,
+  /// NLM_NETWORK_CATEGORY_DOMAIN_AUTHENTICATED The network is authenticated against an Active Directory domain
+  DomainAuthenticated,
+  /// Not connected, or not on Windows
+  Unknown,
+}
+
+#[cfg_attr(feature = "napi", napi)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
+#[serde(rename_all = "lowercase")]
+/// Interface types represent the underlying media for a network link, such as Wi-Fi or Cellular.
+pub enum NWInterfaceType {
+  /// nw_interface_type_other A virtual or otherwise unknown interface type
+  Other,
+  /// nw_interface_type_wifi A Wi-Fi link
+  Wifi,
+  /// nw_interface_type_wifi A Cellular link
+  Cellular,
+  /// nw_interface_type_wired A Wired Ethernet link
+  Wired,
+  /// nw_interface_type_loopback A Loopback link
+  Loopback,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
+#[serde(rename_all = "camelCase")]
 pub struct NetworkInfo {
   pub status: NetworkStatus,
+  /// The path uses an interface that is considered expensive, such as cellular or a personal hotspot.
+  ///
+  /// On macOS this is `nw_path_is_expensive`, on Windows the connection is metered, roaming or over its data limit,
+  /// see `meteredStatusFromCostFlags`.
   pub is_expensive: bool,
+  /// The path uses an interface in Low Data Mode, the user asked to save data on it.
+  ///
+  /// On macOS this is `nw_path_is_constrained`, which is independent from `is_expensive`.
+  /// On Windows the connection is over or close to its data limit, or congested.
   pub is_low_data_mode: bool,
+  /// The connection is roaming outside of the home network of its operator, only reported on Windows.
+  pub is_roaming: bool,
   pub has_ipv4: bool,
   pub has_ipv6: bool,
   pub has_dns: bool,
+  /// Derived from `has_ipv4` and `has_ipv6`
+  pub stack: IpStackType,
+  /// The type of the interface carrying the traffic, `undefined` when there is no usable interface.
+  pub interface_type: Option<NWInterfaceType>,
+  /// Why the path is not usable, only reported by Network.framework on macOS 11+ when the status is `Unsatisfied`.
+  pub unsatisfied_reason: Option<UnsatisfiedReason>,
+  /// The category of the connected network, only reported on Windows.
+  pub network_category: Option<NetworkCategory>,
+  /// The link speed of the primary interface in Mbit/s, when the platform knows it.
+  ///
+  /// It's the negotiated speed of Ethernet and the bitrate of Wi-Fi, not the throughput to the Internet.
+  pub link_speed_mbps: Option<u32>,
+  /// The interfaces used by the path, e.g. a `utun` VPN interface on top of Wi-Fi, only reported on macOS.
+  pub interfaces: Vec<NetworkInterface>,
+  /// The preferred interface of the path, e.g. to bind a socket to it with `IP_BOUND_IF`, only reported on macOS.
+  ///
+  /// `undefined` when the path is not usable.
+  pub primary_interface: Option<NetworkInterface>,
+  /// The first-hop gateways of the path, e.g. `192.168.1.1` or `fe80::1%en0`, only reported on macOS.
+  ///
+  /// Empty when the path is not satisfied.
+  pub gateways: Vec<String>,
+  /// Bumped every time the system resolver configuration changes, e.g. a VPN pushing split DNS,
+  /// it's the moment to flush an in-app DNS cache. Only reported on macOS.
+  pub dns_generation: Option<u32>,
+  /// The path goes over Wi-Fi.
+  ///
+  /// On macOS this is `nw_path_uses_interface_type` and covers every interface of the path,
+  /// other platforms only check the primary interface.
+  pub uses_wifi: bool,
+  /// The path goes over cellular, including an iPhone tethered over USB on macOS.
+  pub uses_cellular: bool,
+  /// The path goes over wired Ethernet.
+  pub uses_wired: bool,
+  /// The path goes over the loopback interface.
+  pub uses_loopback: bool,
+  /// The raw `NLM_CONNECTIVITY` bits of the network list manager, `decodeConnectivityFlags` names them.
+  ///
+  /// They tell an isolated LAN from a machine which is fully offline. Only reported on Windows, 0 on the other platforms.
+  pub connectivity_flags: u32,
+  /// How far IPv4 reaches, e.g. `LocalOnly` on a LAN without Internet and `None` without any connectivity.
+  /// Like `hasIpv4`, `Internet` also needs a routable address, it's `LocalOnly` with only a link-local one.
+  ///
+  /// Only reported on Windows, `None` on the other platforms.
+  pub ipv4_connectivity: ConnectivityLevel,
+  /// How far IPv6 reaches, only reported on Windows, `None` on the other platforms.
+  pub ipv6_connectivity: ConnectivityLevel,
+  /// The raw `NLM_CONNECTION_COST` bits of the connection, `ConnectionCostFlag` names them,
+  /// e.g. to tell a fixed data plan from a variable one or roaming. Only reported on Windows, 0 on the other platforms.
+  pub cost_flags: u32,
+  /// The data limit of the plan of the connection in megabytes, only reported on Windows.
+  ///
+  /// `undefined` without a limit, a metered connection may have none.
+  pub data_limit_mb: Option<u32>,
+  /// Airplane mode is on, e.g. to explain an `Unsatisfied` status by it rather than by the coverage.
+  ///
+  /// Only reported on Windows, `false` on the other platforms and on the machines without radios.
+  pub airplane_mode: bool,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkInterface {
+  /// The BSD name of the interface, e.g. `en0`
+  pub name: String,
+  /// The system interface index
+  pub index: u32,
+  #[cfg_attr(feature = "napi", napi(js_name = "type"))]
+  #[serde(rename = "type")]
+  pub interface_type: NWInterfaceType,
+}
+
+#[cfg_attr(feature = "napi", napi(object, object_from_js = false))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The state of a single interface, for machines with several of them, e.g. Ethernet + Wi-Fi + VPN.
+pub struct InterfaceNetworkInfo {
+  /// The name of the interface, e.g. `en0` on macOS, `eth0` on Linux or `Wi-Fi` on Windows
+  pub name: String,
+  #[cfg_attr(feature = "napi", napi(js_name = "type"))]
+  #[serde(rename = "type")]
+  pub interface_type: NWInterfaceType,
+  /// `Satisfied` when the interface reaches the Internet, `Satisfiable` when it's up, `Unsatisfied` when it's down.
+  ///
+  /// On macOS every interface of the path shares the status of the path.
+  pub status: NetworkStatus,
+  /// The IPv4 and IPv6 addresses assigned to the interface
+  pub addresses: Vec<String>,
+  /// The interface is metered, e.g. a mobile broadband modem or a connection the user marked as metered.
+  pub is_metered: bool,
+}
+
+#[cfg_attr(feature = "napi", napi(object, object_from_js = false))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Whether the machine runs on battery, to combine with the cost of the network in a data saving decision.
+///
+/// It's not part of the `NetworkInfo`, no change event is emitted when it changes.
+pub struct PowerState {
+  /// The AC power is disconnected, `false` when it's unknown
+  pub on_battery: bool,
+  /// The remaining charge from 0 to 100, `undefined` without a battery or when it's unknown
+  pub battery_percent: Option<u8>,
+}
+
+#[cfg_attr(feature = "napi", napi(object, object_from_js = false))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// A network the system remembers, connected or not.
+pub struct KnownNetwork {
+  /// The name shown to the user, e.g. the Wi-Fi SSID on Windows or the connection profile name on Linux
+  pub name: String,
+  pub is_connected: bool,
+  /// `Satisfied` when the network reaches the Internet, `Satisfiable` when it's only connected locally,
+  /// `Unsatisfied` when it's not connected.
+  pub connectivity: NetworkStatus,
+  /// Always `Unknown` on Linux
+  pub category: NetworkCategory,
+}
+
+#[cfg_attr(feature = "napi", napi(string_enum))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+/// A coarse quality tier of a connection.
+///
+/// The tiers are decided in this order:
+/// - `Offline` unless the status is `Satisfied`
+/// - `CellularMetered` for cellular interfaces and expensive paths, e.g. a personal hotspot
+/// - `Slow` when the link speed is known and below 10 Mbit/s
+/// - `WifiFast` for Wi-Fi, `WiredFast` for Ethernet and the other interfaces (VPN, virtual adapters)
+pub enum ConnectionClass {
+  /// Ethernet or another unmetered link which is fast enough
+  WiredFast,
+  /// Wi-Fi which is fast enough
+  WifiFast,
+  /// Cellular or another expensive link, save data on it
+  CellularMetered,
+  /// The link speed is below 10 Mbit/s
+  Slow,
+  /// There is no usable path
+  Offline,
+}
+
+/// Links below this speed are `Slow` whatever the medium is, e.g. 802.11b or a 10BASE-T port.
+const SLOW_LINK_SPEED_MBPS: u32 = 10;
+
+impl ConnectionClass {
+  pub fn new(info: &NetworkInfo) -> Self {
+    if !matches!(info.status, NetworkStatus::Satisfied) {
+      return ConnectionClass::Offline;
+    }
+    if info.interface_type == Some(NWInterfaceType::Cellular) || info.is_expensive {
+      return ConnectionClass::CellularMetered;
+    }
+    if info
+      .link_speed_mbps
+      .is_some_and(|link_speed_mbps| link_speed_mbps < SLOW_LINK_SPEED_MBPS)
+    {
+      return ConnectionClass::Slow;
+    }
+    match info.interface_type {
+      Some(NWInterfaceType::Wifi) => ConnectionClass::WifiFast,
+      _ => ConnectionClass::WiredFast,
+    }
+  }
+}
+
+/// Nothing is known yet: the status is `Unknown` and every other field is empty, the backends fill in what
+/// their platform reports.
+impl Default for NetworkInfo {
+  fn default() -> Self {
+    Self {
+      status: NetworkStatus::Unknown,
+      is_expensive: false,
+      is_low_data_mode: false,
+      is_roaming: false,
+      has_ipv4: false,
+      has_ipv6: false,
+      has_dns: false,
+      stack: IpStackType::None,
+      interface_type: None,
+      unsatisfied_reason: None,
+      network_category: None,
+      link_speed_mbps: None,
+      interfaces: Vec::new(),
+      primary_interface: None,
+      gateways: Vec::new(),
+      dns_generation: None,
+      uses_wifi: false,
+      uses_cellular: false,
+      uses_wired: false,
+      uses_loopback: false,
+      connectivity_flags: 0,
+      ipv4_connectivity: ConnectivityLevel::None,
+      ipv6_connectivity: ConnectivityLevel::None,
+      cost_flags: 0,
+      data_limit_mb: None,
+      airplane_mode: false,
+    }
+  }
+}
+
+impl NetworkInfo {
+  /// It's safe to do a large download right now: the path is `Satisfied`, it's not expensive,
+  /// not in Low Data Mode and not roaming.
+  ///
+  /// Every platform computes it from the same fields, they are only populated differently.
+  pub fn can_use_unmetered(&self) -> bool {
+    matches!(self.status, NetworkStatus::Satisfied)
+      && !self.is_expensive
+      && !self.is_low_data_mode
+      && !self.is_roaming
+  }
+}
+
+#[cfg(any(feature = "napi", target_os = "windows"))]
+/// Receives every new snapshot computed by the platform backend.
+pub(crate) type ChangeHandler = Box<dyn Fn(NetworkInfo) + Send + Sync>;
+
+#[cfg(any(feature = "napi", target_os = "windows"))]
+/// Receives the failures of the platform backend which happen outside of a call from JavaScript.
+pub(crate) type ErrorHandler = Box<dyn Fn(String) + Send + Sync>;
+
+#[cfg(feature = "napi")]
+/// Wrap a JavaScript callback into a threadsafe function callable from any thread,
+/// `weak` doesn't keep the Node.js process alive.
+pub(crate) fn callback<T>(
+  f: napi::bindgen_prelude::Function<T, ()>,
+  weak: bool,
+) -> napi::Result<Box<dyn Fn(T) + Send + Sync>>
+where
+  T: napi::bindgen_prelude::JsValuesTupleIntoVec + Send + 'static,
+{
+  use napi::threadsafe_function::ThreadsafeFunctionCallMode;
+
+  Ok(if weak {
+    let f = f
+      .build_threadsafe_function()
+      .callee_handled::<false>()
+      .weak::<true>()
+      .build_callback(|ctx| Ok(ctx.value))?;
+    Box::new(move |value| {
+      f.call(value, ThreadsafeFunctionCallMode::NonBlocking);
+    })
+  } else {
+    let f = f
+      .build_threadsafe_function()
+      .callee_handled::<false>()
+      .weak::<false>()
+      .build_callback(|ctx| Ok(ctx.value))?;
+    Box::new(move |value| {
+      f.call(value, ThreadsafeFunctionCallMode::NonBlocking);
+    })
+  })
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, Default)]
+pub struct CreateOptions {
+  /// Reject with a `BackendTimeout` error if the platform backend is not ready in time,
+  /// e.g. when the NetworkManager D-Bus service or the Windows netprofm service hangs.
+  /// Its `code` is `BACKEND_TIMEOUT`, 0 always times out.
+  pub timeout_ms: Option<u32>,
+  /// Ignore the adapters whose name or description contains one of these strings, case-insensitively,
+  /// on top of a built-in list of the host-only adapters of Hyper-V, WSL, VMware, VirtualBox and Npcap.
+  ///
+  /// They are up without reaching the Internet and skew `hasDns`, the status and `currentAll`. Only used on Windows.
+  pub exclude_adapters: Option<Vec<String>>,
+  /// How many snapshots `history` keeps, 32 by default, 0 turns the history off.
+  pub history_capacity: Option<u32>,
+  /// Allow `onInterfaceChange`, the per-interface events of IP Helper are immediate but much noisier
+  /// than the connectivity events. Only used on Windows.
+  pub ip_interface_events: Option<bool>,
+}
+
+#[cfg_attr(feature = "napi", napi)]
+/// The `code` of the error `InternetMonitor.create` rejects with when the platform backend is not ready in `timeoutMs`.
+pub const BACKEND_TIMEOUT: &str = "BackendTimeout";
+
+#[cfg(feature = "napi")]
+/// Run the blocking initialization of a platform backend, on a helper thread when a timeout is given.
+///
+/// The platform calls can't be interrupted, after a timeout the helper thread is abandoned:
+/// it keeps waiting for them and drops the backend it created by itself.
+pub(crate) fn init_with_timeout<T, F>(timeout_ms: Option<u32>, init: F) -> napi::Result<T>
+where
+  T: Send + 'static,
+  F: FnOnce() -> napi::Result<T> + Send + 'static,
+{
+  let Some(timeout_ms) = timeout_ms else {
+    return init();
+  };
+  if timeout_ms == 0 {
+    return Err(backend_timeout(timeout_ms));
+  }
+  // the channel is buffered, so the helper thread never blocks on sending after the receiver is gone
+  let (sender, receiver) = std::sync::mpsc::sync_channel(1);
+  std::thread::Builder::new()
+    .name("network-change-init".to_owned())
+    .spawn(move || {
+      let _ = sender.send(init());
+    })
+    .map_err(|err| {
+      napi::Error::new(
+        napi::Status::GenericFailure,
+        format!("Failed to spawn the initialization thread: {err}"),
+      )
+    })?;
+  match receiver.recv_timeout(std::time::Duration::from_millis(timeout_ms.into())) {
+    Ok(backend) => backend,
+    Err(std::sync::mpsc::RecvTimeoutError::Timeout) => Err(backend_timeout(timeout_ms)),
+    Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => Err(napi::Error::new(
+      napi::Status::GenericFailure,
+      "The initialization thread panicked",
+    )),
+  }
+}
+
+#[cfg(feature = "napi")]
+fn backend_timeout(timeout_ms: u32) -> napi::Error {
+  napi::Error::new(
+    napi::Status::GenericFailure,
+    format!("{BACKEND_TIMEOUT}: the network backend is not ready after {timeout_ms}ms"),
+  )
+}
+
+#[cfg(feature = "napi")]
+/// The `reject` of the tasks creating a monitor, the timeout of `init_with_timeout` gets `BACKEND_TIMEOUT` as its `code`.
+pub(crate) fn reject_create<T>(env: &napi::Env, err: napi::Error) -> napi::Result<T> {
+  if !err.reason.starts_with(BACKEND_TIMEOUT) {
+    return Err(err);
+  }
+  let mut error = env.create_error(err)?;
+  error.set_named_property("code", BACKEND_TIMEOUT)?;
+  Err(napi::Error::from(error.into_unknown()))
+}
+
+#[cfg(feature = "napi")]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NetworkSnapshot<'a> {
+  #[serde(flatten)]
+  info: &'a NetworkInfo,
+  platform: &'static str,
+  /// Milliseconds since the Unix epoch
+  timestamp: u64,
+}
+
+#[cfg(feature = "napi")]
+/// Serialize the `NetworkInfo` with the schema shared by every platform, for logging and telemetry.
+pub(crate) fn snapshot_json(info: &NetworkInfo) -> napi::Result<String> {
+  let timestamp = std::time::SystemTime::now()
+    .duration_since(std::time::UNIX_EPOCH)
+    .map(|elapsed| elapsed.as_millis() as u64)
+    .unwrap_or_default();
+  serde_json::to_string(&NetworkSnapshot {
+    info,
+    platform: std::env::consts::OS,
+    timestamp,
+  })
+  .map_err(|err| {
+    napi::Error::new(
+      napi::Status::GenericFailure,
+      format!("Failed to serialize NetworkInfo: {err}"),
+    )
+  })
 }
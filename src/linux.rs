@@ -1,71 +1,277 @@
-use std::sync::{Arc, LazyLock, Mutex};
+#[cfg(feature = "napi")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "napi")]
+use std::sync::{mpsc, Arc, Mutex};
 
+use crate::native;
+use crate::IpStackType;
+use crate::NWInterfaceType;
 use crate::NetworkInfo;
 use crate::NetworkStatus;
+#[cfg(feature = "napi")]
+use crate::{
+  emitter::{Emitter, EventPayload},
+  filter::InterfaceTypeFilter,
+  history::{History, NetworkHistoryEntry},
+  ready::Ready,
+  throughput::{Throughput, ThroughputSampler},
+  BackoffEvent, BackoffOptions, ChangeHandler, ConnectionClass, CreateOptions, EnvCleanupHook,
+  InterfaceNetworkInfo, KnownNetwork, MeteredStatus, MonitorEvent, NetworkCategory, PowerState,
+};
+#[cfg(feature = "napi")]
 use napi::bindgen_prelude::*;
+#[cfg(feature = "napi")]
 use napi::threadsafe_function::{
   ThreadsafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode,
 };
+#[cfg(feature = "napi")]
+use napi::JsObject;
+#[cfg(feature = "napi")]
 use napi_derive::napi;
 
+#[cfg(feature = "napi")]
 const SIGNAL_NAME: &std::ffi::CStr = c"notify::connectivity";
 
-static NETWORK_INFO: LazyLock<Mutex<NetworkInfo>> = LazyLock::new(|| {
-  Mutex::new(NetworkInfo {
-    status: NetworkStatus::Invalid,
-    is_expensive: false,
-    is_low_data_mode: false,
-    has_ipv4: false,
-    has_ipv6: false,
-    has_dns: false,
-  })
-});
-
-#[allow(clippy::type_complexity)]
-static GLOBAL_HANDLER: LazyLock<Mutex<Option<Box<dyn Fn(NetworkInfo) + 'static + Send + Sync>>>> =
-  LazyLock::new(|| Mutex::new(None));
+#[cfg(feature = "napi")]
+/// State owned by a single `InternetMonitor`, handed to the `notify::connectivity` signal as `user_data`.
+struct MonitorContext {
+  network_info: Mutex<NetworkInfo>,
+  handler: Mutex<Option<ChangeHandler>>,
+}
 
+#[cfg(feature = "napi")]
 #[derive(Clone, Copy)]
 struct MainLoopWrapper(*mut ffi::GMainLoop);
+#[cfg(feature = "napi")]
 unsafe impl Send for MainLoopWrapper {}
+#[cfg(feature = "napi")]
 unsafe impl Sync for MainLoopWrapper {}
 
+/// The NetworkManager client backing an `InternetMonitor` and the first snapshot read from it.
+///
+/// `nm_client_new` loads the whole NetworkManager state over D-Bus, `InternetMonitor::create` does it on the libuv thread pool.
+pub struct NetworkManagerClient {
+  client: *mut ffi::NMClient,
+  main_context: *mut ffi::GMainContext,
+  network_info: NetworkInfo,
+}
+
+// SAFETY: libnm objects are not thread-safe, but they are bound to their main context, not to the thread which created them.
+// Until the main loop runs, the thread creating the client is the only one which knows it. Afterwards the client is only
+// read while owning `main_context`: by the signal handlers dispatched by the main loop thread, and by `invoke`.
+// Connecting and disconnecting the GObject signals is thread-safe.
+unsafe impl Send for NetworkManagerClient {}
+
+impl NetworkManagerClient {
+  fn new() -> native::Result<Self> {
+    // Every monitor gets its own main context, so monitors created on different threads
+    // (e.g. in worker_threads) dispatch their NMClient signals independently.
+    let main_context = unsafe { ffi::g_main_context_new() };
+    unsafe { ffi::g_main_context_push_thread_default(main_context) };
+    let client = unsafe { ffi::nm_client_new(std::ptr::null_mut(), std::ptr::null_mut()) };
+    unsafe { ffi::g_main_context_pop_thread_default(main_context) };
+    if client.is_null() {
+      unsafe { ffi::g_main_context_unref(main_context) };
+      return Err(native::Error::new(
+        "Error initializing NetworkManager client.",
+      ));
+    }
+    Ok(Self {
+      client,
+      main_context,
+      network_info: get_network_info(client),
+    })
+  }
+
+  #[cfg(feature = "napi")]
+  /// Run `read` on the thread owning the main context of the client and wait for it, the main loop thread updates
+  /// the libnm objects while it dispatches the D-Bus signals.
+  ///
+  /// The main loop must be running, or not be started yet, in which case `read` runs on the calling thread.
+  fn invoke<T: Send + 'static>(
+    &self,
+    read: impl FnOnce(*mut ffi::NMClient) -> T + Send + 'static,
+  ) -> Result<T> {
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let client = ClientPtr(self.client);
+    let job: Job = Box::new(move || {
+      // capture the whole `ClientPtr`, the raw pointer alone isn't `Send`
+      let client = client;
+      let _ = sender.send(read(client.0));
+    });
+    unsafe {
+      ffi::g_main_context_invoke_full(
+        self.main_context,
+        ffi::G_PRIORITY_DEFAULT,
+        run_job,
+        Box::into_raw(Box::new(Some(job))).cast(),
+        Some(drop_job),
+      )
+    };
+    receiver.recv().map_err(|_| {
+      Error::new(
+        Status::GenericFailure,
+        "The GLib main loop of the monitor is not running",
+      )
+    })
+  }
+}
+
+#[cfg(feature = "napi")]
+/// A closure run by `g_main_context_invoke_full`, taken by `run_job` and freed by `drop_job`,
+/// which is called without `run_job` when the context is destroyed first.
+type Job = Box<dyn FnOnce() + Send>;
+
+#[cfg(feature = "napi")]
+struct ClientPtr(*mut ffi::NMClient);
+
+#[cfg(feature = "napi")]
+// SAFETY: the pointer is only dereferenced by the jobs of `invoke`, on the thread owning the main context
+unsafe impl Send for ClientPtr {}
+
+#[cfg(feature = "napi")]
+extern "C" fn run_job(job: *mut core::ffi::c_void) -> ffi::gboolean {
+  // SAFETY: `job` was created by `invoke` and is alive until `drop_job`
+  if let Some(job) = unsafe { &mut *job.cast::<Option<Job>>() }.take() {
+    job();
+  }
+  // G_SOURCE_REMOVE
+  0
+}
+
+#[cfg(feature = "napi")]
+extern "C" fn drop_job(job: *mut core::ffi::c_void) {
+  // SAFETY: `job` was created by `Box::into_raw` in `invoke`
+  drop(unsafe { Box::from_raw(job.cast::<Option<Job>>()) });
+}
+
+impl Drop for NetworkManagerClient {
+  fn drop(&mut self) {
+    unsafe {
+      ffi::g_object_unref(self.client.cast());
+      ffi::g_main_context_unref(self.main_context);
+    }
+  }
+}
+
+#[cfg(feature = "napi")]
+pub struct CreateInternetMonitor {
+  timeout_ms: Option<u32>,
+  history_capacity: Option<u32>,
+}
+
+#[cfg(feature = "napi")]
+impl Task for CreateInternetMonitor {
+  type Output = NetworkManagerClient;
+  type JsValue = InternetMonitor;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    crate::init_with_timeout(self.timeout_ms, || Ok(NetworkManagerClient::new()?))
+  }
+
+  fn resolve(&mut self, env: Env, client: Self::Output) -> Result<Self::JsValue> {
+    let mut monitor = InternetMonitor::from_client(env, client)?;
+    monitor.history = History::new(self.history_capacity);
+    Ok(monitor)
+  }
+
+  fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+    crate::reject_create(&env, err)
+  }
+}
+
+#[cfg(feature = "napi")]
 #[napi]
 pub struct InternetMonitor {
-  client: *mut ffi::NMClient,
-  signal_id: Arc<Mutex<Option<ffi::gulong>>>,
+  context: Arc<MonitorContext>,
+  /// Releases the callback when the environment is torn down first, removed by `close` or the GC
+  cleanup_hook: Option<EnvCleanupHook>,
+  signal_id: Option<ffi::gulong>,
   thread_handle: Option<std::thread::JoinHandle<()>>,
   lo: MainLoopWrapper,
+  weak_by_default: bool,
+  /// Set by `close`, every method throws afterwards
+  is_disposed: AtomicBool,
+  /// The sampling thread of `startThroughput`
+  throughput_sampler: Option<ThroughputSampler>,
+  /// The listeners of `on`
+  emitter: Emitter,
+  history: History,
+  /// The pending promises of `connected`
+  ready: Ready,
+  /// Set by `newWithType`, the callbacks only see the snapshots of this interface type
+  interface_type: Option<NWInterfaceType>,
+  client: NetworkManagerClient,
 }
 
+#[cfg(feature = "napi")]
 impl Drop for InternetMonitor {
   fn drop(&mut self) {
-    println!("Dropping InternetMonitor");
-    self.stop();
+    self.release();
     unsafe {
-      ffi::g_main_loop_quit(self.lo.0);
-    }
-    if let Some(thread_handle) = self.thread_handle.take() {
-      thread_handle.join().unwrap();
+      ffi::g_main_loop_unref(self.lo.0);
     }
   }
 }
 
+#[cfg(feature = "napi")]
 #[napi]
 impl InternetMonitor {
   #[napi(constructor)]
-  pub fn new() -> Result<Self> {
-    let client = unsafe { ffi::nm_client_new(std::ptr::null_mut(), std::ptr::null_mut()) };
-    if client.is_null() {
-      return Err(Error::new(
-        Status::GenericFailure,
-        "Error initializing NetworkManager client.",
-      ));
-    }
+  /// `timeoutMs` is ignored by the constructor, it doesn't time out.
+  pub fn new(env: Env, options: Option<CreateOptions>) -> Result<Self> {
+    let options = options.unwrap_or_default();
+    let mut monitor = Self::from_client(env, NetworkManagerClient::new()?)?;
+    monitor.history = History::new(options.history_capacity);
+    Ok(monitor)
+  }
+
+  #[napi(factory)]
+  /// Create an InternetMonitor which only reports the changes while `interfaceType` carries the traffic,
+  /// and the one moving the traffic away from it, e.g. to only follow the Wi-Fi.
+  ///
+  /// The same as `NWPathMonitor.newWithType` on macOS. `current` and `history` still describe every interface.
+  pub fn new_with_type(env: Env, interface_type: NWInterfaceType) -> Result<Self> {
+    let mut monitor = Self::new(env, None)?;
+    monitor.interface_type = Some(interface_type);
+    Ok(monitor)
+  }
+
+  #[napi(factory)]
+  /// Create an InternetMonitor whose `start` uses a weak reference, it will not keep the Node.js alive.
+  ///
+  /// Use `startStrong` to opt out for a single listener.
+  pub fn new_weak_by_default(env: Env) -> Result<Self> {
+    let mut monitor = Self::new(env, None)?;
+    monitor.weak_by_default = true;
+    Ok(monitor)
+  }
+
+  #[napi(ts_return_type = "Promise<InternetMonitor>")]
+  /// Create the InternetMonitor on the libuv thread pool, so connecting to NetworkManager won't block the JavaScript thread.
+  pub fn create(options: Option<CreateOptions>) -> AsyncTask<CreateInternetMonitor> {
+    let options = options.unwrap_or_default();
+    AsyncTask::new(CreateInternetMonitor {
+      timeout_ms: options.timeout_ms,
+      history_capacity: options.history_capacity,
+    })
+  }
 
-    network_changed_cb(client, std::ptr::null_mut(), std::ptr::null_mut());
+  fn from_client(env: Env, client: NetworkManagerClient) -> Result<Self> {
+    let context = Arc::new(MonitorContext {
+      network_info: Mutex::new(client.network_info.clone()),
+      handler: Mutex::new(None),
+    });
+    // The environment (Electron renderer, worker thread) may be torn down before the monitor is dropped,
+    // the signal handler must not call into its threadsafe functions after that.
+    let cleanup_hook = EnvCleanupHook::new(&env, {
+      let context = context.clone();
+      move || {
+        context.handler.lock().unwrap().take();
+      }
+    })?;
 
-    let lo = MainLoopWrapper(unsafe { ffi::g_main_loop_new(core::ptr::null_mut(), 0) });
+    let lo = MainLoopWrapper(unsafe { ffi::g_main_loop_new(client.main_context, 0) });
     let thread_handle = std::thread::spawn(move || {
       let l = lo;
       // SAFETY: we know we already init it before AND no other thread will access it.
@@ -73,21 +279,126 @@ impl InternetMonitor {
     });
 
     Ok(Self {
-      client,
-      signal_id: Arc::new(Mutex::new(None)),
+      context,
+      cleanup_hook: Some(cleanup_hook),
+      signal_id: None,
       thread_handle: Some(thread_handle),
       lo,
+      weak_by_default: false,
+      is_disposed: AtomicBool::new(false),
+      throughput_sampler: None,
+      emitter: Emitter::default(),
+      history: History::default(),
+      ready: Ready::default(),
+      interface_type: None,
+      client,
     })
   }
 
   #[napi]
-  pub fn current(&self) -> NetworkInfo {
-    NETWORK_INFO.lock().unwrap().clone()
+  /// The last snapshots delivered to the callback, oldest first, `historyCapacity` of them at most.
+  ///
+  /// They are recorded while the monitor is started, whichever callback it's started with.
+  pub fn history(&self) -> Result<Vec<NetworkHistoryEntry>> {
+    self.check_open()?;
+    Ok(self.history.entries())
+  }
+
+  #[napi]
+  pub fn current(&self) -> Result<NetworkInfo> {
+    self.check_open()?;
+    Ok(self.context.network_info.lock().unwrap().clone())
+  }
+
+  #[napi]
+  /// Evaluate every field again instead of waiting for the next event, and return the new snapshot,
+  /// e.g. after a VPN was connected by another library and the events are late.
+  ///
+  /// The callback passed to `start` is not called.
+  pub fn refresh(&self) -> Result<NetworkInfo> {
+    self.check_open()?;
+    let info = self.client.invoke(get_network_info)?;
+    *self.context.network_info.lock().unwrap() = info.clone();
+    Ok(info)
+  }
+
+  #[napi]
+  /// The state of every NetworkManager device, `current` only describes the primary connection.
+  pub fn current_all(&self) -> Result<Vec<InterfaceNetworkInfo>> {
+    self.check_open()?;
+    let status = self.context.network_info.lock().unwrap().status;
+    self
+      .client
+      .invoke(move |client| current_all(client, status))
+  }
+
+  #[napi]
+  /// The category of the connected network, Windows is the only platform which has one,
+  /// the other platforms always return `Unknown`.
+  pub fn network_category(&self) -> Result<NetworkCategory> {
+    self.check_open()?;
+    Ok(NetworkCategory::Unknown)
+  }
+
+  #[napi]
+  /// Every connection profile saved in NetworkManager, active or not, e.g. for a "known networks" UI.
+  ///
+  /// Not available on macOS, Network.framework only describes the current path.
+  pub fn list_networks(&self) -> Result<Vec<KnownNetwork>> {
+    self.check_open()?;
+    let status = self.context.network_info.lock().unwrap().status;
+    self
+      .client
+      .invoke(move |client| list_networks(client, status))
+  }
+
+  #[napi]
+  /// A coarse quality tier of the current connection, see `ConnectionClass` for the thresholds.
+  pub fn connection_class(&self) -> Result<ConnectionClass> {
+    self.check_open()?;
+    Ok(ConnectionClass::new(
+      &self.context.network_info.lock().unwrap(),
+    ))
+  }
+
+  #[napi]
+  /// It's safe to do a large download right now: the network is `Satisfied`, not expensive, not in Low Data Mode and not roaming.
+  pub fn can_use_unmetered(&self) -> Result<bool> {
+    self.check_open()?;
+    let network_info = self.context.network_info.lock().unwrap();
+    Ok(network_info.can_use_unmetered())
+  }
+
+  #[napi]
+  /// Whether the machine runs on battery, only reported on Windows for now, `null` on the other platforms.
+  pub fn power_state(&self) -> Result<Option<PowerState>> {
+    self.check_open()?;
+    Ok(None)
+  }
+
+  #[napi]
+  /// Serialize the current `NetworkInfo` to JSON with the schema shared by every platform, for logging and telemetry.
+  pub fn snapshot_json(&self) -> Result<String> {
+    self.check_open()?;
+    crate::snapshot_json(&self.context.network_info.lock().unwrap())
   }
 
   #[napi]
   /// Start the InternetMonitor, it will keep the Node.js alive unless you call stop on it.
+  ///
+  /// Monitors created with `newWeakByDefault` don't keep the Node.js alive, the same as `startWeak`.
   pub fn start(&mut self, on_update: Function<NetworkInfo, ()>) -> Result<()> {
+    if self.weak_by_default {
+      self.start_weak(on_update)
+    } else {
+      self.start_strong(on_update)
+    }
+  }
+
+  #[napi]
+  /// Start with a strong reference whatever the monitor was created with, it will keep the Node.js alive unless you call stop on it.
+  pub fn start_strong(&mut self, on_update: Function<NetworkInfo, ()>) -> Result<()> {
+    self.check_open()?;
     let change_handler = Arc::new(
       on_update
         .build_threadsafe_function()
@@ -101,6 +412,7 @@ impl InternetMonitor {
   #[napi]
   /// Start the InternetMonitor with weak reference, it will not keep the Node.js alive.
   pub fn start_weak(&mut self, on_update: Function<NetworkInfo, ()>) -> Result<()> {
+    self.check_open()?;
     let change_handler = Arc::new(
       on_update
         .build_threadsafe_function()
@@ -111,87 +423,389 @@ impl InternetMonitor {
     self.start_inner::<true>(change_handler)
   }
 
+  #[napi]
+  /// Emit `RetrySuggested` events with an exponential backoff while the network is down,
+  /// and a single `Reconnected` event once it's back.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn start_with_backoff(
+    &mut self,
+    options: Option<BackoffOptions>,
+    on_event: Function<BackoffEvent, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::backoff::change_handler(options.unwrap_or_default(), on_event, self.weak_by_default)?;
+    // the monitor may already be offline, start counting from now
+    handler(self.current()?);
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onChange` only when `isExpensive` or `isLowDataMode` flips, the connectivity changes are ignored.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_metered_change(&mut self, on_change: Function<MeteredStatus, ()>) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::metered::change_handler(Some(&self.current()?), on_change, self.weak_by_default)?;
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onChange` with the new `canUseUnmetered` when it flips, e.g. to pause the large downloads when the network
+  /// becomes expensive, Low Data Mode is turned on, roaming starts or the connection is lost.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_unmetered_change(&mut self, on_change: Function<bool, ()>) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::unmetered::change_handler(Some(&self.current()?), on_change, self.weak_by_default)?;
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onChange` with the new interface type when the traffic moves to another one, e.g. from Wi-Fi to cellular
+  /// or from Ethernet to Wi-Fi, to reset the connections even though the status stays `Satisfied`.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_primary_interface_change(
+    &mut self,
+    on_change: Function<NWInterfaceType, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::primary::change_handler(Some(&self.current()?), on_change, self.weak_by_default)?;
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onReconnect` with how long the network was offline, in milliseconds, when the status becomes `Satisfied` again,
+  /// e.g. to size the retry windows. Every offline episode starts the timer over.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_reconnect(&mut self, on_reconnect: Function<f64, ()>) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::reconnect::change_handler(Some(&self.current()?), on_reconnect, self.weak_by_default)?;
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onOnline` once the network stayed `Satisfied` for `minStableMs` in a row, any other status in between
+  /// starts the wait over. Unlike the first `Satisfied` update, it's not fired while the connectivity flaps.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_stable_online(
+    &mut self,
+    min_stable_ms: u32,
+    on_online: Function<NetworkInfo, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler = crate::stable::change_handler(min_stable_ms, on_online, self.weak_by_default)?;
+    // the monitor may already be online, start counting from now
+    handler(self.current()?);
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onUpdate` only with the snapshots matching `filter`, a combination of `ChangeFilter` flags,
+  /// the other ones are dropped before they cross into JavaScript.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn start_filtered(
+    &mut self,
+    filter: u32,
+    on_update: Function<NetworkInfo, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler = crate::filter::change_handler(
+      filter,
+      Some(&self.current()?),
+      on_update,
+      self.weak_by_default,
+    )?;
+    self.start_with_handler(handler)
+  }
+
+  #[napi(ts_args_type = "event: MonitorEvent, listener: (arg: any) => void")]
+  /// Add a listener for `event`, like `EventEmitter.on`. The `change`, `online` and `offline` listeners
+  /// receive a `NetworkInfo`, the `metered` ones a `MeteredStatus` and the `error` ones an `Error`.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on(&mut self, event: MonitorEvent, listener: Function<EventPayload, ()>) -> Result<()> {
+    self.check_open()?;
+    self.emitter.on(event, listener, self.weak_by_default)?;
+    let handler = self.emitter.change_handler(Some(&self.current()?));
+    self.start_with_handler(handler)
+  }
+
+  #[napi(ts_args_type = "event: MonitorEvent, listener: (arg: any) => void")]
+  /// Remove a listener added by `on`, the monitor is stopped once the last one is removed.
+  pub fn off(
+    &mut self,
+    env: Env,
+    event: MonitorEvent,
+    listener: Function<EventPayload, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    if self.emitter.off(&env, event, listener)? && self.emitter.is_empty() {
+      self.unsubscribe();
+    }
+    Ok(())
+  }
+
+  #[napi(ts_return_type = "Promise<void>")]
+  /// Resolve once the monitor saw a `Satisfied` status, right away when it already did,
+  /// so it can be awaited before every request cheaply.
+  ///
+  /// It never resets, going offline afterwards doesn't make it wait again, listen to `offline` with `on` for that.
+  /// A monitor which is not started is started without a callback, `stop` rejects the pending promises.
+  pub fn connected(&mut self, env: Env) -> Result<JsObject> {
+    self.check_open()?;
+    let promise = self.ready.wait(&env, Some(&self.current()?))?;
+    if self.ready.is_pending() && self.context.handler.lock().unwrap().is_none() {
+      // the snapshots only resolve the promises
+      self.start_with_handler(Box::new(|_| {}))?;
+    }
+    Ok(promise)
+  }
+
+  #[napi(ts_return_type = "Promise<void>")]
+  /// Alias of `connected`.
+  pub fn ready(&mut self, env: Env) -> Result<JsObject> {
+    self.connected(env)
+  }
+
   fn start_inner<const WEAK: bool>(
     &mut self,
     change_handler: Arc<ThreadsafeFunction<NetworkInfo, (), NetworkInfo, false, { WEAK }>>,
   ) -> Result<()> {
-    let change_handler_for_cost = change_handler.clone();
+    self.start_with_handler(Box::new(move |info| {
+      change_handler.call(info, ThreadsafeFunctionCallMode::Blocking);
+    }))
+  }
 
-    GLOBAL_HANDLER
-      .lock()
-      .unwrap()
-      .replace(Box::new(move |info| {
-        change_handler_for_cost.call(info, ThreadsafeFunctionCallMode::Blocking);
-      }));
+  fn start_with_handler(&mut self, handler: ChangeHandler) -> Result<()> {
+    let handler = match self.interface_type {
+      Some(interface_type) => {
+        InterfaceTypeFilter::new(interface_type, &self.context.network_info.lock().unwrap())
+          .watch(handler)
+      }
+      None => handler,
+    };
+    let handler = self.ready.watch(self.history.record(handler));
+    self.context.handler.lock().unwrap().replace(handler);
 
-    let signal_id = self.signal_id.clone();
-    unsafe {
-      signal_id.lock().unwrap().replace(ffi::g_signal_connect(
-        self.client,
-        SIGNAL_NAME.as_ptr(),
-        network_changed_cb,
-        std::ptr::null_mut(),
-      ));
+    if self.signal_id.is_none() {
+      // The signal handler owns a strong reference of the context, it's released by `release_context` once the handler is disconnected.
+      self.signal_id = Some(unsafe {
+        ffi::g_signal_connect_data(
+          self.client.client,
+          SIGNAL_NAME.as_ptr(),
+          network_changed_cb,
+          Arc::into_raw(self.context.clone()) as *mut core::ffi::c_void,
+          Some(release_context),
+          0,
+        )
+      });
     }
 
     Ok(())
   }
 
+  #[napi]
+  /// Sample the byte counters of the physical interfaces every `intervalMs` on a separate thread,
+  /// `throughput` returns the rate between the last two samples. Starting it again restarts the sampling.
+  ///
+  /// It measures the total traffic of the machine, not the traffic of the app.
+  /// The sampling is independent of `start` and `stop`, it runs until `stopThroughput` or `close`.
+  pub fn start_throughput(&mut self, interval_ms: u32) -> Result<()> {
+    self.check_open()?;
+    // join the previous sampling thread before spawning another one
+    self.throughput_sampler.take();
+    self.throughput_sampler = Some(ThroughputSampler::start(interval_ms)?);
+    Ok(())
+  }
+
+  #[napi]
+  /// The received and sent bytes per second, `null` until `startThroughput` sampled twice.
+  pub fn throughput(&self) -> Result<Option<Throughput>> {
+    self.check_open()?;
+    Ok(
+      self
+        .throughput_sampler
+        .as_ref()
+        .and_then(ThroughputSampler::throughput),
+    )
+  }
+
+  #[napi]
+  /// Stop the sampling started by `startThroughput`.
+  pub fn stop_throughput(&mut self) -> Result<()> {
+    self.check_open()?;
+    self.throughput_sampler.take();
+    Ok(())
+  }
+
   #[napi]
   /// Stop the InternetMonitor.
   ///
   /// If you don't call this method and leave the monitor alone, it will be stopped automatically when it is GC.
-  pub fn stop(&mut self) {
-    let signal_id = self.signal_id.lock().unwrap().take();
-    unsafe {
-      if let Some(signal_id) = signal_id {
-        ffi::g_signal_handler_disconnect(self.client, signal_id);
+  pub fn stop(&mut self) -> Result<()> {
+    self.check_open()?;
+    self.unsubscribe();
+    Ok(())
+  }
+
+  #[napi]
+  /// Stop the InternetMonitor and release its callback, every method throws once it's closed.
+  ///
+  /// Calling `close` again is a no-op.
+  pub fn close(&mut self) {
+    if self.is_disposed.swap(true, Ordering::AcqRel) {
+      return;
+    }
+    self.throughput_sampler.take();
+    self.release();
+  }
+
+  fn check_open(&self) -> Result<()> {
+    if self.is_disposed.load(Ordering::Acquire) {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "monitor has been disposed",
+      ));
+    }
+    Ok(())
+  }
+
+  /// Disconnect the signal handler and stop the main loop thread, it's a no-op if it's already released.
+  fn release(&mut self) {
+    self.cleanup_hook.take();
+    self.unsubscribe();
+    if let Some(thread_handle) = self.thread_handle.take() {
+      unsafe {
+        ffi::g_main_loop_quit(self.lo.0);
+      }
+      thread_handle.join().unwrap();
+    }
+  }
+
+  /// Disconnect the signal handler and release the callback, it's a no-op if it's already disconnected.
+  fn unsubscribe(&mut self) {
+    if let Some(signal_id) = self.signal_id.take() {
+      unsafe {
+        ffi::g_signal_handler_disconnect(self.client.client, signal_id);
       }
     }
+    self.context.handler.lock().unwrap().take();
+    self.emitter.clear();
+    self.ready.cancel();
   }
 }
 
+#[cfg(feature = "napi")]
 #[inline]
 fn ctx_to_path(ctx: ThreadsafeCallContext<NetworkInfo>) -> Result<NetworkInfo> {
   Ok(ctx.value)
 }
 
+#[cfg(feature = "napi")]
+#[napi]
+/// Read the network state once, for CLI tools and health checks which don't need a long-lived monitor.
+///
+/// A transient NetworkManager client is created and released before returning.
+pub fn get_global_network_info() -> Result<NetworkInfo> {
+  Ok(current_network_info()?)
+}
+
+pub(crate) fn current_network_info() -> native::Result<NetworkInfo> {
+  Ok(NetworkManagerClient::new()?.network_info.clone())
+}
+
+#[cfg(feature = "napi")]
 extern "C" fn network_changed_cb(
   client: *mut ffi::NMClient,
   _: *mut core::ffi::c_void,
-  _: *mut core::ffi::c_void,
+  user_data: *mut core::ffi::c_void,
 ) {
-  let mut info = NETWORK_INFO.lock().unwrap();
+  // SAFETY: `user_data` is the `MonitorContext` of the monitor which connected this signal.
+  let Some(context) = (unsafe { (user_data as *const MonitorContext).as_ref() }) else {
+    return;
+  };
+  if client.is_null() {
+    return;
+  }
+  let info = get_network_info(client);
+  *context.network_info.lock().unwrap() = info.clone();
+
+  if let Some(f) = context.handler.lock().unwrap().as_ref() {
+    f(info)
+  }
+}
+
+#[cfg(feature = "napi")]
+extern "C" fn release_context(user_data: *mut core::ffi::c_void, _: *mut core::ffi::c_void) {
+  // SAFETY: `user_data` was created by `Arc::into_raw` when the signal was connected
+  drop(unsafe { Arc::from_raw(user_data as *const MonitorContext) });
+}
+
+fn get_network_info(client: *mut ffi::NMClient) -> NetworkInfo {
+  let mut info = NetworkInfo {
+    status: NetworkStatus::Invalid,
+    ..Default::default()
+  };
 
   let metered = unsafe { ffi::nm_client_get_metered(client) };
-  info.is_low_data_mode = matches!(
-    metered,
-    ffi::NMMetered::NM_METERED_YES | ffi::NMMetered::NM_METERED_GUESS_YES
-  );
+  info.is_low_data_mode = is_metered(metered);
+  // Metered connections cost money, the same way a data limit does on Windows
+  info.is_expensive = is_metered(metered);
+
+  // `connection.metered` set by the user on any active connection
+  for active_connection in
+    ptr_array::<ffi::NMActiveConnection>(unsafe { ffi::nm_client_get_active_connections(client) })
+  {
+    let connection = unsafe { ffi::nm_active_connection_get_connection(active_connection) };
+    if connection.is_null() {
+      continue;
+    }
+    let setting = unsafe { ffi::nm_connection_get_setting_connection(connection) };
+    if !setting.is_null() && is_metered(unsafe { ffi::nm_setting_connection_get_metered(setting) })
+    {
+      info.is_expensive = true;
+    }
+  }
 
-  let devices = unsafe { &*ffi::nm_client_get_devices(client) };
-  for i in 0..devices.len {
-    let device = unsafe { (devices.pdata as *mut *mut ffi::NMDevice).add(i as usize) };
-    let device_type = unsafe { ffi::nm_device_get_device_type(*device) };
+  // the devices are null while NetworkManager restarts, the IP fields keep their defaults then
+  for device in ptr_array::<ffi::NMDevice>(unsafe { ffi::nm_client_get_devices(client) }) {
+    let device_type = unsafe { ffi::nm_device_get_device_type(device) };
 
     // Check if the connection is expensive (e.g., mobile broadband)
     if device_type == ffi::NMDeviceType::NM_DEVICE_TYPE_MODEM {
       info.is_expensive = true;
     }
 
+    // NetworkManager guesses the metered state of a device, e.g. a phone tethered over USB or a hotspot announcing ANDROID_METERED
+    if is_metered(unsafe { ffi::nm_device_get_metered(device) }) {
+      info.is_expensive = true;
+    }
+
     // Check for IPv4 connectivity
-    let ip4_config = unsafe { ffi::nm_device_get_ip4_config(*device) };
+    let ip4_config = unsafe { ffi::nm_device_get_ip4_config(device) };
     if !ip4_config.is_null() {
       info.has_ipv4 = true;
     }
 
     // Check for IPv6 connectivity
-    let ip6_config = unsafe { ffi::nm_device_get_ip6_config(*device) };
+    let ip6_config = unsafe { ffi::nm_device_get_ip6_config(device) };
     if !ip6_config.is_null() {
       info.has_ipv6 = true;
     }
   }
 
+  info.stack = IpStackType::new(info.has_ipv4, info.has_ipv6);
+
   // Check DNS configuration from global NM settings
   let active_conn = unsafe { ffi::nm_client_get_primary_connection(client) };
   if !active_conn.is_null() {
@@ -199,6 +813,15 @@ extern "C" fn network_changed_cb(
     if !ip_config.is_null() && !unsafe { ffi::nm_ip_config_get_nameservers(ip_config) }.is_null() {
       info.has_dns = true;
     }
+    if let Some(device) = primary_device(active_conn) {
+      let interface_type = interface_type(device);
+      info.interface_type = Some(interface_type);
+      info.uses_wifi = interface_type == NWInterfaceType::Wifi;
+      info.uses_cellular = interface_type == NWInterfaceType::Cellular;
+      info.uses_wired = interface_type == NWInterfaceType::Wired;
+      info.uses_loopback = interface_type == NWInterfaceType::Loopback;
+      info.link_speed_mbps = link_speed_mbps(device);
+    }
   }
 
   // Determine network status
@@ -219,9 +842,176 @@ extern "C" fn network_changed_cb(
     }
   }
 
-  if let Some(f) = GLOBAL_HANDLER.lock().unwrap().as_ref() {
-    f(info.clone())
+  info
+}
+
+#[cfg(feature = "napi")]
+/// Every device, only the device of the primary connection can reach the Internet, the other activated ones are `Satisfiable`.
+fn current_all(client: *mut ffi::NMClient, status: NetworkStatus) -> Vec<InterfaceNetworkInfo> {
+  let primary_connection = unsafe { ffi::nm_client_get_primary_connection(client) };
+  let primary_device = if primary_connection.is_null() {
+    None
+  } else {
+    primary_device(primary_connection)
+  };
+
+  ptr_array::<ffi::NMDevice>(unsafe { ffi::nm_client_get_devices(client) })
+    .into_iter()
+    .map(|device| {
+      let iface = unsafe { ffi::nm_device_get_iface(device) };
+      let name = if iface.is_null() {
+        String::new()
+      } else {
+        unsafe { std::ffi::CStr::from_ptr(iface) }
+          .to_string_lossy()
+          .into_owned()
+      };
+      let interface_type = interface_type(device);
+      let activated = unsafe { ffi::nm_device_get_state(device) }
+        == ffi::NMDeviceState::NM_DEVICE_STATE_ACTIVATED;
+      let status = if !activated {
+        NetworkStatus::Unsatisfied
+      } else if primary_device == Some(device) && matches!(status, NetworkStatus::Satisfied) {
+        NetworkStatus::Satisfied
+      } else {
+        NetworkStatus::Satisfiable
+      };
+      let mut addresses = ip_addresses(unsafe { ffi::nm_device_get_ip4_config(device) });
+      addresses.extend(ip_addresses(unsafe {
+        ffi::nm_device_get_ip6_config(device)
+      }));
+      InterfaceNetworkInfo {
+        name,
+        interface_type,
+        status,
+        addresses,
+        is_metered: interface_type == NWInterfaceType::Cellular
+          || is_metered(unsafe { ffi::nm_device_get_metered(device) }),
+      }
+    })
+    .collect()
+}
+
+#[cfg(feature = "napi")]
+fn ip_addresses(ip_config: *mut ffi::NMIPConfig) -> Vec<String> {
+  if ip_config.is_null() {
+    return Vec::new();
   }
+  ptr_array::<ffi::NMIPAddress>(unsafe { ffi::nm_ip_config_get_addresses(ip_config) })
+    .into_iter()
+    .filter_map(|address| {
+      let address = unsafe { ffi::nm_ip_address_get_address(address) };
+      (!address.is_null()).then(|| {
+        unsafe { std::ffi::CStr::from_ptr(address) }
+          .to_string_lossy()
+          .into_owned()
+      })
+    })
+    .collect()
+}
+
+#[cfg(feature = "napi")]
+/// The saved connections, only the primary one can reach the Internet, the other active ones are `Satisfiable`.
+fn list_networks(client: *mut ffi::NMClient, status: NetworkStatus) -> Vec<KnownNetwork> {
+  let primary_connection = unsafe { ffi::nm_client_get_primary_connection(client) };
+  let primary_connection = if primary_connection.is_null() {
+    std::ptr::null_mut()
+  } else {
+    unsafe { ffi::nm_active_connection_get_connection(primary_connection) }
+  };
+  let active: Vec<_> =
+    ptr_array::<ffi::NMActiveConnection>(unsafe { ffi::nm_client_get_active_connections(client) })
+      .into_iter()
+      .map(|active_connection| unsafe {
+        ffi::nm_active_connection_get_connection(active_connection)
+      })
+      .collect();
+
+  ptr_array::<ffi::NMConnection>(unsafe { ffi::nm_client_get_connections(client) })
+    .into_iter()
+    .map(|connection| {
+      let id = unsafe { ffi::nm_connection_get_id(connection) };
+      let name = if id.is_null() {
+        String::new()
+      } else {
+        unsafe { std::ffi::CStr::from_ptr(id) }
+          .to_string_lossy()
+          .into_owned()
+      };
+      let is_connected = active.contains(&connection);
+      let connectivity = if !is_connected {
+        NetworkStatus::Unsatisfied
+      } else if connection == primary_connection && matches!(status, NetworkStatus::Satisfied) {
+        NetworkStatus::Satisfied
+      } else {
+        NetworkStatus::Satisfiable
+      };
+      KnownNetwork {
+        name,
+        is_connected,
+        connectivity,
+        category: NetworkCategory::Unknown,
+      }
+    })
+    .collect()
+}
+
+/// The first device of the primary connection, the one which carries the default route.
+fn primary_device(active_conn: *mut ffi::NMActiveConnection) -> Option<*mut ffi::NMDevice> {
+  ptr_array::<ffi::NMDevice>(unsafe { ffi::nm_active_connection_get_devices(active_conn) })
+    .first()
+    .copied()
+}
+
+/// The non-null elements of a `GPtrArray` owned by libnm, none when the array or its data is null.
+fn ptr_array<T>(array: *const ffi::GPtrArray) -> Vec<*mut T> {
+  // SAFETY: libnm returns either null or a valid array which lives as long as the client
+  let Some(array) = (unsafe { array.as_ref() }) else {
+    return Vec::new();
+  };
+  if array.pdata.is_null() || array.len == 0 {
+    return Vec::new();
+  }
+  unsafe { std::slice::from_raw_parts(array.pdata as *const *mut T, array.len as usize) }
+    .iter()
+    .copied()
+    .filter(|element| !element.is_null())
+    .collect()
+}
+
+fn interface_type(device: *mut ffi::NMDevice) -> NWInterfaceType {
+  match unsafe { ffi::nm_device_get_device_type(device) } {
+    ffi::NMDeviceType::NM_DEVICE_TYPE_WIFI => NWInterfaceType::Wifi,
+    ffi::NMDeviceType::NM_DEVICE_TYPE_MODEM => NWInterfaceType::Cellular,
+    ffi::NMDeviceType::NM_DEVICE_TYPE_ETHERNET => NWInterfaceType::Wired,
+    ffi::NMDeviceType::NM_DEVICE_TYPE_LOOPBACK => NWInterfaceType::Loopback,
+    _ => NWInterfaceType::Other,
+  }
+}
+
+/// NetworkManager only knows the speed of Ethernet and Wi-Fi devices, 0 means unknown.
+fn link_speed_mbps(device: *mut ffi::NMDevice) -> Option<u32> {
+  let speed = match unsafe { ffi::nm_device_get_device_type(device) } {
+    ffi::NMDeviceType::NM_DEVICE_TYPE_ETHERNET => unsafe {
+      ffi::nm_device_ethernet_get_speed(device)
+    },
+    // the Wi-Fi bitrate is in Kbit/s
+    ffi::NMDeviceType::NM_DEVICE_TYPE_WIFI => unsafe {
+      ffi::nm_device_wifi_get_bitrate(device) / 1000
+    },
+    _ => 0,
+  };
+  (speed != 0).then_some(speed)
+}
+
+/// A guess counts as metered everywhere, for the client, the connections and the devices alike:
+/// NetworkManager only guesses yes from a strong hint, e.g. a modem or a hotspot announcing ANDROID_METERED.
+#[inline]
+fn is_metered(metered: ffi::NMMetered) -> bool {
+  matches!(
+    metered,
+    ffi::NMMetered::NM_METERED_YES | ffi::NMMetered::NM_METERED_GUESS_YES
+  )
 }
 
 #[allow(non_camel_case_types)]
@@ -295,6 +1085,16 @@ mod ffi {
     _unused: [u8; 0],
   }
 
+  #[repr(C)]
+  pub struct NMConnection {
+    _unused: [u8; 0],
+  }
+
+  #[repr(C)]
+  pub struct NMSettingConnection {
+    _unused: [u8; 0],
+  }
+
   #[repr(C)]
   pub struct Cancellable {
     _unused: [u8; 0],
@@ -340,6 +1140,26 @@ mod ffi {
           NM_DEVICE_TYPE_WIREGUARD     = 29,
           NM_DEVICE_TYPE_WIFI_P2P      = 30,
           NM_DEVICE_TYPE_VRF           = 31,
+          NM_DEVICE_TYPE_LOOPBACK      = 32,
+      }
+  }
+
+  enum_with_val! {
+      #[derive(PartialEq, Eq, Clone, Copy)]
+      pub struct NMDeviceState(c_int) {
+          NM_DEVICE_STATE_UNKNOWN      = 0,
+          NM_DEVICE_STATE_UNMANAGED    = 10,
+          NM_DEVICE_STATE_UNAVAILABLE  = 20,
+          NM_DEVICE_STATE_DISCONNECTED = 30,
+          NM_DEVICE_STATE_PREPARE      = 40,
+          NM_DEVICE_STATE_CONFIG       = 50,
+          NM_DEVICE_STATE_NEED_AUTH    = 60,
+          NM_DEVICE_STATE_IP_CONFIG    = 70,
+          NM_DEVICE_STATE_IP_CHECK     = 80,
+          NM_DEVICE_STATE_SECONDARIES  = 90,
+          NM_DEVICE_STATE_ACTIVATED    = 100,
+          NM_DEVICE_STATE_DEACTIVATING = 110,
+          NM_DEVICE_STATE_FAILED       = 120,
       }
   }
 
@@ -348,26 +1168,56 @@ mod ffi {
     _unused: [u8; 0],
   }
 
+  #[repr(C)]
+  pub struct NMIPAddress {
+    _unused: [u8; 0],
+  }
+
   #[cfg_attr(any(target_os = "linux",), link(name = "nm", kind = "dylib"))]
   extern "C" {
     pub fn nm_client_new(callcellable: *mut Cancellable, error: *mut GError) -> *mut NMClient;
 
     pub fn nm_client_get_devices(client: *mut NMClient) -> *mut GPtrArray;
     pub fn nm_device_get_device_type(device: *mut NMDevice) -> NMDeviceType;
+    pub fn nm_device_get_iface(device: *mut NMDevice) -> *const c_char;
+    pub fn nm_device_get_state(device: *mut NMDevice) -> NMDeviceState;
     pub fn nm_device_get_ip4_config(device: *mut NMDevice) -> *mut NMIPConfig;
     pub fn nm_device_get_ip6_config(device: *mut NMDevice) -> *mut NMIPConfig;
     pub fn nm_client_get_primary_connection(device: *mut NMClient) -> *mut NMActiveConnection;
     pub fn nm_active_connection_get_ip4_config(device: *mut NMActiveConnection) -> *mut NMIPConfig;
+    pub fn nm_active_connection_get_devices(
+      active_connection: *mut NMActiveConnection,
+    ) -> *mut GPtrArray;
     pub fn nm_ip_config_get_nameservers(ip_config: *mut NMIPConfig) -> *mut GPtrArray;
+    pub fn nm_ip_config_get_addresses(ip_config: *mut NMIPConfig) -> *mut GPtrArray;
+    pub fn nm_ip_address_get_address(address: *mut NMIPAddress) -> *const c_char;
     pub fn nm_client_get_connectivity(client: *mut NMClient) -> NMConnectivityState;
     pub fn nm_client_get_metered(client: *mut NMClient) -> NMMetered;
+    pub fn nm_device_get_metered(device: *mut NMDevice) -> NMMetered;
+    pub fn nm_device_ethernet_get_speed(device: *mut NMDevice) -> guint32;
+    pub fn nm_device_wifi_get_bitrate(device: *mut NMDevice) -> guint32;
+    pub fn nm_client_get_active_connections(client: *mut NMClient) -> *mut GPtrArray;
+    pub fn nm_active_connection_get_connection(
+      active_connection: *mut NMActiveConnection,
+    ) -> *mut NMConnection;
+    pub fn nm_connection_get_setting_connection(
+      connection: *mut NMConnection,
+    ) -> *mut NMSettingConnection;
+    pub fn nm_setting_connection_get_metered(setting: *mut NMSettingConnection) -> NMMetered;
+    pub fn nm_client_get_connections(client: *mut NMClient) -> *mut GPtrArray;
+    pub fn nm_connection_get_id(connection: *mut NMConnection) -> *const c_char;
   }
 
   pub type gchar = c_char;
   pub type gulong = c_ulong;
   pub type gint = c_int;
-  pub type GClosureNotify = extern "C" fn();
+  pub type guint32 = u32;
+  pub type GClosureNotify = extern "C" fn(data: *mut c_void, closure: *mut c_void);
   pub type gboolean = gint;
+  pub type GSourceFunc = extern "C" fn(user_data: *mut c_void) -> gboolean;
+  pub type GDestroyNotify = extern "C" fn(data: *mut c_void);
+
+  pub const G_PRIORITY_DEFAULT: gint = 0;
 
   #[repr(C)]
   pub struct GMainContext {
@@ -380,7 +1230,7 @@ mod ffi {
   }
   #[cfg_attr(any(target_os = "linux",), link(name = "glib-2.0", kind = "dylib"))]
   extern "C" {
-    fn g_signal_connect_data(
+    pub fn g_signal_connect_data(
       instance: *mut NMClient,
       detailed_signal: *const gchar,
       c_handler: extern "C" fn(client: *mut NMClient, _: *mut c_void, user_data: *mut c_void),
@@ -390,17 +1240,26 @@ mod ffi {
     ) -> gulong;
     pub fn g_signal_handler_disconnect(instance: *mut NMClient, signal_id: gulong);
 
+    pub fn g_main_context_new() -> *mut GMainContext;
+    pub fn g_main_context_push_thread_default(context: *mut GMainContext);
+    pub fn g_main_context_pop_thread_default(context: *mut GMainContext);
+    pub fn g_main_context_unref(context: *mut GMainContext);
+    pub fn g_main_context_invoke_full(
+      context: *mut GMainContext,
+      priority: gint,
+      function: GSourceFunc,
+      data: *mut c_void,
+      notify: Option<GDestroyNotify>,
+    );
+
     pub fn g_main_loop_new(context: *mut GMainContext, is_running: gboolean) -> *mut GMainLoop;
     pub fn g_main_loop_run(lo: *mut GMainLoop);
     pub fn g_main_loop_quit(lo: *mut GMainLoop);
+    pub fn g_main_loop_unref(lo: *mut GMainLoop);
   }
 
-  pub unsafe fn g_signal_connect(
-    instance: *mut NMClient,
-    detailed_signal: *const gchar,
-    c_handler: extern "C" fn(client: *mut NMClient, _: *mut c_void, user_data: *mut c_void),
-    data: *mut c_void,
-  ) -> gulong {
-    g_signal_connect_data(instance, detailed_signal, c_handler, data, None, 0)
+  #[cfg_attr(any(target_os = "linux",), link(name = "gobject-2.0", kind = "dylib"))]
+  extern "C" {
+    pub fn g_object_unref(object: *mut c_void);
   }
 }
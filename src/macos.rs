@@ -1,141 +1,1930 @@
-use std::ffi::c_void;
+use std::cell::RefCell;
+#[cfg(feature = "napi")]
+use std::ffi::CString;
+use std::ffi::{c_void, CStr};
+#[cfg(feature = "napi")]
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::rc::Rc;
+#[cfg(feature = "napi")]
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{mpsc, OnceLock};
+#[cfg(feature = "napi")]
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use block2::RcBlock;
+#[cfg(feature = "napi")]
 use napi::bindgen_prelude::*;
+#[cfg(feature = "napi")]
 use napi::threadsafe_function::{ThreadsafeCallContext, ThreadsafeFunctionCallMode};
+#[cfg(feature = "napi")]
+use napi::JsObject;
+#[cfg(feature = "napi")]
 use napi_derive::napi;
+use objc2::rc::autoreleasepool;
 
-use crate::{NetworkInfo as NWPath, NetworkStatus as NWPathStatus};
+use crate::native;
+#[cfg(feature = "napi")]
+use crate::{
+  emitter::{Emitter, EventPayload},
+  history::{History, NetworkHistoryEntry},
+  ready::Ready,
+  throughput::{Throughput, ThroughputSampler},
+  BackoffEvent, BackoffOptions, ChangeHandler, ConnectionClass, InterfaceNetworkInfo,
+  MeteredStatus, MonitorEvent, NetworkCategory,
+};
+use crate::{
+  IpStackType, NWInterfaceType, NetworkInfo as NWPath, NetworkInterface,
+  NetworkStatus as NWPathStatus, UnsatisfiedReason,
+};
 
+impl From<ffi::nw_path_status_t> for NWPathStatus {
+  fn from(status: ffi::nw_path_status_t) -> Self {
+    match status {
+      ffi::nw_path_status_t::NW_PATH_STATUS_INVALID => NWPathStatus::Invalid,
+      ffi::nw_path_status_t::NW_PATH_STATUS_SATISFIED => NWPathStatus::Satisfied,
+      ffi::nw_path_status_t::NW_PATH_STATUS_UNSATISFIED => NWPathStatus::Unsatisfied,
+      ffi::nw_path_status_t::NW_PATH_STATUS_SATISFIABLE => NWPathStatus::Satisfiable,
+      _ => NWPathStatus::Unknown,
+    }
+  }
+}
+
+impl From<ffi::nw_path_unsatisfied_reason_t> for UnsatisfiedReason {
+  fn from(reason: ffi::nw_path_unsatisfied_reason_t) -> Self {
+    match reason {
+      ffi::nw_path_unsatisfied_reason_t::NW_PATH_UNSATISFIED_REASON_CELLULAR_DENIED => {
+        UnsatisfiedReason::CellularDenied
+      }
+      ffi::nw_path_unsatisfied_reason_t::NW_PATH_UNSATISFIED_REASON_WIFI_DENIED => {
+        UnsatisfiedReason::WifiDenied
+      }
+      ffi::nw_path_unsatisfied_reason_t::NW_PATH_UNSATISFIED_REASON_LOCAL_NETWORK_DENIED => {
+        UnsatisfiedReason::LocalNetworkDenied
+      }
+      ffi::nw_path_unsatisfied_reason_t::NW_PATH_UNSATISFIED_REASON_VPN_INACTIVE => {
+        UnsatisfiedReason::VpnInactive
+      }
+      _ => UnsatisfiedReason::NotAvailable,
+    }
+  }
+}
+
+impl From<ffi::nw_path_t> for NWPath {
+  fn from(path: ffi::nw_path_t) -> Self {
+    let has_ipv4 = unsafe { ffi::nw_path_has_ipv4(path) };
+    let has_ipv6 = unsafe { ffi::nw_path_has_ipv6(path) };
+    let status = unsafe { ffi::nw_path_get_status(path).into() };
+    let interfaces = interfaces(path);
+    NWPath {
+      status,
+      // `expensive` and `constrained` are two different things on Apple platforms:
+      // a path is expensive when it goes through cellular or a personal hotspot,
+      // it is constrained when the user turned on Low Data Mode for the interface.
+      // A cellular link without Low Data Mode is expensive only, a Wi-Fi with Low Data Mode is constrained only.
+      is_expensive: unsafe { ffi::nw_path_is_expensive(path) },
+      is_low_data_mode: unsafe { ffi::nw_path_is_constrained(path) },
+      has_ipv4,
+      has_ipv6,
+      has_dns: unsafe { ffi::nw_path_has_dns(path) },
+      stack: IpStackType::new(has_ipv4, has_ipv6),
+      interface_type: interface_type(path),
+      unsatisfied_reason: match status {
+        NWPathStatus::Unsatisfied | NWPathStatus::Unknown => unsatisfied_reason(path),
+        _ => None,
+      },
+      // Network.framework enumerates the interfaces in the order of preference
+      primary_interface: match status {
+        NWPathStatus::Satisfied | NWPathStatus::Satisfiable => interfaces.first().cloned(),
+        _ => None,
+      },
+      interfaces,
+      gateways: gateways(path),
+      // filled by the monitor, the path doesn't know about the DNS configuration changes
+      dns_generation: None,
+      uses_wifi: uses_interface_type(path, NWInterfaceType::Wifi),
+      uses_cellular: uses_interface_type(path, NWInterfaceType::Cellular),
+      uses_wired: uses_interface_type(path, NWInterfaceType::Wired),
+      uses_loopback: uses_interface_type(path, NWInterfaceType::Loopback),
+      // Network.framework doesn't tell if the cellular link is roaming nor the link speed,
+      // the rest is only reported on Windows
+      ..Default::default()
+    }
+  }
+}
+
+type NwPathGetUnsatisfiedReason =
+  unsafe extern "C" fn(path: ffi::nw_path_t) -> ffi::nw_path_unsatisfied_reason_t;
+
+/// `nw_path_get_unsatisfied_reason` is only available on macOS 11+, look it up at runtime
+/// instead of linking it so the addon still loads on older systems.
+fn unsatisfied_reason(path: ffi::nw_path_t) -> Option<UnsatisfiedReason> {
+  static GET_UNSATISFIED_REASON: OnceLock<Option<NwPathGetUnsatisfiedReason>> = OnceLock::new();
+  let get_unsatisfied_reason = GET_UNSATISFIED_REASON.get_or_init(|| {
+    let symbol = unsafe {
+      ffi::dlsym(
+        ffi::RTLD_DEFAULT,
+        c"nw_path_get_unsatisfied_reason".as_ptr(),
+      )
+    };
+    if symbol.is_null() {
+      None
+    } else {
+      // SAFETY: the symbol has the signature declared in <Network/path.h>
+      Some(unsafe { std::mem::transmute::<*mut c_void, NwPathGetUnsatisfiedReason>(symbol) })
+    }
+  });
+  get_unsatisfied_reason
+    .map(|get_unsatisfied_reason| unsafe { get_unsatisfied_reason(path) }.into())
+}
+
+/// `CTTelephonyNetworkInfo` is only available on iOS, `serviceCurrentRadioAccessTechnology` since iOS 12
+/// and `dataServiceIdentifier` since iOS 13, check them at runtime.
+#[cfg(all(feature = "napi", target_os = "ios"))]
+fn radio_access_technology() -> Option<String> {
+  use objc2::rc::Retained;
+  use objc2::runtime::{AnyClass, AnyObject};
+  use objc2::{msg_send, msg_send_id, sel};
+
+  #[link(name = "CoreTelephony", kind = "framework")]
+  extern "C" {}
+
+  let to_string = |string: &AnyObject| {
+    let utf8: *const std::ffi::c_char = unsafe { msg_send![string, UTF8String] };
+    (!utf8.is_null()).then(|| {
+      unsafe { CStr::from_ptr(utf8) }
+        .to_string_lossy()
+        .into_owned()
+    })
+  };
+  autoreleasepool(|_| {
+    let class = AnyClass::get("CTTelephonyNetworkInfo")?;
+    if !class.responds_to(sel!(serviceCurrentRadioAccessTechnology)) {
+      return None;
+    }
+    let network_info: Retained<AnyObject> = unsafe { msg_send_id![class, new] };
+    // keyed by the service of each SIM, e.g. `0000000100000001`
+    let technologies: Option<Retained<AnyObject>> =
+      unsafe { msg_send_id![&network_info, serviceCurrentRadioAccessTechnology] };
+    let technologies = technologies?;
+    let data_service: Option<Retained<AnyObject>> =
+      if class.responds_to(sel!(dataServiceIdentifier)) {
+        unsafe { msg_send_id![&network_info, dataServiceIdentifier] }
+      } else {
+        None
+      };
+    let technology: Option<Retained<AnyObject>> = match data_service {
+      Some(data_service) => unsafe { msg_send_id![&technologies, objectForKey: &*data_service] },
+      // a single SIM before iOS 13
+      None => unsafe {
+        let values: Retained<AnyObject> = msg_send_id![&technologies, allValues];
+        msg_send_id![&values, firstObject]
+      },
+    };
+    let technology = to_string(&technology?)?;
+    Some(
+      technology
+        .strip_prefix("CTRadioAccessTechnology")
+        .map(str::to_owned)
+        .unwrap_or(technology),
+    )
+  })
+}
+
+#[cfg(all(feature = "napi", not(target_os = "ios")))]
+fn radio_access_technology() -> Option<String> {
+  None
+}
+
+/// The most specific interface type used by the path, Network.framework doesn't tell which one is primary.
+fn interface_type(path: ffi::nw_path_t) -> Option<NWInterfaceType> {
+  [
+    NWInterfaceType::Wifi,
+    NWInterfaceType::Cellular,
+    NWInterfaceType::Wired,
+    NWInterfaceType::Loopback,
+    NWInterfaceType::Other,
+  ]
+  .into_iter()
+  .find(|interface_type| uses_interface_type(path, *interface_type))
+}
+
+#[inline]
+fn uses_interface_type(path: ffi::nw_path_t, interface_type: NWInterfaceType) -> bool {
+  unsafe { ffi::nw_path_uses_interface_type(path, interface_type.into()) }
+}
+
+/// Every interface used by the path, in the order of preference.
+fn interfaces(path: ffi::nw_path_t) -> Vec<NetworkInterface> {
+  let interfaces = Rc::new(RefCell::new(Vec::new()));
+  let collected = interfaces.clone();
+  let enumerate = RcBlock::new(move |interface: ffi::nw_interface_t| {
+    let name = unsafe { CStr::from_ptr(ffi::nw_interface_get_name(interface)) };
+    collected.borrow_mut().push(NetworkInterface {
+      name: name.to_string_lossy().into_owned(),
+      index: unsafe { ffi::nw_interface_get_index(interface) },
+      interface_type: unsafe { ffi::nw_interface_get_type(interface) }.into(),
+    });
+    // keep enumerating
+    true
+  });
+  // the enumeration is synchronous, the block is done when it returns
+  unsafe { ffi::nw_path_enumerate_interfaces(path, &enumerate) };
+  interfaces.take()
+}
+
+#[cfg(feature = "napi")]
+/// The addresses of every interface with its BSD name, Network.framework doesn't expose them.
+fn interface_addresses() -> Vec<(String, IpAddr)> {
+  let mut ifaddrs = std::ptr::null_mut();
+  if unsafe { ffi::getifaddrs(&mut ifaddrs) } != 0 {
+    return Vec::new();
+  }
+  let mut addresses = Vec::new();
+  let mut current = ifaddrs;
+  while !current.is_null() {
+    // SAFETY: the list is alive until freeifaddrs
+    let ifaddr = unsafe { &*current };
+    current = ifaddr.ifa_next;
+    if ifaddr.ifa_addr.is_null() {
+      continue;
+    }
+    let address: IpAddr = match unsafe { (*ifaddr.ifa_addr).sa_family } {
+      ffi::AF_INET => {
+        let sockaddr = unsafe { &*ifaddr.ifa_addr.cast::<ffi::sockaddr_in>() };
+        Ipv4Addr::from(u32::from_be(sockaddr.sin_addr)).into()
+      }
+      ffi::AF_INET6 => {
+        let sockaddr = unsafe { &*ifaddr.ifa_addr.cast::<ffi::sockaddr_in6>() };
+        Ipv6Addr::from(sockaddr.sin6_addr).into()
+      }
+      _ => continue,
+    };
+    let name = unsafe { CStr::from_ptr(ifaddr.ifa_name) };
+    addresses.push((name.to_string_lossy().into_owned(), address));
+  }
+  unsafe { ffi::freeifaddrs(ifaddrs) };
+  addresses
+}
+
+#[cfg(feature = "napi")]
+/// The bytes of the Ethernet, Wi-Fi and cellular interfaces, the bridges and the VPN tunnels would count
+/// the same traffic twice. The counters of `if_data` are 32 bits and wrap around every 4 GiB.
+pub(crate) fn byte_counters() -> std::io::Result<crate::throughput::ByteCounters> {
+  let mut ifaddrs = std::ptr::null_mut();
+  if unsafe { ffi::getifaddrs(&mut ifaddrs) } != 0 {
+    return Err(std::io::Error::last_os_error());
+  }
+  let mut counters = crate::throughput::ByteCounters::default();
+  let mut current = ifaddrs;
+  while !current.is_null() {
+    // SAFETY: the list is alive until freeifaddrs
+    let ifaddr = unsafe { &*current };
+    current = ifaddr.ifa_next;
+    if ifaddr.ifa_addr.is_null()
+      || ifaddr.ifa_data.is_null()
+      || unsafe { (*ifaddr.ifa_addr).sa_family } != ffi::AF_LINK
+    {
+      continue;
+    }
+    let data = unsafe { &*ifaddr.ifa_data.cast::<ffi::if_data>() };
+    if matches!(data.ifi_type, ffi::IFT_ETHER | ffi::IFT_CELLULAR) {
+      counters.rx_bytes += data.ifi_ibytes as u64;
+      counters.tx_bytes += data.ifi_obytes as u64;
+    }
+  }
+  unsafe { ffi::freeifaddrs(ifaddrs) };
+  Ok(counters)
+}
+
+/// The gateways of the path as strings, an unsatisfied path has none.
+fn gateways(path: ffi::nw_path_t) -> Vec<String> {
+  let gateways = Rc::new(RefCell::new(Vec::new()));
+  let collected = gateways.clone();
+  let enumerate = RcBlock::new(move |gateway: ffi::nw_endpoint_t| {
+    if let Some(gateway) = endpoint_to_string(gateway) {
+      collected.borrow_mut().push(gateway);
+    }
+    // keep enumerating
+    true
+  });
+  // the enumeration is synchronous and the endpoints are only borrowed by the block
+  unsafe { ffi::nw_path_enumerate_gateways(path, &enumerate) };
+  gateways.take()
+}
+
+fn endpoint_to_string(endpoint: ffi::nw_endpoint_t) -> Option<String> {
+  match unsafe { ffi::nw_endpoint_get_type(endpoint) } {
+    ffi::nw_endpoint_type_t::NW_ENDPOINT_TYPE_ADDRESS => {
+      let address = unsafe { ffi::nw_endpoint_copy_address_string(endpoint) };
+      if address.is_null() {
+        return None;
+      }
+      let gateway = unsafe { CStr::from_ptr(address) }
+        .to_string_lossy()
+        .into_owned();
+      // the string is copied, it's owned by the caller
+      unsafe { ffi::free(address.cast()) };
+      Some(gateway)
+    }
+    ffi::nw_endpoint_type_t::NW_ENDPOINT_TYPE_HOST => {
+      let hostname = unsafe { ffi::nw_endpoint_get_hostname(endpoint) };
+      (!hostname.is_null()).then(|| {
+        unsafe { CStr::from_ptr(hostname) }
+          .to_string_lossy()
+          .into_owned()
+      })
+    }
+    _ => None,
+  }
+}
+
+impl From<ffi::nw_interface_type_t> for NWInterfaceType {
+  fn from(interface_type: ffi::nw_interface_type_t) -> Self {
+    match interface_type {
+      1 => NWInterfaceType::Wifi,
+      2 => NWInterfaceType::Cellular,
+      3 => NWInterfaceType::Wired,
+      4 => NWInterfaceType::Loopback,
+      _ => NWInterfaceType::Other,
+    }
+  }
+}
+
+impl From<NWInterfaceType> for ffi::nw_interface_type_t {
+  fn from(interface_type: NWInterfaceType) -> Self {
+    match interface_type {
+      NWInterfaceType::Other => 0,
+      NWInterfaceType::Wifi => 1,
+      NWInterfaceType::Cellular => 2,
+      NWInterfaceType::Wired => 3,
+      NWInterfaceType::Loopback => 4,
+    }
+  }
+}
+
+#[cfg(feature = "napi")]
+const ALL_INTERFACE_TYPES: [NWInterfaceType; 5] = [
+  NWInterfaceType::Other,
+  NWInterfaceType::Wifi,
+  NWInterfaceType::Cellular,
+  NWInterfaceType::Wired,
+  NWInterfaceType::Loopback,
+];
+
+#[cfg_attr(feature = "napi", napi(string_enum))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// The quality-of-service class of the dispatch queue which runs the path updates.
+pub enum QosClass {
+  /// QOS_CLASS_USER_INTERACTIVE Work the user is actively interacting with
+  UserInteractive,
+  /// QOS_CLASS_USER_INITIATED Work the user started and is waiting for, e.g. a latency sensitive app
+  UserInitiated,
+  /// QOS_CLASS_DEFAULT The default one
+  #[default]
+  Default,
+  /// QOS_CLASS_UTILITY Long running work the user is not waiting for
+  Utility,
+  /// QOS_CLASS_BACKGROUND Work the user is not aware of, e.g. a background utility app
+  Background,
+}
+
+impl From<QosClass> for ffi::dispatch_qos_class_t {
+  fn from(qos: QosClass) -> Self {
+    match qos {
+      QosClass::UserInteractive => ffi::dispatch_qos_class_t::QOS_CLASS_USER_INTERACTIVE,
+      QosClass::UserInitiated => ffi::dispatch_qos_class_t::QOS_CLASS_USER_INITIATED,
+      QosClass::Default => ffi::dispatch_qos_class_t::QOS_CLASS_DEFAULT,
+      QosClass::Utility => ffi::dispatch_qos_class_t::QOS_CLASS_UTILITY,
+      QosClass::Background => ffi::dispatch_qos_class_t::QOS_CLASS_BACKGROUND,
+    }
+  }
+}
+
+#[cfg(feature = "napi")]
+#[napi(object)]
+pub struct NWPathMonitorOptions {
+  /// Only consider paths over this interface type, the same as `newWithType`.
+  pub required: Option<NWInterfaceType>,
+  /// Never consider paths over these interface types, e.g. "the default path but never cellular".
+  pub prohibited: Option<Vec<NWInterfaceType>>,
+  /// The quality-of-service class of the path updates, `Default` by default.
+  pub qos: Option<QosClass>,
+  /// Skip the updates whose path is equal to the previous one, e.g. during a DHCP renewal, `true` by default.
+  pub only_on_change: Option<bool>,
+  /// The label of the dispatch queue running the path updates, to recognize it in the debugger and Instruments,
+  /// `rs.napi.network-change.path-monitor` by default.
+  pub queue_label: Option<String>,
+  /// Run the path updates on the main dispatch queue instead of a background serial queue, `false` by default.
+  ///
+  /// The updates only run while the host drives the main run loop, e.g. the main process of Electron,
+  /// a plain Node.js never does and the monitor would stay silent. It can't be combined with `qos` or `queueLabel`.
+  pub main_queue: Option<bool>,
+  /// How many paths `history` keeps, 32 by default, 0 turns the history off.
+  pub history_capacity: Option<u32>,
+}
+
+#[cfg(feature = "napi")]
+#[napi]
+/// A monitor that watches for changes in network path status.
+pub struct NWPathMonitor {
+  pm: ffi::nw_path_monitor_t,
+  /// How `pm` was created, to create it again when the monitor is started after `stop`
+  kind: MonitorKind,
+  /// A serial queue, so the updates reach JavaScript in the order Network.framework produced them.
+  /// The main queue with `mainQueue`, which is serial as well.
+  queue: ffi::dispatch_queue_t,
+  started: bool,
+  /// `nw_path_monitor_cancel` was called by `stop`, it's only called once and the next start replaces `pm`
+  cancelled: bool,
+  weak_by_default: bool,
+  only_on_change: bool,
+  /// The last path delivered to the update handler, updates with an equal path are skipped when `only_on_change` is set.
+  last_path: Arc<Mutex<Option<RetainedPath>>>,
+  dns: Arc<DnsState>,
+  /// Only the standalone monitors watch the DNS configuration, a composite monitor would report every change three times.
+  watch_dns: bool,
+  dns_watcher: Option<DnsWatcher>,
+  /// Taken by the cancel handler of the monitor, which runs once after `stop`.
+  cancel_handler: Arc<Mutex<Option<CancelHandler>>>,
+  /// The installed update block, `start` and `startWeak` replace it and `stop` clears it,
+  /// so the threadsafe function it captures doesn't outlive the registration.
+  update_handler: Option<RcBlock<dyn Fn(*mut c_void)>>,
+  /// The sampling thread of `startThroughput`
+  throughput_sampler: Option<ThroughputSampler>,
+  /// The listeners of `on`
+  emitter: Emitter,
+  history: History,
+  /// The pending promises of `connected`
+  ready: Ready,
+}
+
+#[cfg(feature = "napi")]
+type UpdateHandler = Arc<dyn Fn(NWPath) + Send + Sync>;
+#[cfg(feature = "napi")]
+type CancelHandler = Box<dyn FnOnce() + Send>;
+
+#[cfg(feature = "napi")]
+#[derive(Default)]
+struct DnsState {
+  generation: AtomicU32,
+  /// The latest path delivered to the update handler, delivered again when the DNS configuration changes.
+  latest: Mutex<Option<NWPath>>,
+  handler: Mutex<Option<UpdateHandler>>,
+}
+
+#[cfg(feature = "napi")]
+/// Watches the `State:/Network/Global/DNS` key of the SCDynamicStore, `nw_path_has_dns` doesn't change
+/// when the resolvers do. The notifications run on the queue of the monitor, after its path updates.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+struct DnsWatcher {
+  store: ffi::SCDynamicStoreRef,
+}
+
+#[cfg(feature = "napi")]
+impl DnsWatcher {
+  #[cfg(target_os = "macos")]
+  fn new(state: &Arc<DnsState>, queue: ffi::dispatch_queue_t) -> Option<Self> {
+    let mut context = ffi::SCDynamicStoreContext {
+      version: 0,
+      info: Arc::as_ptr(state) as *mut c_void,
+      retain: Some(retain_arc::<DnsState>),
+      release: Some(release_arc::<DnsState>),
+      copy_description: None,
+    };
+    unsafe {
+      let name = cfstring(c"rs.napi.network-change");
+      let store = ffi::SCDynamicStoreCreate(std::ptr::null(), name, dns_changed, &mut context);
+      ffi::CFRelease(name.cast());
+      if store.is_null() {
+        return None;
+      }
+      let key = cfstring(c"State:/Network/Global/DNS");
+      let keys = ffi::CFArrayCreate(
+        std::ptr::null(),
+        &key.cast::<c_void>(),
+        1,
+        &ffi::kCFTypeArrayCallBacks,
+      );
+      let watching = ffi::SCDynamicStoreSetNotificationKeys(store, keys, std::ptr::null()) != 0
+        && ffi::SCDynamicStoreSetDispatchQueue(store, queue) != 0;
+      ffi::CFRelease(keys.cast());
+      ffi::CFRelease(key.cast());
+      if !watching {
+        ffi::CFRelease(store.cast());
+        return None;
+      }
+      Some(Self { store })
+    }
+  }
+
+  /// The SCDynamicStore is not available on iOS, the DNS generation of the paths stays at 0 there.
+  #[cfg(not(target_os = "macos"))]
+  fn new(_state: &Arc<DnsState>, _queue: ffi::dispatch_queue_t) -> Option<Self> {
+    None
+  }
+}
+
+#[cfg(all(feature = "napi", target_os = "macos"))]
+impl Drop for DnsWatcher {
+  fn drop(&mut self) {
+    unsafe {
+      ffi::SCDynamicStoreSetDispatchQueue(self.store, std::ptr::null_mut());
+      // the store releases its reference of the `DnsState`
+      ffi::CFRelease(self.store.cast());
+    }
+  }
+}
+
+#[cfg(all(feature = "napi", target_os = "macos"))]
+unsafe fn cfstring(string: &CStr) -> ffi::CFStringRef {
+  ffi::CFStringCreateWithCString(
+    std::ptr::null(),
+    string.as_ptr(),
+    ffi::kCFStringEncodingUTF8,
+  )
+}
+
+#[cfg(feature = "napi")]
+/// The `retain` callback of the SystemConfiguration contexts whose `info` is an `Arc<T>`.
+extern "C" fn retain_arc<T>(info: *const c_void) -> *const c_void {
+  // SAFETY: `info` comes from `Arc::as_ptr` of an `Arc<T>` which is alive while the context is used
+  unsafe { Arc::increment_strong_count(info as *const T) };
+  info
+}
+
+#[cfg(feature = "napi")]
+extern "C" fn release_arc<T>(info: *const c_void) {
+  // SAFETY: paired with `retain_arc`
+  unsafe { Arc::decrement_strong_count(info as *const T) };
+}
+
+#[cfg(all(feature = "napi", target_os = "macos"))]
+extern "C" fn dns_changed(
+  _store: ffi::SCDynamicStoreRef,
+  _changed_keys: ffi::CFArrayRef,
+  info: *mut c_void,
+) {
+  // SAFETY: the store holds a reference of the `DnsState`
+  let state = unsafe { &*(info as *const DnsState) };
+  autoreleasepool(|_| {
+    let generation = state.generation.fetch_add(1, Ordering::Relaxed) + 1;
+    let path = {
+      let mut latest = state.latest.lock().unwrap();
+      let Some(path) = latest.as_mut() else {
+        return;
+      };
+      path.dns_generation = Some(generation);
+      path.clone()
+    };
+    let handler = state.handler.lock().unwrap().clone();
+    if let Some(handler) = handler {
+      handler(path);
+    }
+  })
+}
+
+/// `nw_path_monitor_create` and `nw_path_monitor_create_with_type` return null when the monitor can't be created,
+/// e.g. under a restrictive sandbox.
+fn created(
+  monitor: ffi::nw_path_monitor_t,
+  function: &str,
+) -> native::Result<ffi::nw_path_monitor_t> {
+  if monitor.is_null() {
+    return Err(native::Error::new(format!("{function} failed")));
+  }
+  Ok(monitor)
+}
+
+/// The interface types a path monitor is created with.
+#[derive(Clone, Default)]
+struct MonitorKind {
+  required: Option<NWInterfaceType>,
+  prohibited: Vec<NWInterfaceType>,
+}
+
+impl MonitorKind {
+  fn create(&self) -> native::Result<ffi::nw_path_monitor_t> {
+    let monitor = match self.required {
+      Some(required) => created(
+        unsafe { ffi::nw_path_monitor_create_with_type(required.into()) },
+        "nw_path_monitor_create_with_type",
+      )?,
+      None => created(
+        unsafe { ffi::nw_path_monitor_create() },
+        "nw_path_monitor_create",
+      )?,
+    };
+    for interface_type in &self.prohibited {
+      unsafe { ffi::nw_path_monitor_prohibit_interface_type(monitor, (*interface_type).into()) };
+    }
+    Ok(monitor)
+  }
+}
+
+#[cfg(feature = "napi")]
+/// A path retained by the monitor, released on drop.
+struct RetainedPath(ffi::nw_path_t);
+
+#[cfg(feature = "napi")]
+impl Drop for RetainedPath {
+  fn drop(&mut self) {
+    unsafe { ffi::nw_release(self.0.cast()) };
+  }
+}
+
+#[cfg(feature = "napi")]
+impl Drop for NWPathMonitor {
+  fn drop(&mut self) {
+    // no update can reach the threadsafe function while the monitor is going away
+    self.clear_update_handler();
+    self.cancel_handler.lock().unwrap().take();
+    self.dns_watcher.take();
+    // a monitor stopped explicitly is already cancelled
+    if self.started && !self.cancelled {
+      unsafe { ffi::nw_path_monitor_cancel(self.pm) };
+    }
+    unsafe {
+      ffi::nw_release(self.pm.cast());
+      ffi::dispatch_release(self.queue.cast());
+    }
+  }
+}
+
+#[cfg(feature = "napi")]
+#[napi]
+impl NWPathMonitor {
+  #[napi(constructor)]
+  /// Create a path monitor, its updates run with the `qos` quality-of-service class, `Default` by default.
+  pub fn new(qos: Option<QosClass>) -> Result<Self> {
+    Self::with_queue(MonitorKind::default(), serial_queue(qos, None))
+  }
+
+  #[napi(factory)]
+  /// Create a path monitor whose `start` uses a weak reference, it will not keep the Node.js alive.
+  ///
+  /// Use `startStrong` to opt out for a single listener.
+  pub fn new_weak_by_default(qos: Option<QosClass>) -> Result<Self> {
+    let mut monitor = Self::new(qos)?;
+    monitor.weak_by_default = true;
+    Ok(monitor)
+  }
+
+  #[napi(factory)]
+  /// Create a new path monitor with the specified interface type.
+  pub fn new_with_type(interface_type: NWInterfaceType, qos: Option<QosClass>) -> Result<Self> {
+    let kind = MonitorKind {
+      required: Some(interface_type),
+      ..Default::default()
+    };
+    Self::with_queue(kind, serial_queue(qos, None))
+  }
+
+  #[napi(factory)]
+  /// Create a new path monitor which requires and/or prohibits interface types.
+  pub fn with_options(options: NWPathMonitorOptions) -> Result<Self> {
+    let prohibited = options.prohibited.unwrap_or_default();
+    if ALL_INTERFACE_TYPES
+      .iter()
+      .all(|interface_type| prohibited.contains(interface_type))
+    {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Prohibiting every interface type leaves nothing to monitor",
+      ));
+    }
+    if let Some(required) = options.required {
+      if prohibited.contains(&required) {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("{required:?} can't be both required and prohibited"),
+        ));
+      }
+    }
+
+    let main_queue = options.main_queue.unwrap_or(false);
+    if main_queue && (options.qos.is_some() || options.queue_label.is_some()) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "mainQueue can't be combined with qos or queueLabel, the main queue has its own",
+      ));
+    }
+    let queue_label = options
+      .queue_label
+      .map(CString::new)
+      .transpose()
+      .map_err(|_| {
+        Error::new(
+          Status::InvalidArg,
+          "queueLabel can't contain a NUL character",
+        )
+      })?;
+
+    let kind = MonitorKind {
+      required: options.required,
+      prohibited,
+    };
+    let queue = if main_queue {
+      main_queue()
+    } else {
+      serial_queue(options.qos, queue_label.as_deref())
+    };
+    let mut monitor = Self::with_queue(kind, queue)?;
+    monitor.only_on_change = options.only_on_change.unwrap_or(true);
+    monitor.history = History::new(options.history_capacity);
+    Ok(monitor)
+  }
+
+  #[napi]
+  /// Start the path monitor, it will keep the Node.js alive unless you call stop on it.
+  ///
+  /// Monitors created with `newWeakByDefault` don't keep the Node.js alive, the same as `startWeak`.
+  /// Calling it again on a started monitor replaces the callback.
+  pub fn start(&mut self, on_update: Function<NWPath, ()>) -> Result<()> {
+    if self.weak_by_default {
+      self.start_inner::<true>(on_update)
+    } else {
+      self.start_inner::<false>(on_update)
+    }
+  }
+
+  #[napi]
+  /// Start with a strong reference whatever the monitor was created with, it will keep the Node.js alive unless you call stop on it.
+  pub fn start_strong(&mut self, on_update: Function<NWPath, ()>) -> Result<()> {
+    self.start_inner::<false>(on_update)
+  }
+
+  #[napi]
+  /// Start the path monitor with weak reference, it will not keep the Node.js alive.
+  ///
+  /// Calling it again on a started monitor replaces the callback.
+  pub fn start_weak(&mut self, on_update: Function<NWPath, ()>) -> Result<()> {
+    self.start_inner::<true>(on_update)
+  }
+
+  fn start_inner<const WEAK: bool>(&mut self, on_update: Function<NWPath, ()>) -> Result<()> {
+    let change_handler = on_update
+      .build_threadsafe_function()
+      .callee_handled::<false>()
+      .weak::<WEAK>()
+      .build_callback(ctx_to_path)?;
+    self.install_update_handler(move |path| {
+      change_handler.call(path, ThreadsafeFunctionCallMode::NonBlocking);
+    })
+  }
+
+  #[napi]
+  /// Emit `RetrySuggested` events with an exponential backoff while the path is not satisfied,
+  /// and a single `Reconnected` event once it's back.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn start_with_backoff(
+    &mut self,
+    options: Option<BackoffOptions>,
+    on_event: Function<BackoffEvent, ()>,
+  ) -> Result<()> {
+    let handler =
+      crate::backoff::change_handler(options.unwrap_or_default(), on_event, self.weak_by_default)?;
+    self.install_update_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onChange` only when `isExpensive` or `isLowDataMode` flips, the connectivity changes are ignored.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_metered_change(&mut self, on_change: Function<MeteredStatus, ()>) -> Result<()> {
+    // the first path is the baseline when the monitor is not started yet
+    let current = self.current_path().ok();
+    let handler =
+      crate::metered::change_handler(current.as_ref(), on_change, self.weak_by_default)?;
+    self.install_update_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onChange` with the new `canUseUnmetered` when it flips, e.g. to pause the large downloads when the path
+  /// becomes expensive, Low Data Mode is turned on or the connection is lost.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_unmetered_change(&mut self, on_change: Function<bool, ()>) -> Result<()> {
+    // the first path is the baseline when the monitor is not started yet
+    let current = self.current_path().ok();
+    let handler =
+      crate::unmetered::change_handler(current.as_ref(), on_change, self.weak_by_default)?;
+    self.install_update_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onChange` with the new interface type when the traffic moves to another one, e.g. from Wi-Fi to cellular
+  /// or from Ethernet to Wi-Fi, to reset the connections even though the status stays `Satisfied`.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_primary_interface_change(
+    &mut self,
+    on_change: Function<NWInterfaceType, ()>,
+  ) -> Result<()> {
+    // the first path is the baseline when the monitor is not started yet
+    let current = self.current_path().ok();
+    let handler =
+      crate::primary::change_handler(current.as_ref(), on_change, self.weak_by_default)?;
+    self.install_update_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onReconnect` with how long the path was offline, in milliseconds, when the status becomes `Satisfied` again,
+  /// e.g. to size the retry windows. Every offline episode starts the timer over.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_reconnect(&mut self, on_reconnect: Function<f64, ()>) -> Result<()> {
+    let current = self.current_path().ok();
+    let handler =
+      crate::reconnect::change_handler(current.as_ref(), on_reconnect, self.weak_by_default)?;
+    self.install_update_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onOnline` once the path stayed `Satisfied` for `minStableMs` in a row, any other status in between
+  /// starts the wait over. Unlike the first `Satisfied` update, it's not fired while the connectivity flaps.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_stable_online(
+    &mut self,
+    min_stable_ms: u32,
+    on_online: Function<NWPath, ()>,
+  ) -> Result<()> {
+    let handler = crate::stable::change_handler(min_stable_ms, on_online, self.weak_by_default)?;
+    // the monitor may already be online, start counting from now
+    if let Ok(current) = self.current_path() {
+      handler(current);
+    }
+    self.install_update_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onUpdate` only with the paths matching `filter`, a combination of `ChangeFilter` flags,
+  /// the other ones are dropped before they cross into JavaScript.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn start_filtered(&mut self, filter: u32, on_update: Function<NWPath, ()>) -> Result<()> {
+    // the first path is the baseline when the monitor is not started yet
+    let current = self.current_path().ok();
+    let handler =
+      crate::filter::change_handler(filter, current.as_ref(), on_update, self.weak_by_default)?;
+    self.install_update_handler(handler)
+  }
+
+  #[napi(ts_args_type = "event: MonitorEvent, listener: (arg: any) => void")]
+  /// Add a listener for `event`, like `EventEmitter.on`. The `change`, `online` and `offline` listeners
+  /// receive a `NetworkInfo` and the `metered` ones a `MeteredStatus`, `error` is never emitted on macOS.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on(&mut self, event: MonitorEvent, listener: Function<EventPayload, ()>) -> Result<()> {
+    self.emitter.on(event, listener, self.weak_by_default)?;
+    // the first path is the baseline when the monitor is not started yet
+    let current = self.current_path().ok();
+    let handler = self.emitter.change_handler(current.as_ref());
+    self.install_update_handler(handler)
+  }
+
+  #[napi(ts_args_type = "event: MonitorEvent, listener: (arg: any) => void")]
+  /// Remove a listener added by `on`, the monitor is stopped once the last one is removed.
+  pub fn off(
+    &mut self,
+    env: Env,
+    event: MonitorEvent,
+    listener: Function<EventPayload, ()>,
+  ) -> Result<()> {
+    if self.emitter.off(&env, event, listener)? && self.emitter.is_empty() {
+      self.stop()?;
+    }
+    Ok(())
+  }
+
+  #[napi(ts_return_type = "Promise<void>")]
+  /// Resolve once the monitor saw a `Satisfied` status, right away when it already did,
+  /// so it can be awaited before every request cheaply.
+  ///
+  /// It never resets, going offline afterwards doesn't make it wait again, listen to `offline` with `on` for that.
+  /// A monitor which is not started is started without a callback, `stop` rejects the pending promises.
+  pub fn connected(&mut self, env: Env) -> Result<JsObject> {
+    let promise = self.ready.wait(&env, self.current_path().ok().as_ref())?;
+    if self.ready.is_pending() && !self.started {
+      // the paths only resolve the promises
+      self.install_update_handler(|_| {})?;
+    }
+    Ok(promise)
+  }
+
+  #[napi(ts_return_type = "Promise<void>")]
+  /// Alias of `connected`.
+  pub fn ready(&mut self, env: Env) -> Result<JsObject> {
+    self.connected(env)
+  }
+
+  #[napi]
+  /// The last paths delivered to the callback, oldest first, `historyCapacity` of them at most.
+  ///
+  /// They are recorded while the monitor is started, whichever callback it's started with.
+  pub fn history(&self) -> Vec<NetworkHistoryEntry> {
+    self.history.entries()
+  }
+
+  #[napi]
+  /// The current path, including its gateways.
+  ///
+  /// The path monitor must be started first.
+  pub fn current(&self) -> Result<NWPath> {
+    self.current_path()
+  }
+
+  #[napi]
+  /// The same as `current`, the path is copied from the monitor on every call so it's never stale.
+  ///
+  /// The path monitor must be started first.
+  pub fn refresh(&self) -> Result<NWPath> {
+    self.current_path()
+  }
+
+  #[napi]
+  /// The state of every interface used by the current path, they all share the status of the path.
+  ///
+  /// The path monitor must be started first.
+  pub fn current_all(&self) -> Result<Vec<InterfaceNetworkInfo>> {
+    let path = self.current_path()?;
+    let addresses = interface_addresses();
+    Ok(
+      path
+        .interfaces
+        .into_iter()
+        .map(|interface| InterfaceNetworkInfo {
+          addresses: addresses
+            .iter()
+            .filter(|(name, _)| *name == interface.name)
+            .map(|(_, address)| address.to_string())
+            .collect(),
+          name: interface.name,
+          interface_type: interface.interface_type,
+          status: path.status,
+          is_metered: path.is_expensive,
+        })
+        .collect(),
+    )
+  }
+
+  #[napi]
+  /// Serialize the current path to JSON with the schema shared by every platform, for logging and telemetry.
+  ///
+  /// The path monitor must be started first.
+  pub fn snapshot_json(&self) -> Result<String> {
+    crate::snapshot_json(&self.current_path()?)
+  }
+
+  #[napi]
+  /// The category of the connected network, Windows is the only platform which has one,
+  /// the other platforms always return `Unknown`.
+  pub fn network_category(&self) -> NetworkCategory {
+    NetworkCategory::Unknown
+  }
+
+  #[napi]
+  /// A coarse quality tier of the current path, see `ConnectionClass` for the thresholds.
+  ///
+  /// The path monitor must be started first.
+  pub fn connection_class(&self) -> Result<ConnectionClass> {
+    Ok(ConnectionClass::new(&self.current_path()?))
+  }
+
+  #[napi]
+  /// It's safe to do a large download right now: the path is `Satisfied`, not expensive, not in Low Data Mode and not roaming.
+  ///
+  /// The path monitor must be started first.
+  pub fn can_use_unmetered(&self) -> Result<bool> {
+    Ok(self.current_path()?.can_use_unmetered())
+  }
+
+  #[napi]
+  /// The radio access technology of the cellular data service without its `CTRadioAccessTechnology` prefix,
+  /// e.g. `LTE` or `NRNSA`.
+  ///
+  /// `null` when the path doesn't use cellular, on macOS which has no CoreTelephony and before iOS 12.
+  /// The path monitor must be started first.
+  pub fn cellular_technology(&self) -> Result<Option<String>> {
+    if !self.current_path()?.uses_cellular {
+      return Ok(None);
+    }
+    Ok(radio_access_technology())
+  }
+
+  #[napi]
+  /// Call `onCancel` once the monitor is fully stopped, no update runs after it,
+  /// so the resources touched by the update callback can be freed.
+  ///
+  /// If the monitor is not started, `stop` calls it right away. It doesn't keep the Node.js alive.
+  pub fn on_cancel(&mut self, on_cancel: Function<(), ()>) -> Result<()> {
+    let on_cancel = on_cancel
+      .build_threadsafe_function()
+      .callee_handled::<false>()
+      .weak::<true>()
+      .build_callback(|_: ThreadsafeCallContext<()>| Ok(()))?;
+    self
+      .cancel_handler
+      .lock()
+      .unwrap()
+      .replace(Box::new(move || {
+        on_cancel.call((), ThreadsafeFunctionCallMode::NonBlocking);
+      }));
+    Ok(())
+  }
+
+  #[napi]
+  /// Sample the byte counters of the physical interfaces every `intervalMs` on a separate thread,
+  /// `throughput` returns the rate between the last two samples. Starting it again restarts the sampling.
+  ///
+  /// It measures the total traffic of the machine, not the traffic of the app.
+  /// The sampling is independent of `start` and `stop`, it runs until `stopThroughput`.
+  pub fn start_throughput(&mut self, interval_ms: u32) -> Result<()> {
+    // join the previous sampling thread before spawning another one
+    self.throughput_sampler.take();
+    self.throughput_sampler = Some(ThroughputSampler::start(interval_ms)?);
+    Ok(())
+  }
+
+  #[napi]
+  /// The received and sent bytes per second, `null` until `startThroughput` sampled twice.
+  pub fn throughput(&self) -> Result<Option<Throughput>> {
+    Ok(
+      self
+        .throughput_sampler
+        .as_ref()
+        .and_then(ThroughputSampler::throughput),
+    )
+  }
+
+  #[napi]
+  /// Stop the sampling started by `startThroughput`.
+  pub fn stop_throughput(&mut self) -> Result<()> {
+    self.throughput_sampler.take();
+    Ok(())
+  }
+
+  #[napi]
+  /// Stop the path monitor, starting it again afterwards creates a new one with the same options.
+  ///
+  /// If you don't call this method and leave the monitor alone, it will be stopped automatically when it is GC.
+  pub fn stop(&mut self) -> Result<()> {
+    // release the threadsafe function, otherwise a strong one keeps the Node.js alive after stop
+    self.clear_update_handler();
+    self.emitter.clear();
+    self.ready.cancel();
+    self.dns_watcher.take();
+    if !self.started {
+      // Network.framework only runs the cancel handler of a started monitor
+      let on_cancel = self.cancel_handler.lock().unwrap().take();
+      if let Some(on_cancel) = on_cancel {
+        on_cancel();
+      }
+    }
+    if !self.cancelled {
+      unsafe { ffi::nw_path_monitor_cancel(self.pm) };
+      self.cancelled = true;
+    }
+    self.started = false;
+    self.last_path.lock().unwrap().take();
+    Ok(())
+  }
+}
+
+#[cfg(feature = "napi")]
+impl NWPathMonitor {
+  /// The monitor owns the reference to the queue, it's released on drop or when the monitor can't be created.
+  fn with_queue(kind: MonitorKind, queue: ffi::dispatch_queue_t) -> Result<Self> {
+    let monitor = kind.create().inspect_err(|_| {
+      unsafe { ffi::dispatch_release(queue.cast()) };
+    })?;
+    let cancel_handler: Arc<Mutex<Option<CancelHandler>>> = Default::default();
+    attach(monitor, queue, cancel_handler.clone());
+    Ok(Self {
+      pm: monitor,
+      kind,
+      queue,
+      started: false,
+      cancelled: false,
+      weak_by_default: false,
+      only_on_change: true,
+      last_path: Default::default(),
+      dns: Default::default(),
+      watch_dns: true,
+      dns_watcher: None,
+      cancel_handler,
+      update_handler: None,
+      throughput_sampler: None,
+      emitter: Emitter::default(),
+      history: History::default(),
+      ready: Ready::default(),
+    })
+  }
+
+  /// Network.framework never restarts a cancelled monitor, replace it with a new one created the same way.
+  fn recreate(&mut self) -> Result<()> {
+    let monitor = self.kind.create()?;
+    // the cancel handler of the previous monitor may still be pending, it keeps the `onCancel` registered before `stop`
+    self.cancel_handler = Default::default();
+    attach(monitor, self.queue, self.cancel_handler.clone());
+    unsafe { ffi::nw_release(self.pm.cast()) };
+    self.pm = monitor;
+    self.cancelled = false;
+    Ok(())
+  }
+
+  /// Install the update handler and start the monitor if it's not started yet.
+  ///
+  /// Unless `only_on_change` is turned off, `cb` is not called for a path equal to the previous one according to `nw_path_is_equal`.
+  /// It's called again with the latest path when the DNS configuration changes.
+  fn install_update_handler(&mut self, cb: impl Fn(NWPath) + Send + Sync + 'static) -> Result<()> {
+    if self.cancelled {
+      self.recreate()?;
+    }
+    let cb: UpdateHandler = Arc::from(self.ready.watch(self.history.record(Box::new(cb))));
+    self.dns.handler.lock().unwrap().replace(cb.clone());
+    let only_on_change = self.only_on_change;
+    let last_path = self.last_path.clone();
+    let dns = self.dns.clone();
+    let handler = RcBlock::new(move |path: *mut c_void| {
+      // the block runs on a dispatch worker thread which has no autorelease pool of its own,
+      // drain whatever the handler autoreleases every time instead of for the lifetime of the thread
+      autoreleasepool(|_| {
+        if !only_on_change || is_new_path(&last_path, path.cast()) {
+          // The path is only guaranteed to be alive while the block runs,
+          // read it here instead of on the JavaScript thread.
+          let mut path = NWPath::from(path.cast::<ffi::nw_path>());
+          path.dns_generation = Some(dns.generation.load(Ordering::Relaxed));
+          *dns.latest.lock().unwrap() = Some(path.clone());
+          cb(path);
+        }
+      })
+    });
+    // Network.framework releases its copy of the previous block when it's replaced,
+    // dropping ours releases the threadsafe function it captures
+    unsafe {
+      ffi::nw_path_monitor_set_update_handler(self.pm, &handler);
+    };
+    self.update_handler = Some(handler);
+    if !self.started {
+      if self.watch_dns {
+        self.dns_watcher = DnsWatcher::new(&self.dns, self.queue);
+      }
+      unsafe { ffi::nw_path_monitor_start(self.pm) };
+      self.started = true;
+    }
+    Ok(())
+  }
+
+  fn current_path(&self) -> Result<NWPath> {
+    let path = unsafe { ffi::nw_path_monitor_copy_current_path(self.pm) };
+    if path.is_null() {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "The path monitor has not received a path yet, call start first",
+      ));
+    }
+    let mut info = NWPath::from(path);
+    unsafe { ffi::nw_release(path.cast()) };
+    info.dns_generation = Some(self.dns.generation.load(Ordering::Relaxed));
+    Ok(info)
+  }
+
+  /// Swap the installed block, which captures the threadsafe function, with an empty one.
+  fn clear_update_handler(&mut self) {
+    unsafe { ffi::nw_path_monitor_set_update_handler(self.pm, &RcBlock::new(|_: *mut c_void| {})) };
+    self.update_handler.take();
+    self.dns.handler.lock().unwrap().take();
+  }
+}
+
+#[cfg(feature = "napi")]
+/// Run the updates of `monitor` on `queue`, its cancel handler calls the `onCancel` taken from `cancel_handler`.
+fn attach(
+  monitor: ffi::nw_path_monitor_t,
+  queue: ffi::dispatch_queue_t,
+  cancel_handler: Arc<Mutex<Option<CancelHandler>>>,
+) {
+  // Network.framework keeps the block until the monitor is released,
+  // the threadsafe function it reaches is released as soon as it runs.
+  let cancel_block = RcBlock::new(move || {
+    autoreleasepool(|_| {
+      let on_cancel = cancel_handler.lock().unwrap().take();
+      if let Some(on_cancel) = on_cancel {
+        on_cancel();
+      }
+    })
+  });
+  unsafe {
+    ffi::nw_path_monitor_set_queue(monitor, queue);
+    ffi::nw_path_monitor_set_cancel_handler(monitor, &cancel_block);
+  };
+}
+
+#[cfg(feature = "napi")]
+/// Remember the path and tell if it's different from the previous one, `nw_path_is_equal` compares
+/// every property of the paths, including those the `NetworkInfo` doesn't expose.
+fn is_new_path(last_path: &Mutex<Option<RetainedPath>>, path: ffi::nw_path_t) -> bool {
+  let mut last_path = last_path.lock().unwrap();
+  if last_path
+    .as_ref()
+    .is_some_and(|last_path| unsafe { ffi::nw_path_is_equal(last_path.0, path) })
+  {
+    return false;
+  }
+  // the path is only borrowed by the update handler
+  unsafe { ffi::nw_retain(path.cast()) };
+  *last_path = Some(RetainedPath(path));
+  true
+}
+
+/// libdispatch copies the label, it doesn't need to outlive the queue.
+fn serial_queue(qos: Option<QosClass>, label: Option<&CStr>) -> ffi::dispatch_queue_t {
+  let qos = qos.unwrap_or_default().into();
+  let label = label.unwrap_or(c"rs.napi.network-change.path-monitor");
+  unsafe {
+    // a null attribute is a serial queue
+    let attr = ffi::dispatch_queue_attr_make_with_qos_class(std::ptr::null_mut(), qos, 0);
+    ffi::dispatch_queue_create(label.as_ptr(), attr)
+  }
+}
+
+#[cfg(feature = "napi")]
+/// `dispatch_get_main_queue` is a macro, the main queue is a static object
+/// and `dispatch_retain` and `dispatch_release` are no-ops on it.
+fn main_queue() -> ffi::dispatch_queue_t {
+  unsafe { std::ptr::addr_of!(ffi::_dispatch_main_q).cast_mut() }
+}
+
+#[cfg(feature = "napi")]
+#[inline]
+fn ctx_to_path(ctx: ThreadsafeCallContext<NWPath>) -> Result<NWPath> {
+  Ok(ctx.value)
+}
+
+/// How long `getGlobalNetworkInfo` waits for the first path, it's usually delivered right after the monitor starts.
+const FIRST_PATH_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[cfg(feature = "napi")]
+#[napi]
+/// Read the current path once, for CLI tools and health checks which don't need a long-lived monitor.
+///
+/// A transient path monitor is started and released before returning, it blocks until the first path arrives.
+pub fn get_global_network_info() -> Result<NWPath> {
+  Ok(current_network_info()?)
+}
+
+/// A bare path monitor, the `NWPathMonitor` of the bindings carries the history and the listeners of JavaScript.
+pub(crate) fn current_network_info() -> native::Result<NWPath> {
+  let monitor = MonitorKind::default().create()?;
+  let queue = serial_queue(None, None);
+  let (sender, receiver) = mpsc::sync_channel(1);
+  let handler = RcBlock::new(move |path: *mut c_void| {
+    autoreleasepool(|_| {
+      let mut path = NWPath::from(path.cast::<ffi::nw_path>());
+      // the DNS configuration is not watched, the generation stays at its first value
+      path.dns_generation = Some(0);
+      let _ = sender.try_send(path);
+    })
+  });
+  unsafe {
+    ffi::nw_path_monitor_set_queue(monitor, queue);
+    ffi::nw_path_monitor_set_update_handler(monitor, &handler);
+    ffi::nw_path_monitor_start(monitor);
+  }
+  let path = receiver
+    .recv_timeout(FIRST_PATH_TIMEOUT)
+    .map_err(|_| native::Error::new("The path monitor did not receive a path in time"));
+  // Network.framework releases its copy of the block, and the sender it captures, with the monitor
+  unsafe {
+    ffi::nw_path_monitor_cancel(monitor);
+    ffi::nw_release(monitor.cast());
+    ffi::dispatch_release(queue.cast());
+  }
+  path
+}
+
+#[cfg(feature = "napi")]
+#[napi(object, object_from_js = false)]
+#[derive(Debug, Clone, Default)]
+/// The latest path of every interface type, `undefined` until its monitor reported once.
+pub struct NWCompositePath {
+  pub wifi: Option<NWPath>,
+  pub cellular: Option<NWPath>,
+  pub wired: Option<NWPath>,
+}
+
+#[cfg(feature = "napi")]
+#[napi]
+/// Watches the Wi-Fi, Cellular and Wired paths at once.
+///
+/// The underlying monitors share one serial dispatch queue, so their updates never run concurrently
+/// and every update carries the latest path of all of them.
+pub struct NWCompositePathMonitor {
+  wifi: NWPathMonitor,
+  cellular: NWPathMonitor,
+  wired: NWPathMonitor,
+  paths: Arc<Mutex<NWCompositePath>>,
+}
+
+#[cfg(feature = "napi")]
+#[napi]
+impl NWCompositePathMonitor {
+  #[napi(constructor)]
+  pub fn new() -> Result<Self> {
+    let queue = unsafe {
+      ffi::dispatch_queue_create(
+        c"rs.napi.network-change.composite".as_ptr(),
+        std::ptr::null_mut(),
+      )
+    };
+    let (wifi, cellular, wired) = (
+      typed_monitor(NWInterfaceType::Wifi, queue),
+      typed_monitor(NWInterfaceType::Cellular, queue),
+      typed_monitor(NWInterfaceType::Wired, queue),
+    );
+    // the monitors created before a failure release their own reference when dropped
+    unsafe { ffi::dispatch_release(queue.cast()) };
+    Ok(Self {
+      wifi: wifi?,
+      cellular: cellular?,
+      wired: wired?,
+      paths: Default::default(),
+    })
+  }
+
+  #[napi]
+  /// Start the monitors, it will keep the Node.js alive unless you call stop on it.
+  pub fn start(&mut self, on_update: Function<NWCompositePath, ()>) -> Result<()> {
+    self.start_inner::<false>(on_update)
+  }
+
+  #[napi]
+  /// Start the monitors with weak reference, it will not keep the Node.js alive.
+  pub fn start_weak(&mut self, on_update: Function<NWCompositePath, ()>) -> Result<()> {
+    self.start_inner::<true>(on_update)
+  }
+
+  fn start_inner<const WEAK: bool>(
+    &mut self,
+    on_update: Function<NWCompositePath, ()>,
+  ) -> Result<()> {
+    let change_handler = Arc::new(
+      on_update
+        .build_threadsafe_function()
+        .callee_handled::<false>()
+        .weak::<WEAK>()
+        .build_callback(|ctx: ThreadsafeCallContext<NWCompositePath>| Ok(ctx.value))?,
+    );
+    *self.paths.lock().unwrap() = NWCompositePath::default();
+
+    let monitors: [(
+      &mut NWPathMonitor,
+      fn(&mut NWCompositePath) -> &mut Option<NWPath>,
+    ); 3] = [
+      (&mut self.wifi, |paths| &mut paths.wifi),
+      (&mut self.cellular, |paths| &mut paths.cellular),
+      (&mut self.wired, |paths| &mut paths.wired),
+    ];
+    for (monitor, slot) in monitors {
+      let change_handler = change_handler.clone();
+      let paths = self.paths.clone();
+      monitor.install_update_handler(move |path| {
+        let paths = {
+          let mut paths = paths.lock().unwrap();
+          *slot(&mut paths) = Some(path);
+          paths.clone()
+        };
+        change_handler.call(paths, ThreadsafeFunctionCallMode::NonBlocking);
+      })?;
+    }
+    Ok(())
+  }
+
+  #[napi]
+  /// Stop the monitors.
+  ///
+  /// If you don't call this method and leave the monitor alone, it will be stopped automatically when it is GC.
+  pub fn stop(&mut self) -> Result<()> {
+    self.wifi.stop()?;
+    self.cellular.stop()?;
+    self.wired.stop()
+  }
+}
+
+#[cfg(feature = "napi")]
+/// A monitor restricted to `interface_type` on the shared serial `queue`, it owns a reference to the queue.
+fn typed_monitor(
+  interface_type: NWInterfaceType,
+  queue: ffi::dispatch_queue_t,
+) -> Result<NWPathMonitor> {
+  let kind = MonitorKind {
+    required: Some(interface_type),
+    ..Default::default()
+  };
+  unsafe { ffi::dispatch_retain(queue.cast()) };
+  let mut monitor = NWPathMonitor::with_queue(kind, queue)?;
+  monitor.watch_dns = false;
+  Ok(monitor)
+}
+
+#[cfg(feature = "napi")]
+#[napi]
+/// Watches one path per interface type and reports the best available one, e.g. Wi-Fi and Cellular together.
+///
+/// Every update of any of them is merged with the latest path of the others, see `mergeNetworkInfo`.
+/// `interfaceTypes` is the order of preference when two paths have the same status.
+pub struct AggregateMonitor {
+  monitors: Vec<NWPathMonitor>,
+  paths: Arc<Mutex<Vec<Option<NWPath>>>>,
+}
+
+#[cfg(feature = "napi")]
+#[napi]
+impl AggregateMonitor {
+  #[napi(constructor)]
+  pub fn new(interface_types: Vec<NWInterfaceType>) -> Result<Self> {
+    if interface_types.is_empty() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "AggregateMonitor needs at least one interface type",
+      ));
+    }
+    let queue = unsafe {
+      ffi::dispatch_queue_create(
+        c"rs.napi.network-change.aggregate".as_ptr(),
+        std::ptr::null_mut(),
+      )
+    };
+    let monitors = interface_types
+      .iter()
+      .map(|interface_type| typed_monitor(*interface_type, queue))
+      .collect::<Result<Vec<_>>>();
+    // the monitors created before a failure release their own reference when dropped
+    unsafe { ffi::dispatch_release(queue.cast()) };
+    Ok(Self {
+      paths: Arc::new(Mutex::new(vec![None; interface_types.len()])),
+      monitors: monitors?,
+    })
+  }
+
+  #[napi]
+  /// The best path of the latest ones, `null` until a monitor reported once.
+  pub fn current(&self) -> Option<NWPath> {
+    crate::aggregate::merge(self.paths.lock().unwrap().iter().flatten())
+  }
+
+  #[napi]
+  /// Start the monitors, it will keep the Node.js alive unless you call stop on it.
+  pub fn start(&mut self, on_update: Function<NWPath, ()>) -> Result<()> {
+    self.start_inner::<false>(on_update)
+  }
+
+  #[napi]
+  /// Start the monitors with weak reference, it will not keep the Node.js alive.
+  pub fn start_weak(&mut self, on_update: Function<NWPath, ()>) -> Result<()> {
+    self.start_inner::<true>(on_update)
+  }
+
+  fn start_inner<const WEAK: bool>(&mut self, on_update: Function<NWPath, ()>) -> Result<()> {
+    let change_handler = Arc::new(
+      on_update
+        .build_threadsafe_function()
+        .callee_handled::<false>()
+        .weak::<WEAK>()
+        .build_callback(|ctx: ThreadsafeCallContext<NWPath>| Ok(ctx.value))?,
+    );
+    self.paths.lock().unwrap().fill(None);
+
+    for (slot, monitor) in self.monitors.iter_mut().enumerate() {
+      let change_handler = change_handler.clone();
+      let paths = self.paths.clone();
+      monitor.install_update_handler(move |path| {
+        let merged = {
+          let mut paths = paths.lock().unwrap();
+          paths[slot] = Some(path);
+          crate::aggregate::merge(paths.iter().flatten())
+        };
+        if let Some(merged) = merged {
+          change_handler.call(merged, ThreadsafeFunctionCallMode::NonBlocking);
+        }
+      })?;
+    }
+    Ok(())
+  }
+
+  #[napi]
+  /// Stop the monitors.
+  ///
+  /// If you don't call this method and leave the monitor alone, it will be stopped automatically when it is GC.
+  pub fn stop(&mut self) -> Result<()> {
+    for monitor in &mut self.monitors {
+      monitor.stop()?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(feature = "napi")]
+#[napi(object, object_from_js = false)]
+#[derive(Debug, Clone)]
+pub struct InterfaceAvailability {
+  /// The path over the interface type is satisfied
+  pub available: bool,
+  pub path: NWPath,
+}
+
+#[cfg(feature = "napi")]
 #[napi]
-/// Interface types represent the underlying media for a network link, such as Wi-Fi or Cellular.
-pub enum NWInterfaceType {
-  /// nw_interface_type_other A virtual or otherwise unknown interface type
-  Other,
-  /// nw_interface_type_wifi A Wi-Fi link
-  Wifi,
-  /// nw_interface_type_wifi A Cellular link
-  Cellular,
-  /// nw_interface_type_wired A Wired Ethernet link
-  Wired,
-  /// nw_interface_type_loopback A Loopback link
-  Loopback,
+/// Returned by `watchInterfaceAvailability`, it owns the path monitor of the watched interface type.
+pub struct InterfaceAvailabilityWatcher {
+  monitor: NWPathMonitor,
 }
 
-impl From<ffi::nw_path_status_t> for NWPathStatus {
-  fn from(status: ffi::nw_path_status_t) -> Self {
-    match status {
-      ffi::nw_path_status_t::NW_PATH_STATUS_INVALID => NWPathStatus::Invalid,
-      ffi::nw_path_status_t::NW_PATH_STATUS_SATISFIED => NWPathStatus::Satisfied,
-      ffi::nw_path_status_t::NW_PATH_STATUS_UNSATISFIED => NWPathStatus::Unsatisfied,
-      ffi::nw_path_status_t::NW_PATH_STATUS_SATISFIABLE => NWPathStatus::Satisfiable,
-      _ => NWPathStatus::Unknown,
-    }
+#[cfg(feature = "napi")]
+#[napi]
+impl InterfaceAvailabilityWatcher {
+  #[napi]
+  /// Stop watching and release the path monitor.
+  ///
+  /// If you don't call this method and leave the watcher alone, it will be stopped automatically when it is GC.
+  pub fn dispose(&mut self) -> Result<()> {
+    self.monitor.stop()
   }
 }
 
-impl From<NWInterfaceType> for ffi::nw_interface_type_t {
-  fn from(interface_type: NWInterfaceType) -> Self {
-    match interface_type {
-      NWInterfaceType::Other => 0,
-      NWInterfaceType::Wifi => 1,
-      NWInterfaceType::Cellular => 2,
-      NWInterfaceType::Wired => 3,
-      NWInterfaceType::Loopback => 4,
+#[cfg(feature = "napi")]
+#[napi]
+/// Call `onChange` whenever the interface type becomes available or unavailable, whether or not it's used by the default path,
+/// e.g. "tell me when Wi-Fi is available". The first path is always reported.
+///
+/// Every watcher has its own path monitor, it keeps the Node.js alive until it's disposed.
+pub fn watch_interface_availability(
+  interface_type: NWInterfaceType,
+  on_change: Function<InterfaceAvailability, ()>,
+) -> Result<InterfaceAvailabilityWatcher> {
+  let on_change = on_change
+    .build_threadsafe_function()
+    .callee_handled::<false>()
+    .weak::<false>()
+    .build_callback(|ctx: ThreadsafeCallContext<InterfaceAvailability>| Ok(ctx.value))?;
+  let mut monitor = NWPathMonitor::new_with_type(interface_type, None)?;
+  // a DNS change never flips the availability
+  monitor.watch_dns = false;
+  let last_available = Mutex::new(None);
+  monitor.install_update_handler(move |path| {
+    let available = matches!(path.status, NWPathStatus::Satisfied);
+    if last_available.lock().unwrap().replace(available) != Some(available) {
+      on_change.call(
+        InterfaceAvailability { available, path },
+        ThreadsafeFunctionCallMode::NonBlocking,
+      );
     }
-  }
+  })?;
+  Ok(InterfaceAvailabilityWatcher { monitor })
 }
 
+#[cfg(feature = "napi")]
 #[napi]
-/// A monitor that watches for changes in network path status.
-pub struct NWPathMonitor {
-  pm: ffi::nw_path_monitor_t,
+/// Watches the path to a specific endpoint, e.g. the server of the application, instead of the default path.
+///
+/// Network.framework has no endpoint-scoped path monitor, the path is the one of an `nw_connection` to the endpoint,
+/// so the endpoint is contacted over TCP while the monitor is started.
+pub struct NWEndpointPathMonitor {
+  endpoint: ffi::nw_endpoint_t,
+  queue: ffi::dispatch_queue_t,
+  /// A cancelled connection can't be started again, every `start` after `stop` creates a new one.
+  connection: Option<ffi::nw_connection_t>,
+  handler: Arc<Mutex<Option<ChangeHandler>>>,
+}
+
+#[cfg(feature = "napi")]
+impl Drop for NWEndpointPathMonitor {
+  fn drop(&mut self) {
+    self.stop();
+    unsafe {
+      ffi::nw_release(self.endpoint.cast());
+      ffi::dispatch_release(self.queue.cast());
+    }
+  }
 }
 
+#[cfg(feature = "napi")]
 #[napi]
-impl NWPathMonitor {
+impl NWEndpointPathMonitor {
   #[napi(constructor)]
-  #[allow(clippy::new_without_default)]
-  pub fn new() -> Self {
-    let monitor = unsafe { ffi::nw_path_monitor_create() };
-    let queue =
-      unsafe { ffi::dispatch_get_global_queue(ffi::dispatch_qos_class_t::QOS_CLASS_DEFAULT, 0) };
-    unsafe { ffi::nw_path_monitor_set_queue(monitor, queue.cast()) };
-    Self { pm: monitor }
+  /// Create a monitor of the path to `host:port`, its updates run with the `qos` quality-of-service class.
+  pub fn new(host: String, port: u16, qos: Option<QosClass>) -> Result<Self> {
+    let host = std::ffi::CString::new(host)
+      .map_err(|_| Error::new(Status::InvalidArg, "The host contains a NUL byte"))?;
+    let port = std::ffi::CString::new(port.to_string()).expect("a port has no NUL byte");
+    let endpoint = unsafe { ffi::nw_endpoint_create_host(host.as_ptr(), port.as_ptr()) };
+    if endpoint.is_null() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "nw_endpoint_create_host failed",
+      ));
+    }
+    Ok(Self {
+      endpoint,
+      queue: serial_queue(qos, None),
+      connection: None,
+      handler: Default::default(),
+    })
   }
 
-  #[napi(factory)]
-  /// Create a new path monitor with the specified interface type.
-  pub fn new_with_type(interface_type: NWInterfaceType) -> Self {
-    let monitor = unsafe { ffi::nw_path_monitor_create_with_type(interface_type.into()) };
-    let queue =
-      unsafe { ffi::dispatch_get_global_queue(ffi::dispatch_qos_class_t::QOS_CLASS_DEFAULT, 0) };
-    unsafe { ffi::nw_path_monitor_set_queue(monitor, queue.cast()) };
-    Self { pm: monitor }
+  #[napi]
+  /// Start watching the path to the endpoint, it will keep the Node.js alive unless you call stop on it.
+  ///
+  /// Calling it again on a started monitor replaces the callback.
+  pub fn start(&mut self, on_update: Function<NWPath, ()>) -> Result<()> {
+    self.start_inner::<false>(on_update)
   }
 
   #[napi]
-  /// Start the path monitor, it will keep the Node.js alive unless you call stop on it.
-  pub fn start(&mut self, on_update: Function<NWPath, ()>) -> Result<()> {
+  /// Start watching the path to the endpoint with weak reference, it will not keep the Node.js alive.
+  pub fn start_weak(&mut self, on_update: Function<NWPath, ()>) -> Result<()> {
+    self.start_inner::<true>(on_update)
+  }
+
+  fn start_inner<const WEAK: bool>(&mut self, on_update: Function<NWPath, ()>) -> Result<()> {
     let change_handler = on_update
       .build_threadsafe_function()
       .callee_handled::<false>()
-      .weak::<false>()
+      .weak::<WEAK>()
       .build_callback(ctx_to_path)?;
-    let cb = move |path: *mut c_void| {
-      change_handler.call(path.cast(), ThreadsafeFunctionCallMode::NonBlocking);
+    self.handler.lock().unwrap().replace(Box::new(move |path| {
+      change_handler.call(path, ThreadsafeFunctionCallMode::NonBlocking);
+    }));
+    if self.connection.is_some() {
+      return Ok(());
+    }
+
+    let connection = unsafe {
+      let parameters = ffi::nw_parameters_create_secure_tcp(
+        ffi::_nw_parameters_configure_protocol_disable,
+        ffi::_nw_parameters_configure_protocol_default_configuration,
+      );
+      let connection = ffi::nw_connection_create(self.endpoint, parameters);
+      ffi::nw_release(parameters.cast());
+      connection
     };
+    if connection.is_null() {
+      self.handler.lock().unwrap().take();
+      return Err(Error::new(
+        Status::GenericFailure,
+        "nw_connection_create failed",
+      ));
+    }
+    let handler = self.handler.clone();
+    let path_changed = RcBlock::new(move |path: *mut c_void| {
+      autoreleasepool(|_| {
+        // The path is only guaranteed to be alive while the block runs,
+        // read it here instead of on the JavaScript thread.
+        let path = NWPath::from(path.cast::<ffi::nw_path>());
+        if let Some(handler) = handler.lock().unwrap().as_ref() {
+          handler(path);
+        }
+      })
+    });
     unsafe {
-      ffi::nw_path_monitor_set_update_handler(self.pm, &RcBlock::new(cb));
-    };
-    unsafe { ffi::nw_path_monitor_start(self.pm) };
+      ffi::nw_connection_set_queue(connection, self.queue);
+      ffi::nw_connection_set_path_changed_handler(connection, &path_changed);
+      ffi::nw_connection_start(connection);
+    }
+    self.connection = Some(connection);
     Ok(())
   }
 
   #[napi]
-  /// Start the path monitor with weak reference, it will not keep the Node.js alive.
-  pub fn start_weak(&mut self, on_update: Function<NWPath, ()>) -> Result<()> {
+  /// The current path to the endpoint.
+  ///
+  /// The monitor must be started first.
+  pub fn current(&self) -> Result<NWPath> {
+    let path = self
+      .connection
+      .map(|connection| unsafe { ffi::nw_connection_copy_current_path(connection) })
+      .unwrap_or(std::ptr::null_mut());
+    if path.is_null() {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "The endpoint monitor has not received a path yet, call start first",
+      ));
+    }
+    let info = NWPath::from(path);
+    unsafe { ffi::nw_release(path.cast()) };
+    Ok(info)
+  }
+
+  #[napi]
+  /// Stop watching the path to the endpoint and close the connection.
+  ///
+  /// If you don't call this method and leave the monitor alone, it will be stopped automatically when it is GC.
+  pub fn stop(&mut self) {
+    self.handler.lock().unwrap().take();
+    if let Some(connection) = self.connection.take() {
+      unsafe {
+        ffi::nw_connection_cancel(connection);
+        ffi::nw_release(connection.cast());
+      }
+    }
+  }
+}
+
+#[cfg(feature = "napi")]
+#[napi(object, object_from_js = false)]
+#[derive(Debug, Clone, Copy)]
+/// The `SCNetworkReachabilityFlags` of a host decoded into booleans.
+pub struct ReachabilityFlags {
+  /// kSCNetworkReachabilityFlagsReachable The host is reachable with the current configuration
+  pub is_reachable: bool,
+  /// kSCNetworkReachabilityFlagsConnectionRequired A connection must be established first, e.g. a VPN on demand
+  pub is_connection_required: bool,
+  /// kSCNetworkReachabilityFlagsIsWWAN The host is reached through cellular
+  pub is_wwan: bool,
+  /// kSCNetworkReachabilityFlagsTransientConnection The host is reached through a transient connection, e.g. PPP
+  pub is_transient_connection: bool,
+  /// kSCNetworkReachabilityFlagsInterventionRequired The user must act first, e.g. enter a password
+  pub is_intervention_required: bool,
+  /// kSCNetworkReachabilityFlagsIsLocalAddress The host is an address of this machine
+  pub is_local_address: bool,
+  /// kSCNetworkReachabilityFlagsIsDirect The host is reached without a gateway
+  pub is_direct: bool,
+}
+
+#[cfg(feature = "napi")]
+impl From<ffi::SCNetworkReachabilityFlags> for ReachabilityFlags {
+  fn from(flags: ffi::SCNetworkReachabilityFlags) -> Self {
+    let has = |flag: ffi::SCNetworkReachabilityFlags| flags & flag == flag;
+    Self {
+      is_reachable: has(ffi::kSCNetworkReachabilityFlagsReachable),
+      is_connection_required: has(ffi::kSCNetworkReachabilityFlagsConnectionRequired),
+      is_wwan: has(ffi::kSCNetworkReachabilityFlagsIsWWAN),
+      is_transient_connection: has(ffi::kSCNetworkReachabilityFlagsTransientConnection),
+      is_intervention_required: has(ffi::kSCNetworkReachabilityFlagsInterventionRequired),
+      is_local_address: has(ffi::kSCNetworkReachabilityFlagsIsLocalAddress),
+      is_direct: has(ffi::kSCNetworkReachabilityFlagsIsDirect),
+    }
+  }
+}
+
+#[cfg(feature = "napi")]
+type ReachabilityHandler = Box<dyn Fn(ReachabilityFlags) + Send + Sync>;
+
+#[cfg(feature = "napi")]
+#[napi]
+/// The reachability of a host with the SCNetworkReachability semantics, for code written against them.
+///
+/// Prefer `NWPathMonitor` for new code, both can be used in the same process.
+pub struct HostReachability {
+  target: ffi::SCNetworkReachabilityRef,
+  queue: ffi::dispatch_queue_t,
+  started: bool,
+  handler: Arc<Mutex<Option<ReachabilityHandler>>>,
+}
+
+#[cfg(feature = "napi")]
+impl Drop for HostReachability {
+  fn drop(&mut self) {
+    self.stop();
+    unsafe {
+      ffi::CFRelease(self.target.cast());
+      ffi::dispatch_release(self.queue.cast());
+    }
+  }
+}
+
+#[cfg(feature = "napi")]
+#[napi]
+impl HostReachability {
+  #[napi(constructor)]
+  pub fn new(hostname: String) -> Result<Self> {
+    let hostname = std::ffi::CString::new(hostname)
+      .map_err(|_| Error::new(Status::InvalidArg, "The hostname contains a NUL byte"))?;
+    let target =
+      unsafe { ffi::SCNetworkReachabilityCreateWithName(std::ptr::null(), hostname.as_ptr()) };
+    if target.is_null() {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "SCNetworkReachabilityCreateWithName failed",
+      ));
+    }
+    Ok(Self {
+      target,
+      queue: unsafe {
+        ffi::dispatch_queue_create(
+          c"rs.napi.network-change.reachability".as_ptr(),
+          std::ptr::null_mut(),
+        )
+      },
+      started: false,
+      handler: Default::default(),
+    })
+  }
+
+  #[napi]
+  /// Read the flags synchronously, it may block while the hostname is resolved.
+  pub fn current(&self) -> Result<ReachabilityFlags> {
+    let mut flags = 0;
+    if unsafe { ffi::SCNetworkReachabilityGetFlags(self.target, &mut flags) } == 0 {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "SCNetworkReachabilityGetFlags failed",
+      ));
+    }
+    Ok(flags.into())
+  }
+
+  #[napi]
+  /// Start watching the host, it will keep the Node.js alive unless you call stop on it.
+  ///
+  /// Calling it again on a started instance replaces the callback.
+  pub fn start(&mut self, on_update: Function<ReachabilityFlags, ()>) -> Result<()> {
+    self.start_inner::<false>(on_update)
+  }
+
+  #[napi]
+  /// Start watching the host with weak reference, it will not keep the Node.js alive.
+  pub fn start_weak(&mut self, on_update: Function<ReachabilityFlags, ()>) -> Result<()> {
+    self.start_inner::<true>(on_update)
+  }
+
+  fn start_inner<const WEAK: bool>(
+    &mut self,
+    on_update: Function<ReachabilityFlags, ()>,
+  ) -> Result<()> {
     let change_handler = on_update
       .build_threadsafe_function()
       .callee_handled::<false>()
-      .weak::<true>()
-      .build_callback(ctx_to_path)?;
-    let cb = move |path: *mut c_void| {
-      change_handler.call(path.cast(), ThreadsafeFunctionCallMode::NonBlocking);
+      .weak::<WEAK>()
+      .build_callback(|ctx: ThreadsafeCallContext<ReachabilityFlags>| Ok(ctx.value))?;
+    self.handler.lock().unwrap().replace(Box::new(move |flags| {
+      change_handler.call(flags, ThreadsafeFunctionCallMode::NonBlocking);
+    }));
+    if self.started {
+      return Ok(());
+    }
+
+    let mut context = ffi::SCNetworkReachabilityContext {
+      version: 0,
+      info: Arc::as_ptr(&self.handler) as *mut c_void,
+      retain: Some(retain_arc::<Mutex<Option<ReachabilityHandler>>>),
+      release: Some(release_arc::<Mutex<Option<ReachabilityHandler>>>),
+      copy_description: None,
     };
-    unsafe {
-      ffi::nw_path_monitor_set_update_handler(self.pm, &RcBlock::new(cb));
+    let scheduled = unsafe {
+      ffi::SCNetworkReachabilitySetCallback(self.target, Some(reachability_changed), &mut context)
+        != 0
+        && ffi::SCNetworkReachabilitySetDispatchQueue(self.target, self.queue) != 0
     };
-    unsafe { ffi::nw_path_monitor_start(self.pm) };
+    if !scheduled {
+      self.stop();
+      return Err(Error::new(
+        Status::GenericFailure,
+        "Failed to schedule the SCNetworkReachability callback",
+      ));
+    }
+    self.started = true;
     Ok(())
   }
 
   #[napi]
-  /// Stop the path monitor.
+  /// Stop watching the host.
   ///
-  /// If you don't call this method and leave the monitor alone, it will be stopped automatically when it is GC.
-  pub fn stop(&mut self) -> Result<()> {
-    unsafe { ffi::nw_path_monitor_cancel(self.pm) };
-    Ok(())
+  /// If you don't call this method and leave the instance alone, it will be stopped automatically when it is GC.
+  pub fn stop(&mut self) {
+    unsafe {
+      ffi::SCNetworkReachabilitySetDispatchQueue(self.target, std::ptr::null_mut());
+      // releases the reference of the handler held by the context
+      ffi::SCNetworkReachabilitySetCallback(self.target, None, std::ptr::null_mut());
+    }
+    self.handler.lock().unwrap().take();
+    self.started = false;
   }
 }
 
-#[inline]
-fn ctx_to_path(ctx: ThreadsafeCallContext<ffi::nw_path_t>) -> Result<NWPath> {
-  Ok(NWPath {
-    status: unsafe { ffi::nw_path_get_status(ctx.value).into() },
-    is_expensive: unsafe { ffi::nw_path_is_expensive(ctx.value) },
-    is_low_data_mode: unsafe { ffi::nw_path_is_constrained(ctx.value) },
-    has_ipv4: unsafe { ffi::nw_path_has_ipv4(ctx.value) },
-    has_ipv6: unsafe { ffi::nw_path_has_ipv6(ctx.value) },
-    has_dns: unsafe { ffi::nw_path_has_dns(ctx.value) },
+#[cfg(feature = "napi")]
+extern "C" fn reachability_changed(
+  _target: ffi::SCNetworkReachabilityRef,
+  flags: ffi::SCNetworkReachabilityFlags,
+  info: *mut c_void,
+) {
+  // SAFETY: the target holds a reference of the handler
+  let handler = unsafe { &*(info as *const Mutex<Option<ReachabilityHandler>>) };
+  autoreleasepool(|_| {
+    if let Some(handler) = handler.lock().unwrap().as_ref() {
+      handler(flags.into());
+    }
   })
 }
 
 #[allow(non_camel_case_types)]
+#[allow(non_upper_case_globals)]
 #[allow(unused)]
 mod ffi {
-  use core::ffi::{c_int, c_uint, c_void};
+  use core::ffi::{c_char, c_int, c_uint, c_void};
 
   use block2::Block;
 
@@ -185,6 +1974,104 @@ mod ffi {
     }
   }
 
+  enum_with_val! {
+    /// The reason a path is unsatisfied, available since macOS 11.
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct nw_path_unsatisfied_reason_t(pub c_int) {
+      NW_PATH_UNSATISFIED_REASON_NOT_AVAILABLE = 0,
+      NW_PATH_UNSATISFIED_REASON_CELLULAR_DENIED = 1,
+      NW_PATH_UNSATISFIED_REASON_WIFI_DENIED = 2,
+      NW_PATH_UNSATISFIED_REASON_LOCAL_NETWORK_DENIED = 3,
+      NW_PATH_UNSATISFIED_REASON_VPN_INACTIVE = 4,
+    }
+  }
+
+  enum_with_val! {
+    /// The kind of an endpoint, gateways are addresses.
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct nw_endpoint_type_t(pub c_int) {
+      NW_ENDPOINT_TYPE_INVALID = 0,
+      NW_ENDPOINT_TYPE_ADDRESS = 1,
+      NW_ENDPOINT_TYPE_HOST = 2,
+      NW_ENDPOINT_TYPE_BONJOUR_SERVICE = 3,
+      NW_ENDPOINT_TYPE_URL = 4,
+    }
+  }
+
+  pub const AF_INET: u8 = 2;
+  pub const AF_INET6: u8 = 30;
+  pub const AF_LINK: u8 = 18;
+
+  /// `IFT_*` of <net/if_types.h>, Wi-Fi is an Ethernet interface too
+  pub const IFT_ETHER: u8 = 0x6;
+  pub const IFT_CELLULAR: u8 = 0xff;
+
+  /// `struct if_data` of <net/if_var.h>, the `ifa_data` of the `AF_LINK` entries of `getifaddrs`
+  #[repr(C)]
+  pub struct if_data {
+    pub ifi_type: u8,
+    pub ifi_typelen: u8,
+    pub ifi_physical: u8,
+    pub ifi_addrlen: u8,
+    pub ifi_hdrlen: u8,
+    pub ifi_recvquota: u8,
+    pub ifi_xmitquota: u8,
+    pub ifi_unused1: u8,
+    pub ifi_mtu: u32,
+    pub ifi_metric: u32,
+    pub ifi_baudrate: u32,
+    pub ifi_ipackets: u32,
+    pub ifi_ierrors: u32,
+    pub ifi_opackets: u32,
+    pub ifi_oerrors: u32,
+    pub ifi_collisions: u32,
+    pub ifi_ibytes: u32,
+    pub ifi_obytes: u32,
+  }
+
+  /// `struct sockaddr` of <sys/socket.h>, BSD sockets start with the length
+  #[repr(C)]
+  pub struct sockaddr {
+    pub sa_len: u8,
+    pub sa_family: u8,
+    pub sa_data: [c_char; 14],
+  }
+
+  #[repr(C)]
+  pub struct sockaddr_in {
+    pub sin_len: u8,
+    pub sin_family: u8,
+    pub sin_port: u16,
+    /// In network byte order
+    pub sin_addr: u32,
+    pub sin_zero: [c_char; 8],
+  }
+
+  #[repr(C)]
+  pub struct sockaddr_in6 {
+    pub sin6_len: u8,
+    pub sin6_family: u8,
+    pub sin6_port: u16,
+    pub sin6_flowinfo: u32,
+    pub sin6_addr: [u8; 16],
+    pub sin6_scope_id: u32,
+  }
+
+  /// `struct ifaddrs` of <ifaddrs.h>
+  #[repr(C)]
+  pub struct ifaddrs {
+    pub ifa_next: *mut ifaddrs,
+    pub ifa_name: *mut c_char,
+    pub ifa_flags: c_uint,
+    pub ifa_addr: *mut sockaddr,
+    pub ifa_netmask: *mut sockaddr,
+    pub ifa_dstaddr: *mut sockaddr,
+    pub ifa_data: *mut c_void,
+  }
+
+  /// Search every image loaded in the process, `((void *) -2)` in <dlfcn.h>
+  pub const RTLD_DEFAULT: *mut c_void = -2isize as *mut c_void;
+
   #[repr(C)]
   // Dispatch.Framework
   // https://developer.apple.com/documentation/dispatch/dispatch_queue_t
@@ -193,6 +2080,12 @@ mod ffi {
   }
   pub type dispatch_queue_t = *mut dispatch_queue;
 
+  #[repr(C)]
+  pub struct dispatch_queue_attr {
+    _unused: [u8; 0],
+  }
+  pub type dispatch_queue_attr_t = *mut dispatch_queue_attr;
+
   #[repr(C)]
   pub struct dispatch_queue_global {
     _unused: [u8; 0],
@@ -209,6 +2102,13 @@ mod ffi {
 
   pub type nw_interface_type_t = c_int;
 
+  #[repr(C)]
+  pub struct nw_endpoint {
+    _unused: [u8; 0],
+  }
+
+  pub type nw_endpoint_t = *mut nw_endpoint;
+
   #[repr(C)]
   pub struct nw_path {
     _unused: [u8; 0],
@@ -221,6 +2121,21 @@ mod ffi {
   }
   pub type nw_path_monitor_t = *mut nw_path_monitor;
 
+  #[repr(C)]
+  pub struct nw_parameters {
+    _unused: [u8; 0],
+  }
+  pub type nw_parameters_t = *mut nw_parameters;
+
+  #[repr(C)]
+  pub struct nw_connection {
+    _unused: [u8; 0],
+  }
+  pub type nw_connection_t = *mut nw_connection;
+
+  /// `nw_parameters_configure_protocol_block_t`, called with an `nw_protocol_options_t`.
+  pub type nw_parameters_configure_protocol_block_t = &'static Block<dyn Fn(*mut c_void)>;
+
   #[cfg_attr(
     any(
       target_os = "macos",
@@ -232,13 +2147,169 @@ mod ffi {
     link(name = "System", kind = "dylib")
   )]
   extern "C" {
+    pub fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    pub fn free(ptr: *mut c_void);
+    pub fn getifaddrs(ifap: *mut *mut ifaddrs) -> c_int;
+    pub fn freeifaddrs(ifp: *mut ifaddrs);
+
     pub static _dispatch_main_q: dispatch_queue;
+    /// Creates a new dispatch queue, a null `attr` makes it serial.
+    pub fn dispatch_queue_create(
+      label: *const c_char,
+      attr: dispatch_queue_attr_t,
+    ) -> dispatch_queue_t;
+    pub fn dispatch_queue_attr_make_with_qos_class(
+      attr: dispatch_queue_attr_t,
+      qos_class: dispatch_qos_class_t,
+      relative_priority: c_int,
+    ) -> dispatch_queue_attr_t;
+    pub fn dispatch_retain(object: *mut c_void);
+    pub fn dispatch_release(object: *mut c_void);
     /// Returns a system-defined global concurrent queue with the specified quality-of-service class.
     pub fn dispatch_get_global_queue(
       identifier: dispatch_qos_class_t,
       flags: usize,
     ) -> dispatch_queue_global_t;
   }
+  pub type CFIndex = isize;
+  pub type CFStringEncoding = u32;
+  pub const kCFStringEncodingUTF8: CFStringEncoding = 0x0800_0100;
+
+  #[repr(C)]
+  pub struct __CFString {
+    _unused: [u8; 0],
+  }
+  pub type CFStringRef = *const __CFString;
+
+  #[repr(C)]
+  pub struct __CFArray {
+    _unused: [u8; 0],
+  }
+  pub type CFArrayRef = *const __CFArray;
+
+  #[repr(C)]
+  pub struct CFArrayCallBacks {
+    _unused: [u8; 0],
+  }
+
+  #[cfg_attr(
+    any(
+      target_os = "macos",
+      target_os = "ios",
+      target_os = "tvos",
+      target_os = "watchos",
+      target_os = "visionos"
+    ),
+    link(name = "CoreFoundation", kind = "framework")
+  )]
+  extern "C" {
+    pub static kCFTypeArrayCallBacks: CFArrayCallBacks;
+
+    pub fn CFStringCreateWithCString(
+      alloc: *const c_void,
+      c_str: *const c_char,
+      encoding: CFStringEncoding,
+    ) -> CFStringRef;
+    pub fn CFArrayCreate(
+      alloc: *const c_void,
+      values: *const *const c_void,
+      num_values: CFIndex,
+      call_backs: *const CFArrayCallBacks,
+    ) -> CFArrayRef;
+    pub fn CFRelease(cf: *const c_void);
+  }
+
+  #[repr(C)]
+  pub struct __SCDynamicStore {
+    _unused: [u8; 0],
+  }
+  pub type SCDynamicStoreRef = *const __SCDynamicStore;
+
+  pub type SCDynamicStoreCallBack =
+    extern "C" fn(store: SCDynamicStoreRef, changed_keys: CFArrayRef, info: *mut c_void);
+
+  #[repr(C)]
+  pub struct SCDynamicStoreContext {
+    pub version: CFIndex,
+    pub info: *mut c_void,
+    pub retain: Option<extern "C" fn(info: *const c_void) -> *const c_void>,
+    pub release: Option<extern "C" fn(info: *const c_void)>,
+    pub copy_description: Option<extern "C" fn(info: *const c_void) -> CFStringRef>,
+  }
+
+  #[repr(C)]
+  pub struct __SCNetworkReachability {
+    _unused: [u8; 0],
+  }
+  pub type SCNetworkReachabilityRef = *const __SCNetworkReachability;
+
+  pub type SCNetworkReachabilityFlags = u32;
+  pub const kSCNetworkReachabilityFlagsTransientConnection: SCNetworkReachabilityFlags = 1 << 0;
+  pub const kSCNetworkReachabilityFlagsReachable: SCNetworkReachabilityFlags = 1 << 1;
+  pub const kSCNetworkReachabilityFlagsConnectionRequired: SCNetworkReachabilityFlags = 1 << 2;
+  pub const kSCNetworkReachabilityFlagsInterventionRequired: SCNetworkReachabilityFlags = 1 << 4;
+  pub const kSCNetworkReachabilityFlagsIsLocalAddress: SCNetworkReachabilityFlags = 1 << 16;
+  pub const kSCNetworkReachabilityFlagsIsDirect: SCNetworkReachabilityFlags = 1 << 17;
+  pub const kSCNetworkReachabilityFlagsIsWWAN: SCNetworkReachabilityFlags = 1 << 18;
+
+  pub type SCNetworkReachabilityCallBack = extern "C" fn(
+    target: SCNetworkReachabilityRef,
+    flags: SCNetworkReachabilityFlags,
+    info: *mut c_void,
+  );
+
+  #[repr(C)]
+  pub struct SCNetworkReachabilityContext {
+    pub version: CFIndex,
+    pub info: *mut c_void,
+    pub retain: Option<extern "C" fn(info: *const c_void) -> *const c_void>,
+    pub release: Option<extern "C" fn(info: *const c_void)>,
+    pub copy_description: Option<extern "C" fn(info: *const c_void) -> CFStringRef>,
+  }
+
+  #[cfg_attr(
+    any(
+      target_os = "macos",
+      target_os = "ios",
+      target_os = "tvos",
+      target_os = "watchos",
+      target_os = "visionos"
+    ),
+    link(name = "SystemConfiguration", kind = "framework")
+  )]
+  extern "C" {
+    pub fn SCDynamicStoreCreate(
+      allocator: *const c_void,
+      name: CFStringRef,
+      callout: SCDynamicStoreCallBack,
+      context: *mut SCDynamicStoreContext,
+    ) -> SCDynamicStoreRef;
+    pub fn SCDynamicStoreSetNotificationKeys(
+      store: SCDynamicStoreRef,
+      keys: CFArrayRef,
+      patterns: CFArrayRef,
+    ) -> u8;
+    pub fn SCDynamicStoreSetDispatchQueue(store: SCDynamicStoreRef, queue: dispatch_queue_t) -> u8;
+
+    pub fn SCNetworkReachabilityCreateWithName(
+      allocator: *const c_void,
+      nodename: *const c_char,
+    ) -> SCNetworkReachabilityRef;
+    pub fn SCNetworkReachabilityGetFlags(
+      target: SCNetworkReachabilityRef,
+      flags: *mut SCNetworkReachabilityFlags,
+    ) -> u8;
+    pub fn SCNetworkReachabilitySetCallback(
+      target: SCNetworkReachabilityRef,
+      callout: Option<SCNetworkReachabilityCallBack>,
+      context: *mut SCNetworkReachabilityContext,
+    ) -> u8;
+    pub fn SCNetworkReachabilitySetDispatchQueue(
+      target: SCNetworkReachabilityRef,
+      queue: dispatch_queue_t,
+    ) -> u8;
+  }
+
   #[cfg_attr(
     any(
       target_os = "macos",
@@ -255,10 +2326,16 @@ mod ffi {
       required_interface_type: nw_interface_type_t,
     ) -> nw_path_monitor_t;
 
-    // pub fn nw_path_monitor_set_cancel_handler(
-    //   monitor: nw_path_monitor_t,
-    //   cancel_handler: nw_path_monitor_cancel_handler_t,
-    // );
+    pub fn nw_path_monitor_set_cancel_handler(
+      monitor: nw_path_monitor_t,
+      cancel_handler: &Block<dyn Fn()>,
+    );
+
+    /// Available since macOS 11.
+    pub fn nw_path_monitor_prohibit_interface_type(
+      monitor: nw_path_monitor_t,
+      interface_type: nw_interface_type_t,
+    );
 
     pub fn nw_path_monitor_set_update_handler(
       monitor: nw_path_monitor_t,
@@ -269,13 +2346,62 @@ mod ffi {
     pub fn nw_path_monitor_cancel(monitor: nw_path_monitor_t);
     pub fn nw_path_monitor_copy_current_path(monitor: nw_path_monitor_t) -> nw_path_t;
 
+    pub fn nw_retain(obj: *mut c_void) -> *mut c_void;
     pub fn nw_release(obj: *mut c_void);
 
     pub fn nw_path_get_status(path: nw_path_t) -> nw_path_status_t;
+    pub fn nw_path_is_equal(path: nw_path_t, other_path: nw_path_t) -> bool;
     pub fn nw_path_is_expensive(path: nw_path_t) -> bool;
     pub fn nw_path_is_constrained(path: nw_path_t) -> bool;
     pub fn nw_path_has_ipv4(path: nw_path_t) -> bool;
     pub fn nw_path_has_ipv6(path: nw_path_t) -> bool;
     pub fn nw_path_has_dns(path: nw_path_t) -> bool;
+    pub fn nw_path_enumerate_interfaces(
+      path: nw_path_t,
+      enumerate_block: &Block<dyn Fn(nw_interface_t) -> bool>,
+    );
+
+    pub fn nw_path_enumerate_gateways(
+      path: nw_path_t,
+      enumerate_block: &Block<dyn Fn(nw_endpoint_t) -> bool>,
+    );
+
+    pub fn nw_endpoint_create_host(hostname: *const c_char, port: *const c_char) -> nw_endpoint_t;
+    pub fn nw_endpoint_get_type(endpoint: nw_endpoint_t) -> nw_endpoint_type_t;
+    pub fn nw_endpoint_get_hostname(endpoint: nw_endpoint_t) -> *const c_char;
+    /// The returned string must be freed with `free`.
+    pub fn nw_endpoint_copy_address_string(endpoint: nw_endpoint_t) -> *mut c_char;
+
+    pub fn nw_interface_get_name(interface: nw_interface_t) -> *const c_char;
+    pub fn nw_interface_get_index(interface: nw_interface_t) -> u32;
+    pub fn nw_interface_get_type(interface: nw_interface_t) -> nw_interface_type_t;
+
+    pub fn nw_path_uses_interface_type(
+      path: nw_path_t,
+      interface_type: nw_interface_type_t,
+    ) -> bool;
+
+    /// NW_PARAMETERS_DISABLE_PROTOCOL
+    pub static _nw_parameters_configure_protocol_disable: nw_parameters_configure_protocol_block_t;
+    /// NW_PARAMETERS_DEFAULT_CONFIGURATION
+    pub static _nw_parameters_configure_protocol_default_configuration:
+      nw_parameters_configure_protocol_block_t;
+    pub fn nw_parameters_create_secure_tcp(
+      configure_tls: nw_parameters_configure_protocol_block_t,
+      configure_tcp: nw_parameters_configure_protocol_block_t,
+    ) -> nw_parameters_t;
+
+    pub fn nw_connection_create(
+      endpoint: nw_endpoint_t,
+      parameters: nw_parameters_t,
+    ) -> nw_connection_t;
+    pub fn nw_connection_set_queue(connection: nw_connection_t, queue: dispatch_queue_t);
+    pub fn nw_connection_set_path_changed_handler(
+      connection: nw_connection_t,
+      handler: &Block<dyn Fn(*mut c_void)>,
+    );
+    pub fn nw_connection_start(connection: nw_connection_t);
+    pub fn nw_connection_cancel(connection: nw_connection_t);
+    pub fn nw_connection_copy_current_path(connection: nw_connection_t) -> nw_path_t;
   }
 }
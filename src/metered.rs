@@ -0,0 +1,72 @@
+#[cfg(feature = "napi")]
+use napi_derive::napi;
+
+use crate::{ConnectionCostFlag, NetworkInfo};
+
+#[cfg_attr(feature = "napi", napi(object, object_from_js = false))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The billing related part of a `NetworkInfo`.
+pub struct MeteredStatus {
+  pub is_expensive: bool,
+  pub is_low_data_mode: bool,
+}
+
+impl MeteredStatus {
+  /// The mapping of Windows from the `NLM_CONNECTION_COST` bits, see `meteredStatusFromCostFlags`.
+  pub fn from_cost_flags(cost_flags: u32) -> Self {
+    let has_any =
+      |flags: &[ConnectionCostFlag]| flags.iter().any(|flag| cost_flags & *flag as u32 != 0);
+    Self {
+      is_expensive: has_any(&[
+        ConnectionCostFlag::Fixed,
+        ConnectionCostFlag::Variable,
+        ConnectionCostFlag::Roaming,
+        ConnectionCostFlag::OverDataLimit,
+      ]),
+      is_low_data_mode: has_any(&[
+        ConnectionCostFlag::OverDataLimit,
+        ConnectionCostFlag::ApproachingDataLimit,
+        ConnectionCostFlag::Congested,
+      ]),
+    }
+  }
+}
+
+#[cfg_attr(feature = "napi", napi)]
+/// The `MeteredStatus` Windows derives from `costFlags`: the connection is expensive when it's metered (a fixed
+/// or a variable plan), roaming or over its data limit, the same as "Metered connection" in the Settings app.
+/// It's in Low Data Mode when it's over or close to the data limit, or congested.
+///
+/// Use it with the `costFlags` of another source, or read `costFlags` and `dataLimitMb` to apply another policy.
+pub fn metered_status_from_cost_flags(cost_flags: u32) -> MeteredStatus {
+  MeteredStatus::from_cost_flags(cost_flags)
+}
+
+impl From<&NetworkInfo> for MeteredStatus {
+  fn from(info: &NetworkInfo) -> Self {
+    Self {
+      is_expensive: info.is_expensive,
+      is_low_data_mode: info.is_low_data_mode,
+    }
+  }
+}
+
+#[cfg(feature = "napi")]
+/// Build the `ChangeHandler` behind `onMeteredChange`, it only calls `on_change` when the `MeteredStatus` flips.
+///
+/// Without a `current` snapshot the first one received is the baseline and is not reported.
+pub(crate) fn change_handler(
+  current: Option<&NetworkInfo>,
+  on_change: napi::bindgen_prelude::Function<MeteredStatus, ()>,
+  weak: bool,
+) -> napi::Result<crate::ChangeHandler> {
+  let emit = crate::callback(on_change, weak)?;
+  let last = std::sync::Mutex::new(current.map(MeteredStatus::from));
+  Ok(Box::new(move |info| {
+    let status = MeteredStatus::from(&info);
+    let previous = last.lock().unwrap().replace(status);
+    if previous.is_some_and(|previous| previous != status) {
+      emit(status);
+    }
+  }))
+}
@@ -0,0 +1,516 @@
+// The `mock` feature replaces the platform `InternetMonitor` with one driven from JavaScript by `pushState`,
+// for testing the reconnection logic of an app without real network transitions, e.g. offline -> portal -> online in CI.
+//
+// The `start` callbacks run synchronously inside `pushState`. The derived events (`startWithBackoff`, `onMeteredChange`,
+// `onUnmeteredChange`, `onStableOnline`) go through the same handlers as the real backends, so they are filtered,
+// delayed and queued the same way.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::emitter::{Emitter, EventPayload};
+use crate::filter::InterfaceTypeFilter;
+use crate::history::{History, NetworkHistoryEntry};
+use crate::native;
+use crate::ready::Ready;
+use crate::throughput::{Throughput, ThroughputSampler};
+use crate::BackoffEvent;
+use crate::BackoffOptions;
+use crate::ChangeHandler;
+use crate::ConnectionClass;
+use crate::CreateOptions;
+use crate::InterfaceNetworkInfo;
+use crate::KnownNetwork;
+use crate::MeteredStatus;
+use crate::MonitorEvent;
+use crate::NWInterfaceType;
+use crate::NetworkCategory;
+use crate::NetworkInfo;
+use crate::NetworkStatus;
+use crate::PowerState;
+use napi::bindgen_prelude::*;
+use napi::JsObject;
+use napi_derive::napi;
+
+/// The latest state pushed to any mock monitor, returned by `getGlobalNetworkInfo`.
+static LATEST_STATE: Mutex<Option<NetworkInfo>> = Mutex::new(None);
+
+pub struct CreateInternetMonitor {
+  timeout_ms: Option<u32>,
+  history_capacity: Option<u32>,
+}
+
+impl Task for CreateInternetMonitor {
+  type Output = ();
+  type JsValue = InternetMonitor;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    crate::init_with_timeout(self.timeout_ms, || Ok(()))
+  }
+
+  fn resolve(&mut self, _env: Env, _: Self::Output) -> Result<Self::JsValue> {
+    let mut monitor = InternetMonitor::new(None);
+    monitor.history = History::new(self.history_capacity);
+    Ok(monitor)
+  }
+
+  fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+    crate::reject_create(&env, err)
+  }
+}
+
+#[napi]
+pub struct InternetMonitor {
+  network_info: NetworkInfo,
+  /// The callback of `start`, called synchronously by `pushState`
+  on_update: Option<FunctionRef<NetworkInfo, ()>>,
+  /// The handler of `startWithBackoff`, `onMeteredChange`, `onUnmeteredChange` or `onStableOnline`
+  handler: Option<ChangeHandler>,
+  weak_by_default: bool,
+  /// Set by `close`, every method throws afterwards
+  is_disposed: AtomicBool,
+  /// The sampling thread of `startThroughput`
+  throughput_sampler: Option<ThroughputSampler>,
+  /// The listeners of `on`
+  emitter: Emitter,
+  history: History,
+  /// The pending promises of `connected`
+  ready: Ready,
+  /// Set by `newWithType`, `pushState` only notifies the callbacks of the matching states
+  interface_filter: Option<InterfaceTypeFilter>,
+}
+
+impl Default for InternetMonitor {
+  fn default() -> Self {
+    Self::new(None)
+  }
+}
+
+#[napi]
+impl InternetMonitor {
+  #[napi(constructor)]
+  /// Create a mock InternetMonitor, it's offline until the first `pushState`. `timeoutMs` is ignored.
+  pub fn new(options: Option<CreateOptions>) -> Self {
+    let options = options.unwrap_or_default();
+    let mut monitor = Self::with_state(offline());
+    monitor.history = History::new(options.history_capacity);
+    monitor
+  }
+
+  #[napi(factory)]
+  /// Create a mock InternetMonitor which only notifies the callbacks while the pushed `interfaceType` is `interfaceType`,
+  /// and once more when it moves away from it.
+  pub fn new_with_type(interface_type: NWInterfaceType) -> Self {
+    let mut monitor = Self::new(None);
+    monitor.interface_filter = Some(InterfaceTypeFilter::new(
+      interface_type,
+      &monitor.network_info,
+    ));
+    monitor
+  }
+
+  #[napi(factory)]
+  /// Create a mock InternetMonitor whose derived events use weak threadsafe functions, the same as the real backends.
+  pub fn new_weak_by_default() -> Self {
+    let mut monitor = Self::new(None);
+    monitor.weak_by_default = true;
+    monitor
+  }
+
+  #[napi(ts_return_type = "Promise<InternetMonitor>")]
+  /// Resolve a mock InternetMonitor, the timeout is accepted for parity with the real backends.
+  pub fn create(options: Option<CreateOptions>) -> AsyncTask<CreateInternetMonitor> {
+    let options = options.unwrap_or_default();
+    AsyncTask::new(CreateInternetMonitor {
+      timeout_ms: options.timeout_ms,
+      history_capacity: options.history_capacity,
+    })
+  }
+
+  fn with_state(network_info: NetworkInfo) -> Self {
+    Self {
+      network_info,
+      on_update: None,
+      handler: None,
+      weak_by_default: false,
+      is_disposed: AtomicBool::new(false),
+      throughput_sampler: None,
+      emitter: Emitter::default(),
+      history: History::default(),
+      ready: Ready::default(),
+      interface_filter: None,
+    }
+  }
+
+  #[napi]
+  /// Replace the current state and notify the registered callback, as if the platform reported a change.
+  pub fn push_state(&mut self, env: Env, info: NetworkInfo) -> Result<()> {
+    self.check_open()?;
+    self.network_info = info.clone();
+    LATEST_STATE.lock().unwrap().replace(info.clone());
+    self.ready.observe(&info);
+    if self.handler.is_some() || self.on_update.is_some() {
+      self.history.push(&info);
+    }
+    let interface_filter = self.interface_filter.as_ref();
+    if !interface_filter.is_none_or(|interface_filter| interface_filter.matches(&info)) {
+      return Ok(());
+    }
+    if let Some(handler) = self.handler.as_ref() {
+      handler(info.clone());
+    }
+    if let Some(on_update) = self.on_update.as_ref() {
+      on_update.borrow_back(&env)?.call(info)?;
+    }
+    Ok(())
+  }
+
+  #[napi]
+  /// The last snapshots delivered to the callback, oldest first, `historyCapacity` of them at most.
+  ///
+  /// They are recorded while the monitor is started, whichever callback it's started with.
+  pub fn history(&self) -> Result<Vec<NetworkHistoryEntry>> {
+    self.check_open()?;
+    Ok(self.history.entries())
+  }
+
+  #[napi]
+  pub fn current(&self) -> Result<NetworkInfo> {
+    self.check_open()?;
+    Ok(self.network_info.clone())
+  }
+
+  #[napi]
+  /// The same as `current`, the mock only changes on `pushState`.
+  pub fn refresh(&self) -> Result<NetworkInfo> {
+    self.current()
+  }
+
+  #[napi]
+  /// Always empty, the mock only has the state pushed to it.
+  pub fn current_all(&self) -> Result<Vec<InterfaceNetworkInfo>> {
+    self.check_open()?;
+    Ok(Vec::new())
+  }
+
+  #[napi]
+  /// The `networkCategory` of the pushed state, `Unknown` when it has none.
+  pub fn network_category(&self) -> Result<NetworkCategory> {
+    self.check_open()?;
+    let network_category = self.network_info.network_category;
+    Ok(network_category.unwrap_or(NetworkCategory::Unknown))
+  }
+
+  #[napi]
+  /// Always empty, the mock only has the state pushed to it.
+  pub fn list_networks(&self) -> Result<Vec<KnownNetwork>> {
+    self.check_open()?;
+    Ok(Vec::new())
+  }
+
+  #[napi]
+  /// A coarse quality tier of the current connection, see `ConnectionClass` for the thresholds.
+  pub fn connection_class(&self) -> Result<ConnectionClass> {
+    self.check_open()?;
+    Ok(ConnectionClass::new(&self.network_info))
+  }
+
+  #[napi]
+  /// It's safe to do a large download right now: the network is `Satisfied`, not expensive, not in Low Data Mode and not roaming.
+  pub fn can_use_unmetered(&self) -> Result<bool> {
+    self.check_open()?;
+    Ok(self.network_info.can_use_unmetered())
+  }
+
+  #[napi]
+  /// Whether the machine runs on battery, only reported on Windows for now, `null` on the other platforms.
+  pub fn power_state(&self) -> Result<Option<PowerState>> {
+    self.check_open()?;
+    Ok(None)
+  }
+
+  #[napi]
+  /// Serialize the current `NetworkInfo` to JSON with the schema shared by every platform, for logging and telemetry.
+  pub fn snapshot_json(&self) -> Result<String> {
+    self.check_open()?;
+    crate::snapshot_json(&self.network_info)
+  }
+
+  #[napi]
+  /// Call `onUpdate` synchronously on every `pushState`.
+  ///
+  /// The mock never keeps the Node.js alive, whatever the monitor was created with.
+  pub fn start(&mut self, on_update: Function<NetworkInfo, ()>) -> Result<()> {
+    self.check_open()?;
+    self.on_update = Some(on_update.create_ref()?);
+    self.handler = None;
+    Ok(())
+  }
+
+  #[napi]
+  /// The same as `start`, for parity with the real backends.
+  pub fn start_strong(&mut self, on_update: Function<NetworkInfo, ()>) -> Result<()> {
+    self.start(on_update)
+  }
+
+  #[napi]
+  /// The same as `start`, for parity with the real backends.
+  pub fn start_weak(&mut self, on_update: Function<NetworkInfo, ()>) -> Result<()> {
+    self.start(on_update)
+  }
+
+  #[napi]
+  /// Emit `RetrySuggested` events with an exponential backoff while the network is down,
+  /// and a single `Reconnected` event once it's back.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn start_with_backoff(
+    &mut self,
+    options: Option<BackoffOptions>,
+    on_event: Function<BackoffEvent, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::backoff::change_handler(options.unwrap_or_default(), on_event, self.weak_by_default)?;
+    // the monitor may already be offline, start counting from now
+    handler(self.current()?);
+    self.start_with_handler(handler);
+    Ok(())
+  }
+
+  #[napi]
+  /// Call `onChange` only when `isExpensive` or `isLowDataMode` flips, the connectivity changes are ignored.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_metered_change(&mut self, on_change: Function<MeteredStatus, ()>) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::metered::change_handler(Some(&self.current()?), on_change, self.weak_by_default)?;
+    self.start_with_handler(handler);
+    Ok(())
+  }
+
+  #[napi]
+  /// Call `onChange` with the new `canUseUnmetered` when it flips, e.g. to pause the large downloads when the network
+  /// becomes expensive, Low Data Mode is turned on, roaming starts or the connection is lost.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_unmetered_change(&mut self, on_change: Function<bool, ()>) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::unmetered::change_handler(Some(&self.current()?), on_change, self.weak_by_default)?;
+    self.start_with_handler(handler);
+    Ok(())
+  }
+
+  #[napi]
+  /// Call `onChange` with the new interface type when the traffic moves to another one, e.g. from Wi-Fi to cellular
+  /// or from Ethernet to Wi-Fi, to reset the connections even though the status stays `Satisfied`.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_primary_interface_change(
+    &mut self,
+    on_change: Function<NWInterfaceType, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::primary::change_handler(Some(&self.current()?), on_change, self.weak_by_default)?;
+    self.start_with_handler(handler);
+    Ok(())
+  }
+
+  #[napi]
+  /// Call `onReconnect` with how long the network was offline, in milliseconds, when the status becomes `Satisfied` again,
+  /// e.g. to size the retry windows. Every offline episode starts the timer over.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_reconnect(&mut self, on_reconnect: Function<f64, ()>) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::reconnect::change_handler(Some(&self.current()?), on_reconnect, self.weak_by_default)?;
+    self.start_with_handler(handler);
+    Ok(())
+  }
+
+  #[napi]
+  /// Call `onOnline` once the network stayed `Satisfied` for `minStableMs` in a row, any other status in between
+  /// starts the wait over. Unlike the first `Satisfied` update, it's not fired while the connectivity flaps.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_stable_online(
+    &mut self,
+    min_stable_ms: u32,
+    on_online: Function<NetworkInfo, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler = crate::stable::change_handler(min_stable_ms, on_online, self.weak_by_default)?;
+    // the monitor may already be online, start counting from now
+    handler(self.current()?);
+    self.start_with_handler(handler);
+    Ok(())
+  }
+
+  #[napi]
+  /// Call `onUpdate` only with the snapshots matching `filter`, a combination of `ChangeFilter` flags,
+  /// the other ones are dropped before they cross into JavaScript.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn start_filtered(
+    &mut self,
+    filter: u32,
+    on_update: Function<NetworkInfo, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler = crate::filter::change_handler(
+      filter,
+      Some(&self.current()?),
+      on_update,
+      self.weak_by_default,
+    )?;
+    self.start_with_handler(handler);
+    Ok(())
+  }
+
+  #[napi(ts_args_type = "event: MonitorEvent, listener: (arg: any) => void")]
+  /// Add a listener for `event`, like `EventEmitter.on`. The `change`, `online` and `offline` listeners
+  /// receive a `NetworkInfo`, the `metered` ones a `MeteredStatus` and the `error` ones an `Error`.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on(&mut self, event: MonitorEvent, listener: Function<EventPayload, ()>) -> Result<()> {
+    self.check_open()?;
+    self.emitter.on(event, listener, self.weak_by_default)?;
+    let handler = self.emitter.change_handler(Some(&self.current()?));
+    self.start_with_handler(handler);
+    Ok(())
+  }
+
+  #[napi(ts_args_type = "event: MonitorEvent, listener: (arg: any) => void")]
+  /// Remove a listener added by `on`, the monitor is stopped once the last one is removed.
+  pub fn off(
+    &mut self,
+    env: Env,
+    event: MonitorEvent,
+    listener: Function<EventPayload, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    if self.emitter.off(&env, event, listener)? && self.emitter.is_empty() {
+      self.unsubscribe();
+    }
+    Ok(())
+  }
+
+  #[napi(ts_return_type = "Promise<void>")]
+  /// Resolve once the monitor saw a `Satisfied` status, right away when it already did,
+  /// so it can be awaited before every request cheaply.
+  ///
+  /// It never resets, going offline afterwards doesn't make it wait again, listen to `offline` with `on` for that.
+  /// The mock resolves it on `pushState` whether it's started or not, `stop` rejects the pending promises.
+  pub fn connected(&mut self, env: Env) -> Result<JsObject> {
+    self.check_open()?;
+    self.ready.wait(&env, Some(&self.network_info))
+  }
+
+  #[napi(ts_return_type = "Promise<void>")]
+  /// Alias of `connected`.
+  pub fn ready(&mut self, env: Env) -> Result<JsObject> {
+    self.connected(env)
+  }
+
+  fn start_with_handler(&mut self, handler: ChangeHandler) {
+    self.handler = Some(handler);
+    self.on_update = None;
+  }
+
+  #[napi]
+  /// Sample the byte counters of the physical interfaces every `intervalMs` on a separate thread,
+  /// `throughput` returns the rate between the last two samples. Starting it again restarts the sampling.
+  ///
+  /// It measures the total traffic of the machine, not the traffic of the app. The mock samples the real interfaces,
+  /// it doesn't fake the throughput.
+  /// The sampling is independent of `start` and `stop`, it runs until `stopThroughput` or `close`.
+  pub fn start_throughput(&mut self, interval_ms: u32) -> Result<()> {
+    self.check_open()?;
+    // join the previous sampling thread before spawning another one
+    self.throughput_sampler.take();
+    self.throughput_sampler = Some(ThroughputSampler::start(interval_ms)?);
+    Ok(())
+  }
+
+  #[napi]
+  /// The received and sent bytes per second, `null` until `startThroughput` sampled twice.
+  pub fn throughput(&self) -> Result<Option<Throughput>> {
+    self.check_open()?;
+    Ok(
+      self
+        .throughput_sampler
+        .as_ref()
+        .and_then(ThroughputSampler::throughput),
+    )
+  }
+
+  #[napi]
+  /// Stop the sampling started by `startThroughput`.
+  pub fn stop_throughput(&mut self) -> Result<()> {
+    self.check_open()?;
+    self.throughput_sampler.take();
+    Ok(())
+  }
+
+  #[napi]
+  /// Release the callbacks, `pushState` doesn't notify anything afterwards.
+  pub fn stop(&mut self) -> Result<()> {
+    self.check_open()?;
+    self.unsubscribe();
+    Ok(())
+  }
+
+  #[napi]
+  /// Stop the InternetMonitor and release its callback, every method throws once it's closed.
+  ///
+  /// Calling `close` again is a no-op.
+  pub fn close(&mut self) {
+    if self.is_disposed.swap(true, Ordering::AcqRel) {
+      return;
+    }
+    self.throughput_sampler.take();
+    self.unsubscribe();
+  }
+
+  fn check_open(&self) -> Result<()> {
+    if self.is_disposed.load(Ordering::Acquire) {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "monitor has been disposed",
+      ));
+    }
+    Ok(())
+  }
+
+  fn unsubscribe(&mut self) {
+    self.on_update = None;
+    self.handler = None;
+    self.emitter.clear();
+    self.ready.cancel();
+  }
+}
+
+#[napi]
+/// The latest state pushed to any mock monitor, offline until the first `pushState`.
+pub fn get_global_network_info() -> Result<NetworkInfo> {
+  Ok(current_network_info()?)
+}
+
+pub(crate) fn current_network_info() -> native::Result<NetworkInfo> {
+  Ok(latest_state())
+}
+
+fn latest_state() -> NetworkInfo {
+  LATEST_STATE.lock().unwrap().clone().unwrap_or_else(offline)
+}
+
+fn offline() -> NetworkInfo {
+  NetworkInfo {
+    status: NetworkStatus::Unsatisfied,
+    ..Default::default()
+  }
+}
@@ -0,0 +1,67 @@
+//! The detection without Node.js, for the Rust programs depending on the crate as an `rlib`.
+//!
+//! `getGlobalNetworkInfo` runs the same code, nothing here needs a JavaScript environment.
+//! Build the crate with `default-features = false` to leave the Node.js bindings out.
+
+use std::fmt;
+
+use crate::NetworkInfo;
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+use crate::macos::current_network_info as platform_network_info;
+
+#[cfg(all(target_os = "windows", not(feature = "mock")))]
+use crate::windows::current_network_info as platform_network_info;
+
+#[cfg(all(target_os = "linux", not(feature = "gio"), not(feature = "mock")))]
+use crate::linux::current_network_info as platform_network_info;
+
+#[cfg(all(target_os = "linux", feature = "gio", not(feature = "mock")))]
+use crate::gio::current_network_info as platform_network_info;
+
+#[cfg(all(feature = "mock", not(any(target_os = "macos", target_os = "ios"))))]
+use crate::mock::current_network_info as platform_network_info;
+
+/// A failure of the platform backend, e.g. NetworkManager is not running or the netprofm service hangs.
+///
+/// The backends return it, the bindings turn it into a JavaScript `Error` with the same message.
+#[derive(Debug, Clone)]
+pub struct Error {
+  message: String,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+  pub(crate) fn new(message: impl Into<String>) -> Self {
+    Self {
+      message: message.into(),
+    }
+  }
+
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.message)
+  }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(feature = "napi")]
+impl From<Error> for napi::Error {
+  fn from(err: Error) -> Self {
+    napi::Error::new(napi::Status::GenericFailure, err.message)
+  }
+}
+
+/// Read the network state once, the same snapshot as `getGlobalNetworkInfo`.
+///
+/// It blocks while the platform backend is created, e.g. until the first path arrives on macOS.
+pub fn current_network_info() -> Result<NetworkInfo> {
+  platform_network_info()
+}
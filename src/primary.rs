@@ -0,0 +1,40 @@
+use std::sync::Mutex;
+
+use napi::bindgen_prelude::*;
+
+use crate::{ChangeHandler, NWInterfaceType, NetworkInfo};
+
+/// Build the `ChangeHandler` behind `onPrimaryInterfaceChange`, it only calls `on_change` when the traffic moves to
+/// another type of interface. Going offline in between doesn't count, Wi-Fi, offline and Wi-Fi again is not reported.
+///
+/// Without a `current` snapshot the first one with an interface is the baseline and is not reported.
+pub(crate) fn change_handler(
+  current: Option<&NetworkInfo>,
+  on_change: Function<NWInterfaceType, ()>,
+  weak: bool,
+) -> Result<ChangeHandler> {
+  let emit = crate::callback(on_change, weak)?;
+  let last = Mutex::new(current.and_then(primary_interface_type));
+  Ok(Box::new(move |info| {
+    let Some(interface_type) = primary_interface_type(&info) else {
+      return;
+    };
+    let previous = last.lock().unwrap().replace(interface_type);
+    if previous.is_some_and(|previous| previous != interface_type) {
+      emit(interface_type);
+    }
+  }))
+}
+
+/// The type of the interface carrying the traffic.
+///
+/// macOS enumerates the interfaces of the path in the order of preference, the first one which is not a VPN tunnel
+/// is the primary one. The other platforms only report `interface_type`, which already follows the default route.
+fn primary_interface_type(info: &NetworkInfo) -> Option<NWInterfaceType> {
+  info
+    .interfaces
+    .iter()
+    .map(|interface| interface.interface_type)
+    .find(|interface_type| *interface_type != NWInterfaceType::Other)
+    .or(info.interface_type)
+}
@@ -0,0 +1,76 @@
+use std::sync::{Arc, Mutex};
+
+use napi::bindgen_prelude::*;
+use napi::{JsDeferred, JsObject};
+
+use crate::{ChangeHandler, NetworkInfo, NetworkStatus};
+
+type Deferred = JsDeferred<(), fn(Env) -> Result<()>>;
+
+#[derive(Default)]
+struct ReadyState {
+  /// A `Satisfied` snapshot was seen, it's never reset
+  satisfied: bool,
+  waiters: Vec<Deferred>,
+}
+
+/// The promises of `connected`, resolved together on the first `Satisfied` snapshot.
+#[derive(Clone, Default)]
+pub(crate) struct Ready {
+  state: Arc<Mutex<ReadyState>>,
+}
+
+impl Ready {
+  /// A promise resolved right away if the monitor was already online once or `current` is, pending otherwise.
+  pub(crate) fn wait(&self, env: &Env, current: Option<&NetworkInfo>) -> Result<JsObject> {
+    let (deferred, promise) = env.create_deferred::<(), fn(Env) -> Result<()>>()?;
+    let mut state = self.state.lock().unwrap();
+    state.satisfied |= current.is_some_and(is_satisfied);
+    if state.satisfied {
+      deferred.resolve(|_| Ok(()));
+    } else {
+      state.waiters.push(deferred);
+    }
+    Ok(promise)
+  }
+
+  #[cfg_attr(feature = "mock", allow(dead_code))]
+  pub(crate) fn is_pending(&self) -> bool {
+    !self.state.lock().unwrap().waiters.is_empty()
+  }
+
+  pub(crate) fn observe(&self, info: &NetworkInfo) {
+    if !is_satisfied(info) {
+      return;
+    }
+    let mut state = self.state.lock().unwrap();
+    state.satisfied = true;
+    for deferred in state.waiters.drain(..) {
+      deferred.resolve(|_| Ok(()));
+    }
+  }
+
+  /// Resolve the pending promises before `handler` receives the snapshot.
+  #[cfg_attr(feature = "mock", allow(dead_code))]
+  pub(crate) fn watch(&self, handler: ChangeHandler) -> ChangeHandler {
+    let ready = self.clone();
+    Box::new(move |info| {
+      ready.observe(&info);
+      handler(info);
+    })
+  }
+
+  /// Reject the pending promises, the monitor is stopped and won't see the next snapshots.
+  pub(crate) fn cancel(&self) {
+    for deferred in self.state.lock().unwrap().waiters.drain(..) {
+      deferred.reject(Error::new(
+        Status::Cancelled,
+        "The monitor was stopped before it was online",
+      ));
+    }
+  }
+}
+
+fn is_satisfied(info: &NetworkInfo) -> bool {
+  matches!(info.status, NetworkStatus::Satisfied)
+}
@@ -0,0 +1,47 @@
+use std::sync::Mutex;
+use std::time::Instant;
+
+use napi::bindgen_prelude::*;
+
+use crate::{ChangeHandler, NetworkInfo, NetworkStatus};
+
+/// Build the `ChangeHandler` behind `onReconnect`, it calls `on_reconnect` with how long the network was offline
+/// when the status becomes `Satisfied` again. Every status other than `Satisfied` counts as offline.
+///
+/// When `current` is already offline the first episode is measured from now, the monitor doesn't know when it started.
+pub(crate) fn change_handler(
+  current: Option<&NetworkInfo>,
+  on_reconnect: Function<f64, ()>,
+  weak: bool,
+) -> Result<ChangeHandler> {
+  let emit = crate::callback(on_reconnect, weak)?;
+  let offline_since = OfflineTimer::default();
+  if let Some(current) = current {
+    offline_since.observe(current);
+  }
+  Ok(Box::new(move |info| {
+    if let Some(duration_ms) = offline_since.observe(&info) {
+      emit(duration_ms);
+    }
+  }))
+}
+
+/// When the current offline episode started, `None` while online.
+#[derive(Default)]
+struct OfflineTimer {
+  offline_since: Mutex<Option<Instant>>,
+}
+
+impl OfflineTimer {
+  /// The offline duration in milliseconds when `info` ends an offline episode.
+  fn observe(&self, info: &NetworkInfo) -> Option<f64> {
+    let mut offline_since = self.offline_since.lock().unwrap();
+    if info.status != NetworkStatus::Satisfied {
+      offline_since.get_or_insert_with(Instant::now);
+      return None;
+    }
+    offline_since
+      .take()
+      .map(|offline_since| offline_since.elapsed().as_secs_f64() * 1000.0)
+  }
+}
@@ -0,0 +1,123 @@
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use napi::bindgen_prelude::*;
+
+use crate::{ChangeHandler, NetworkInfo, NetworkStatus};
+
+struct StableState {
+  info: Option<NetworkInfo>,
+  online: bool,
+  /// Bumped on every online/offline transition, so a sleeping timer knows the stretch it waits for is over
+  generation: u64,
+  /// The current online stretch was already reported
+  fired: bool,
+  shutdown: bool,
+}
+
+struct Shared {
+  state: Mutex<StableState>,
+  wakeup: Condvar,
+  min_stable: Duration,
+  emit: ChangeHandler,
+}
+
+/// Reports the snapshot of a monitor once it stayed `Satisfied` for `min_stable` in a row,
+/// any other status in between starts the wait over.
+///
+/// The timer runs on its own thread, it's stopped and joined when the `StableOnline` is dropped.
+struct StableOnline {
+  shared: Arc<Shared>,
+  timer: Option<JoinHandle<()>>,
+}
+
+impl StableOnline {
+  fn new(min_stable_ms: u32, emit: ChangeHandler) -> Self {
+    let shared = Arc::new(Shared {
+      state: Mutex::new(StableState {
+        info: None,
+        online: false,
+        generation: 0,
+        fired: false,
+        shutdown: false,
+      }),
+      wakeup: Condvar::new(),
+      min_stable: Duration::from_millis(min_stable_ms.into()),
+      emit,
+    });
+    let timer_shared = shared.clone();
+    let timer = std::thread::spawn(move || run_timer(&timer_shared));
+    Self {
+      shared,
+      timer: Some(timer),
+    }
+  }
+
+  fn update(&self, info: NetworkInfo) {
+    let online = matches!(info.status, NetworkStatus::Satisfied);
+    let mut state = self.shared.state.lock().unwrap();
+    if state.online != online {
+      state.online = online;
+      state.fired = false;
+      state.generation += 1;
+    }
+    state.info = Some(info);
+    drop(state);
+    self.shared.wakeup.notify_all();
+  }
+}
+
+impl Drop for StableOnline {
+  fn drop(&mut self) {
+    self.shared.state.lock().unwrap().shutdown = true;
+    self.shared.wakeup.notify_all();
+    if let Some(timer) = self.timer.take() {
+      let _ = timer.join();
+    }
+  }
+}
+
+fn run_timer(shared: &Shared) {
+  let mut state = shared.state.lock().unwrap();
+  loop {
+    if state.shutdown {
+      return;
+    }
+    if !state.online || state.fired {
+      state = shared.wakeup.wait(state).unwrap();
+      continue;
+    }
+
+    let generation = state.generation;
+    let deadline = Instant::now() + shared.min_stable;
+    loop {
+      let now = Instant::now();
+      if state.shutdown || state.generation != generation || now >= deadline {
+        break;
+      }
+      state = shared.wakeup.wait_timeout(state, deadline - now).unwrap().0;
+    }
+    if state.shutdown || state.generation != generation {
+      continue;
+    }
+
+    state.fired = true;
+    let info = state.info.clone().expect("online without a snapshot");
+    // don't block `update` while the snapshot is queued
+    drop(state);
+    (shared.emit)(info);
+    state = shared.state.lock().unwrap();
+  }
+}
+
+/// Build the `ChangeHandler` behind `onStableOnline`, the timer lives as long as the handler.
+pub(crate) fn change_handler(
+  min_stable_ms: u32,
+  on_online: Function<NetworkInfo, ()>,
+  weak: bool,
+) -> Result<ChangeHandler> {
+  let emit: ChangeHandler = crate::callback(on_online, weak)?;
+  let stable = StableOnline::new(min_stable_ms, emit);
+  Ok(Box::new(move |info| stable.update(info)))
+}
@@ -0,0 +1,175 @@
+// A passive throughput estimate for the adaptive media, sampled on its own thread from the byte counters
+// of the physical interfaces, independently of the change events.
+//
+// It measures the total traffic of the machine, every process included, not the traffic of the app.
+
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+#[napi(object, object_from_js = false)]
+#[derive(Debug, Clone, Copy)]
+/// The traffic of the physical interfaces between the last two samples.
+pub struct Throughput {
+  /// Received bytes per second
+  pub rx_bps: f64,
+  /// Sent bytes per second
+  pub tx_bps: f64,
+}
+
+/// The received and sent bytes summed over the physical interfaces since they came up.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ByteCounters {
+  pub(crate) rx_bytes: u64,
+  pub(crate) tx_bytes: u64,
+}
+
+/// Reads the byte counters every `interval` until it's dropped.
+pub(crate) struct ThroughputSampler {
+  latest: Arc<Mutex<Option<Throughput>>>,
+  /// Dropped to wake the sampling thread up and stop it
+  stop: Option<mpsc::Sender<()>>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl ThroughputSampler {
+  pub(crate) fn start(interval_ms: u32) -> Result<Self> {
+    if interval_ms == 0 {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "intervalMs must be greater than 0",
+      ));
+    }
+    let interval = Duration::from_millis(interval_ms as u64);
+    // the first read tells whether the platform has the counters at all
+    let mut previous = (read_byte_counters()?, Instant::now());
+    let latest = Arc::new(Mutex::new(None));
+    let (stop, stopped) = mpsc::channel::<()>();
+    let thread = std::thread::Builder::new()
+      .name("network-change-throughput".to_owned())
+      .spawn({
+        let latest = latest.clone();
+        move || loop {
+          match stopped.recv_timeout(interval) {
+            Err(RecvTimeoutError::Timeout) => {}
+            _ => break,
+          }
+          // e.g. an interface went away in the middle of the read, try again on the next tick
+          let Ok(counters) = read_byte_counters() else {
+            continue;
+          };
+          let now = Instant::now();
+          let (previous_counters, previous_at) = previous;
+          let seconds = now.duration_since(previous_at).as_secs_f64();
+          // the counters start over when an interface is removed or wraps around, count it as no traffic
+          let rx_bytes = counters.rx_bytes.saturating_sub(previous_counters.rx_bytes);
+          let tx_bytes = counters.tx_bytes.saturating_sub(previous_counters.tx_bytes);
+          latest.lock().unwrap().replace(Throughput {
+            rx_bps: rx_bytes as f64 / seconds,
+            tx_bps: tx_bytes as f64 / seconds,
+          });
+          previous = (counters, now);
+        }
+      })
+      .map_err(|err| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Failed to spawn the throughput sampling thread: {err}"),
+        )
+      })?;
+    Ok(Self {
+      latest,
+      stop: Some(stop),
+      thread: Some(thread),
+    })
+  }
+
+  /// `None` until the first interval elapsed.
+  pub(crate) fn throughput(&self) -> Option<Throughput> {
+    *self.latest.lock().unwrap()
+  }
+}
+
+impl Drop for ThroughputSampler {
+  fn drop(&mut self) {
+    self.stop.take();
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
+    }
+  }
+}
+
+fn read_byte_counters() -> Result<ByteCounters> {
+  byte_counters().map_err(|err| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to read the interface byte counters: {err}"),
+    )
+  })
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+use crate::macos::byte_counters;
+
+/// The interfaces backed by a device, the bridges, VPN tunnels and veth pairs of the containers
+/// would count the same traffic twice.
+#[cfg(target_os = "linux")]
+fn byte_counters() -> std::io::Result<ByteCounters> {
+  let mut counters = ByteCounters::default();
+  for entry in std::fs::read_dir("/sys/class/net")? {
+    let path = entry?.path();
+    if !path.join("device").exists() {
+      continue;
+    }
+    let read_counter = |name: &str| {
+      std::fs::read_to_string(path.join("statistics").join(name))
+        .ok()
+        .and_then(|counter| counter.trim().parse::<u64>().ok())
+        .unwrap_or(0)
+    };
+    counters.rx_bytes += read_counter("rx_bytes");
+    counters.tx_bytes += read_counter("tx_bytes");
+  }
+  Ok(counters)
+}
+
+/// The hardware interfaces, `GetIfTable2` also lists the filter drivers and the tunnels on top of them
+/// which would count the same traffic twice.
+#[cfg(target_os = "windows")]
+fn byte_counters() -> std::io::Result<ByteCounters> {
+  use windows::Win32::NetworkManagement::IpHelper::{FreeMibTable, GetIfTable2, MIB_IF_TABLE2};
+
+  let mut table: *mut MIB_IF_TABLE2 = std::ptr::null_mut();
+  let code = unsafe { GetIfTable2(&mut table) };
+  if code.0 != 0 {
+    return Err(std::io::Error::from_raw_os_error(code.0 as i32));
+  }
+  // SAFETY: the table is allocated by GetIfTable2 and alive until FreeMibTable
+  let rows =
+    unsafe { std::slice::from_raw_parts((*table).Table.as_ptr(), (*table).NumEntries as usize) };
+  let mut counters = ByteCounters::default();
+  // the first bit of the flags is `HardwareInterface`
+  for row in rows
+    .iter()
+    .filter(|row| row.InterfaceAndOperStatusFlags._bitfield & 1 != 0)
+  {
+    counters.rx_bytes += row.InOctets;
+    counters.tx_bytes += row.OutOctets;
+  }
+  unsafe { FreeMibTable(table.cast()) };
+  Ok(counters)
+}
+
+#[cfg(not(any(
+  target_os = "macos",
+  target_os = "ios",
+  target_os = "linux",
+  target_os = "windows"
+)))]
+fn byte_counters() -> std::io::Result<ByteCounters> {
+  Err(std::io::ErrorKind::Unsupported.into())
+}
@@ -0,0 +1,24 @@
+use std::sync::Mutex;
+
+use napi::bindgen_prelude::*;
+
+use crate::{ChangeHandler, NetworkInfo};
+
+/// Build the `ChangeHandler` behind `onUnmeteredChange`, it only calls `on_change` when `can_use_unmetered` flips.
+///
+/// Without a `current` snapshot the first one received is the baseline and is not reported.
+pub(crate) fn change_handler(
+  current: Option<&NetworkInfo>,
+  on_change: Function<bool, ()>,
+  weak: bool,
+) -> Result<ChangeHandler> {
+  let emit = crate::callback(on_change, weak)?;
+  let last = Mutex::new(current.map(NetworkInfo::can_use_unmetered));
+  Ok(Box::new(move |info| {
+    let unmetered = info.can_use_unmetered();
+    let previous = last.lock().unwrap().replace(unmetered);
+    if previous.is_some_and(|previous| previous != unmetered) {
+      emit(unmetered);
+    }
+  }))
+}
@@ -1,76 +1,324 @@
 use std::borrow::Cow;
 use std::mem::MaybeUninit;
-use std::rc::Rc;
-use std::sync::{
-  atomic::{AtomicBool, AtomicU8, Ordering},
-  Arc,
-};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
 
 use bitflags::bitflags;
+#[cfg(feature = "napi")]
 use napi::bindgen_prelude::*;
+#[cfg(feature = "napi")]
 use napi::threadsafe_function::{
   ThreadsafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode,
 };
+#[cfg(feature = "napi")]
+use napi::JsObject;
+#[cfg(feature = "napi")]
 use napi_derive::napi;
-use windows::Win32::Foundation::{self, ERROR_BUFFER_OVERFLOW};
+use windows::Devices::Radios::{Radio, RadioState};
+#[cfg(feature = "napi")]
+use windows::Foundation::TypedEventHandler;
+use windows::Networking::Connectivity::{
+  NetworkConnectivityLevel, NetworkCostType, NetworkInformation, NetworkStatusChangedEventHandler,
+};
+use windows::Win32::Foundation::{self, ERROR_BUFFER_OVERFLOW, ERROR_NO_DATA};
 use windows::Win32::NetworkManagement::Ndis::IfOperStatusUp;
 use windows::Win32::Networking::NetworkListManager::*;
+#[cfg(feature = "napi")]
+use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
 use windows::Win32::System::{self, Com::*};
+#[cfg(feature = "napi")]
+use windows_core::IInspectable;
 use windows_core::{implement, IUnknown, Interface, HRESULT};
 
-use crate::{NetworkInfo, NetworkStatus};
+use crate::native;
+#[cfg(feature = "napi")]
+use crate::{
+  emitter::{Emitter, EventPayload},
+  filter::InterfaceTypeFilter,
+  history::{History, NetworkHistoryEntry},
+  ready::Ready,
+  throughput::{Throughput, ThroughputSampler},
+  BackoffEvent, BackoffOptions, ConnectionClass, CreateOptions, EnvCleanupHook,
+  InterfaceNetworkInfo, KnownNetwork, MonitorEvent, PowerState,
+};
+use crate::{
+  ChangeHandler, ConnectivityLevel, ErrorHandler, IpStackType, MeteredStatus, NWInterfaceType,
+  NetworkCategory, NetworkInfo, NetworkStatus,
+};
 
+#[cfg(feature = "napi")]
 #[napi]
 pub struct InternetMonitor {
+  /// Releases the handlers when the environment is torn down first, removed by `close` or the GC
+  cleanup_hook: Option<EnvCleanupHook>,
+  change_handler: Arc<Mutex<Option<ChangeHandler>>>,
+  /// Receives the failures of the sinks, set by `on`
+  error_handler: Arc<Mutex<Option<ErrorHandler>>>,
+  /// Set by `onNetworkLifecycle`
+  lifecycle_handler: Arc<Mutex<Option<LifecycleHandler>>>,
+  /// Set by `onInterfaceChange`, shared with the IP Helper notification
+  interface_change_handler: Arc<Mutex<Option<InterfaceChangeHandler>>>,
+  /// Set by `onDefaultRouteChange`
+  route_change_handler: Arc<Mutex<Option<RouteChangeHandler>>>,
+  /// Set by `onAirplaneModeChange`
+  airplane_mode_handler: Arc<Mutex<Option<AirplaneModeHandler>>>,
+  /// The `ipInterfaceEvents` option
+  ip_interface_events: bool,
+  /// Registered by `onInterfaceChange`, independently of the COM objects
+  ip_interface_notification: Option<MibNotification<Mutex<Option<InterfaceChangeHandler>>>>,
+  /// Registered while the monitor is started, the address changes update `hasIpv4` and `hasIpv6` ahead of the network list manager
+  address_notification: Option<MibNotification<AddressWatch>>,
+  /// Registered while the monitor is started or `onDefaultRouteChange` is set
+  route_notification: Option<MibNotification<RouteWatch>>,
+  /// Registered while the monitor is started or `onAirplaneModeChange` is set
+  radio_watch: Option<RadioWatch>,
+  excluded_adapters: Arc<ExcludedAdapters>,
+  /// The listeners of `on`
+  emitter: Emitter,
+  history: History,
+  /// The pending promises of `connected`
+  ready: Ready,
+  /// Set by `newWithType`, the callbacks only see the snapshots of this interface type
+  interface_type: Option<NWInterfaceType>,
+  /// The latest snapshot, replaced as a whole by the event handlers so `current` never observes a half updated state.
+  network_info: Arc<Mutex<NetworkInfo>>,
+  weak_by_default: bool,
+  /// Set by `close`, every method throws afterwards
+  is_disposed: AtomicBool,
+  /// The sampling thread of `startThroughput`
+  throughput_sampler: Option<ThroughputSampler>,
+  backend: Backend,
+  /// Owns the COM objects, every call into them goes through it
+  com_thread: ComThread,
+}
+
+#[cfg(feature = "napi")]
+impl Drop for InternetMonitor {
+  fn drop(&mut self) {
+    // the connection points keep the sinks alive until they are unadvised, it's a no-op if `stop` was already called
+    let _ = self.unsubscribe();
+  }
+}
+
+#[cfg_attr(feature = "napi", napi(string_enum))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// What happened to the network of a `NetworkLifecycleEvent`.
+pub enum NetworkLifecycleKind {
+  /// INetworkEvents::NetworkAdded, e.g. a VPN connected or a hotspot appeared
+  Added,
+  /// INetworkEvents::NetworkDeleted
+  Deleted,
+}
+
+#[cfg_attr(feature = "napi", napi(object, object_from_js = false))]
+#[derive(Debug, Clone)]
+/// A network added to or deleted from the network list, before its connectivity is known.
+pub struct NetworkLifecycleEvent {
+  pub kind: NetworkLifecycleKind,
+  /// The GUID of the network, e.g. `{D81B5A2E-...}`
+  pub network_id: String,
+  /// The name of the network, `None` once it's deleted from the list
+  pub name: Option<String>,
+  /// The description of the network, `None` once it's deleted from the list
+  pub description: Option<String>,
+}
+
+type LifecycleHandler = Box<dyn Fn(NetworkLifecycleEvent) + Send + Sync>;
+
+#[cfg_attr(feature = "napi", napi(string_enum))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// What happened to the interface of an `IpInterfaceChangeEvent`.
+pub enum IpInterfaceChangeKind {
+  /// MibAddInstance
+  Added,
+  /// MibDeleteInstance
+  Deleted,
+  /// MibParameterNotification, e.g. the interface connected or its MTU changed
+  ParameterChanged,
+}
+
+#[cfg_attr(feature = "napi", napi(string_enum))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpFamily {
+  Ipv4,
+  Ipv6,
+}
+
+#[cfg_attr(feature = "napi", napi(object, object_from_js = false))]
+#[derive(Debug, Clone)]
+/// An IPv4 or IPv6 interface reported by `NotifyIpInterfaceChange`, an adapter has one per family.
+pub struct IpInterfaceChangeEvent {
+  pub kind: IpInterfaceChangeKind,
+  /// The alias of the interface, e.g. `Wi-Fi`, `None` once it's removed
+  pub name: Option<String>,
+  pub interface_index: u32,
+  pub family: IpFamily,
+  /// The interface is connected, always `false` for `Deleted`
+  pub is_up: bool,
+}
+
+#[cfg(feature = "napi")]
+type InterfaceChangeHandler = Box<dyn Fn(IpInterfaceChangeEvent) + Send + Sync>;
+
+#[cfg_attr(feature = "napi", napi(object, object_from_js = false))]
+#[derive(Debug, Clone)]
+/// The default route of a family moved to another interface, e.g. from Ethernet to Wi-Fi,
+/// the sockets bound to the previous one must be re-established even if the connectivity didn't change.
+pub struct DefaultRouteChangeEvent {
+  pub family: IpFamily,
+  /// `None` when there was no default route
+  pub previous_interface_index: Option<u32>,
+  pub previous_interface_name: Option<String>,
+  /// `None` when the default route is gone
+  pub interface_index: Option<u32>,
+  pub interface_name: Option<String>,
+}
+
+#[cfg(feature = "napi")]
+type RouteChangeHandler = Box<dyn Fn(DefaultRouteChangeEvent) + Send + Sync>;
+
+#[cfg(feature = "napi")]
+type AirplaneModeHandler = Box<dyn Fn(bool) + Send + Sync>;
+
+#[cfg_attr(feature = "napi", napi(string_enum))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Which networks `getNetworks` lists.
+pub enum NetworkListFilter {
+  /// NLM_ENUM_NETWORK_CONNECTED
+  #[cfg_attr(feature = "napi", napi(value = "connected"))]
+  Connected,
+  /// NLM_ENUM_NETWORK_DISCONNECTED
+  #[cfg_attr(feature = "napi", napi(value = "disconnected"))]
+  Disconnected,
+  /// NLM_ENUM_NETWORK_ALL
+  #[cfg_attr(feature = "napi", napi(value = "all"))]
+  All,
+}
+
+impl From<NetworkListFilter> for NLM_ENUM_NETWORK {
+  fn from(filter: NetworkListFilter) -> Self {
+    match filter {
+      NetworkListFilter::Connected => NLM_ENUM_NETWORK_CONNECTED,
+      NetworkListFilter::Disconnected => NLM_ENUM_NETWORK_DISCONNECTED,
+      NetworkListFilter::All => NLM_ENUM_NETWORK_ALL,
+    }
+  }
+}
+
+#[cfg_attr(feature = "napi", napi(object, object_from_js = false))]
+#[derive(Debug, Clone)]
+/// A network of the network list manager, see `getNetworks`.
+pub struct NetworkListEntry {
+  pub name: String,
+  pub description: String,
+  /// The GUID of the network, e.g. `{D81B5A2E-...}`
+  pub network_id: String,
+  pub category: NetworkCategory,
+  /// The raw `NLM_CONNECTIVITY` bits, `decodeConnectivityFlags` names them
+  pub connectivity: u32,
+  pub is_connected_to_internet: bool,
+}
+
+#[cfg_attr(feature = "napi", napi(object, object_from_js = false))]
+#[derive(Debug, Clone, Copy)]
+/// The cost of the connection used to reach a destination, see `getCostForDestination`.
+pub struct DestinationCost {
+  /// The raw `NLM_CONNECTION_COST` bits, `ConnectionCostFlag` names them
+  pub cost_flags: u32,
+  /// The connection is metered, roaming or over its data limit, the same as `isExpensive`
+  pub is_expensive: bool,
+  /// The data limit of the plan in megabytes, `undefined` without a limit
+  pub data_limit_mb: Option<u32>,
+}
+
+/// Where the change events of an `InternetMonitor` come from, the snapshots have the same shape either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backend {
+  /// The `INetworkListManager` and `INetworkCostManager` connection points
+  Com,
+  /// `Windows.Networking.Connectivity.NetworkInformation`, Windows 10+
+  WinRt,
+}
+
+/// Initializes COM on the `ComThread` of a monitor and balances it with `CoUninitialize` on drop.
+struct ComApartment {
+  /// `false` when the host already initialized the thread as a single-threaded apartment, it owns that initialization
+  initialized: bool,
+}
+
+impl ComApartment {
+  fn initialize() -> native::Result<Self> {
+    // SAFETY: Windows API requires unsafe block
+    let hr = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) };
+    // e.g. the Electron main process or a .NET host, COM is usable and the apartment is theirs to tear down
+    if hr == Foundation::RPC_E_CHANGED_MODE {
+      return Ok(Self { initialized: false });
+    }
+    hr.ok()
+      .map_err(|err| native::Error::new(format!("CoInitializeEx failed: {err}")))?;
+    Ok(Self { initialized: true })
+  }
+}
+
+impl Drop for ComApartment {
+  fn drop(&mut self) {
+    if self.initialized {
+      // SAFETY: paired with the successful CoInitializeEx in `ComApartment::initialize`
+      unsafe { CoUninitialize() };
+    }
+  }
+}
+
+/// The COM objects of a monitor and its event registrations, they are only touched on its `ComThread`.
+///
+/// The sinks are called by the network list service on the threads of the multithreaded apartment,
+/// they only share the `Arc`s with the monitor.
+// `current_network_info` only reads the first snapshot, the monitor of the bindings drives the rest
+#[cfg_attr(not(feature = "napi"), allow(dead_code))]
+struct ComState {
+  network_list_manager: INetworkListManager,
+  network_list_manager_events_connection_point: IConnectionPoint,
+  network_cost_manager_events_connection_point: IConnectionPoint,
+  network_cost_manager: INetworkCostManager,
   network_events_manager: INetworkEvents,
   cost_event_manager: INetworkCostManagerEvents,
   advise_network_list_manager_cookie: u32,
   advise_cost_manager_cookie: u32,
-  network_list_manager: Rc<INetworkListManager>,
-  network_list_manager_events_connection_point: IConnectionPoint,
-  network_cost_manager: Rc<INetworkCostManager>,
-  network_cost_manager_events_connection_point: IConnectionPoint,
-  is_expensive: Arc<AtomicBool>,
-  is_low_data_mode: Arc<AtomicBool>,
-  has_ipv4: Arc<AtomicBool>,
-  has_ipv6: Arc<AtomicBool>,
-  has_dns: Arc<AtomicBool>,
-  status: Arc<AtomicU8>,
+  /// The registration of the `NetworkStatusChanged` handler of the WinRT backend
+  network_status_changed_token: Option<i64>,
+  change_handler: Arc<Mutex<Option<ChangeHandler>>>,
+  error_handler: Arc<Mutex<Option<ErrorHandler>>>,
+  lifecycle_handler: Arc<Mutex<Option<LifecycleHandler>>>,
+  network_info: Arc<Mutex<NetworkInfo>>,
+  excluded_adapters: Arc<ExcludedAdapters>,
+  /// Set when a call failed because the network list service restarted, see `is_service_restarted`
+  backend_lost: Arc<AtomicBool>,
 }
 
-#[napi::module_init]
-fn init() {
-  unsafe {
-    // https://stackoverflow.com/a/2979671
-    CoInitializeEx(None, COINIT_MULTITHREADED)
-      .ok()
-      .expect("CoInitializeEx failed");
-  }
-}
-
-#[napi]
-impl InternetMonitor {
-  #[napi(constructor)]
-  pub fn new() -> Result<Self> {
+#[cfg_attr(not(feature = "napi"), allow(dead_code))]
+impl ComState {
+  /// Create the COM objects and read the first snapshot, into `shared_network_info` when the objects are created again.
+  fn new(
+    change_handler: Arc<Mutex<Option<ChangeHandler>>>,
+    error_handler: Arc<Mutex<Option<ErrorHandler>>>,
+    lifecycle_handler: Arc<Mutex<Option<LifecycleHandler>>>,
+    excluded_adapters: Arc<ExcludedAdapters>,
+    shared_network_info: Option<Arc<Mutex<NetworkInfo>>>,
+    backend_lost: Arc<AtomicBool>,
+  ) -> native::Result<Self> {
     // SAFETY: Windows API requires unsafe block
     unsafe {
-      let network_list_manager: Rc<INetworkListManager> = Rc::new(
+      let network_list_manager: INetworkListManager =
         CoCreateInstance(&NetworkListManager, None, CLSCTX_ALL).map_err(|_| {
-          Error::new(
-            Status::GenericFailure,
-            "CoCreateInstance::CoCreateInstance INetworkListManager failed",
-          )
-        })?,
-      );
+          native::Error::new("CoCreateInstance::CoCreateInstance INetworkListManager failed")
+        })?;
 
-      let network_cost_manager: Rc<INetworkCostManager> = Rc::new(
+      let network_cost_manager: INetworkCostManager =
         CoCreateInstance(&NetworkListManager, None, CLSCTX_ALL).map_err(|_| {
-          Error::new(
-            Status::GenericFailure,
-            "CoCreateInstance::CoCreateInstance INetworkCostManager failed",
-          )
-        })?,
-      );
+          native::Error::new("CoCreateInstance::CoCreateInstance INetworkCostManager failed")
+        })?;
 
       let mut network_list_manager_connection_point_container: MaybeUninit<
         IConnectionPointContainer,
@@ -83,12 +331,7 @@ impl InternetMonitor {
             .cast(),
         )
         .ok()
-        .map_err(|_| {
-          Error::new(
-            Status::GenericFailure,
-            "INetworkListManager::QueryInterface failed",
-          )
-        })?;
+        .map_err(|_| native::Error::new("INetworkListManager::QueryInterface failed"))?;
 
       let mut network_cost_manager_connection_point_container: MaybeUninit<
         IConnectionPointContainer,
@@ -101,12 +344,7 @@ impl InternetMonitor {
             .cast(),
         )
         .ok()
-        .map_err(|_| {
-          Error::new(
-            Status::GenericFailure,
-            "INetworkCostManager::QueryInterface failed",
-          )
-        })?;
+        .map_err(|_| native::Error::new("INetworkCostManager::QueryInterface failed"))?;
 
       // SAFETY: network_list_manager_connection_point_container is initialized when query is successful
       let network_list_manager_connection_point_container =
@@ -116,8 +354,7 @@ impl InternetMonitor {
         network_list_manager_connection_point_container
           .FindConnectionPoint(&INetworkEvents::IID)
           .map_err(|_| {
-            Error::new(
-              Status::GenericFailure,
+            native::Error::new(
               "FindConnectionPoint::FindConnectionPoint(INetworkListManagerEvents) failed",
             )
           })?;
@@ -130,416 +367,1995 @@ impl InternetMonitor {
         network_cost_manager_connection_point_container
           .FindConnectionPoint(&INetworkCostManagerEvents::IID)
           .map_err(|_| {
-            Error::new(
-              Status::GenericFailure,
+            native::Error::new(
               "FindConnectionPoint::FindConnectionPoint(INetworkCostManagerEvents) failed",
             )
           })?;
 
       let mut network_info = NetworkInfo {
-        has_ipv4: false,
-        has_ipv6: false,
-        has_dns: false,
-        is_low_data_mode: false,
-        is_expensive: false,
         status: NetworkStatus::Invalid,
+        // the radios are enumerated only here, `RadioWatch` keeps it up to date afterwards
+        airplane_mode: airplane_mode(),
+        ..Default::default()
       };
 
-      let is_expensive = Arc::new(AtomicBool::new(false));
-      let is_low_data_mode = Arc::new(AtomicBool::new(network_info.is_low_data_mode));
-      let status = Arc::new(AtomicU8::new(network_info.status as u8));
       let mut get_network_info = || {
-        {
-          let connectivity = network_list_manager.GetConnectivity()?;
-
-          let connections = network_list_manager.GetNetworkConnections()?;
-          let mut all_connections = [None];
-          connections.Next(&mut all_connections, None)?;
-          if let Some(Some(connection)) = all_connections.first() {
-            let mut network_connection_cost: MaybeUninit<INetworkConnectionCost> =
-              MaybeUninit::uninit();
-            connection
-              .query(
-                &INetworkConnectionCost::IID,
-                network_connection_cost.as_mut_ptr().cast(),
-              )
-              .ok()?;
-            let network_connection_cost = network_connection_cost.assume_init();
-            let cost = network_connection_cost.GetCost()?;
-            let mut data_plan = NLM_DATAPLAN_STATUS::default();
-            network_connection_cost.GetDataPlanStatus(&mut data_plan)?;
-            is_expensive.store(data_plan.DataLimitInMegabytes != u32::MAX, Ordering::SeqCst);
-            is_low_data_mode.store(
-              cost > NlmConnectionCost::UNRESTRICTED.bits(),
-              Ordering::SeqCst,
-            );
-            network_info = get_network_info(
-              connectivity,
-              &is_expensive,
-              &is_low_data_mode,
-              &status,
-              &network_list_manager,
-            )?;
-          }
-        }
+        let connectivity = network_list_manager.GetConnectivity()?;
+
+        // without any connection, e.g. every adapter is disabled, the cost stays unmetered until `CostChanged`
+        read_connection_cost(
+          &mut network_info,
+          &network_list_manager,
+          &excluded_adapters,
+          &error_handler,
+        )?;
+        // the connectivity doesn't depend on the connections, it's always read
+        network_info = get_network_info(
+          connectivity,
+          &network_info,
+          &network_list_manager,
+          &AdapterSummary::read(&excluded_adapters)?,
+        )?;
         Ok::<(), windows_core::Error>(())
       };
 
-      get_network_info().map_err(|err| Error::new(Status::GenericFailure, format!("{err}")))?;
+      get_network_info().map_err(|err| native::Error::new(format!("{err}")))?;
 
-      let has_ipv4 = Arc::new(AtomicBool::new(network_info.has_ipv4));
-      let has_ipv6 = Arc::new(AtomicBool::new(network_info.has_ipv6));
-      let has_dns = Arc::new(AtomicBool::new(network_info.has_dns));
+      let network_info = match shared_network_info {
+        Some(shared_network_info) => {
+          *shared_network_info.lock().unwrap() = network_info;
+          shared_network_info
+        }
+        None => Arc::new(Mutex::new(network_info)),
+      };
 
       Ok(Self {
         network_events_manager: NetworkEventsHandler {
-          inner: Box::new(move |_status| {}),
+          inner: change_handler.clone(),
+          error_handler: error_handler.clone(),
+          lifecycle_handler: lifecycle_handler.clone(),
           network_list_manager: network_list_manager.clone(),
-          is_expensive: is_expensive.clone(),
-          is_low_data_mode: is_low_data_mode.clone(),
-          status: status.clone(),
+          network_info: network_info.clone(),
+          excluded_adapters: excluded_adapters.clone(),
+          backend_lost: backend_lost.clone(),
         }
         .into(),
         cost_event_manager: NetworkCostEventsHandler {
-          inner: Box::new(move |_status| {}),
+          inner: change_handler.clone(),
+          error_handler: error_handler.clone(),
           network_cost_manager: network_cost_manager.clone(),
-          is_expensive: is_expensive.clone(),
-          is_low_data_mode: is_low_data_mode.clone(),
-          has_ipv4: has_ipv4.clone(),
-          has_ipv6: has_ipv6.clone(),
-          has_dns: has_dns.clone(),
-          status: status.clone(),
+          network_info: network_info.clone(),
         }
         .into(),
         advise_network_list_manager_cookie: 0,
         advise_cost_manager_cookie: 0,
+        network_status_changed_token: None,
         network_list_manager,
         network_list_manager_events_connection_point,
-        network_cost_manager,
         network_cost_manager_events_connection_point,
-        is_expensive,
-        is_low_data_mode,
-        has_ipv4,
-        has_ipv6,
-        has_dns,
-        status,
+        network_cost_manager,
+        change_handler,
+        error_handler,
+        lifecycle_handler,
+        network_info,
+        excluded_adapters,
+        backend_lost,
       })
     }
   }
 
-  #[napi]
-  pub fn current(&self) -> NetworkInfo {
-    NetworkInfo {
-      is_expensive: self.is_expensive.load(Ordering::SeqCst),
-      is_low_data_mode: self.is_low_data_mode.load(Ordering::SeqCst),
-      has_ipv4: self.has_ipv4.load(Ordering::SeqCst),
-      has_ipv6: self.has_ipv6.load(Ordering::SeqCst),
-      has_dns: self.has_dns.load(Ordering::SeqCst),
-      status: match self.status.load(Ordering::SeqCst) {
-        0 => NetworkStatus::Invalid,
-        1 => NetworkStatus::Satisfied,
-        2 => NetworkStatus::Unsatisfied,
-        3 => NetworkStatus::Satisfiable,
-        4 => NetworkStatus::Unknown,
-        _ => NetworkStatus::Invalid,
-      },
+  /// Create the COM objects again after the network list service restarted, e.g. while a corporate machine is patched.
+  ///
+  /// The old proxies and the advised sinks died with the service. The new sinks share the callbacks and the snapshot
+  /// of the monitor and are advised if the old ones were, then the fresh snapshot is reported.
+  fn restart(&mut self, backend: Backend) -> native::Result<()> {
+    let was_advised = self.advise_network_list_manager_cookie != 0;
+    let state = ComState::new(
+      self.change_handler.clone(),
+      self.error_handler.clone(),
+      self.lifecycle_handler.clone(),
+      self.excluded_adapters.clone(),
+      Some(self.network_info.clone()),
+      self.backend_lost.clone(),
+    )?;
+    // the `NetworkStatusChanged` registration of the WinRT backend doesn't go through the service, it's kept
+    *self = ComState {
+      network_status_changed_token: self.network_status_changed_token.take(),
+      ..state
+    };
+    if was_advised {
+      self.subscribe(Backend::Com)?;
+    }
+    self.backend_lost.store(false, Ordering::Release);
+    let info = self
+      .read_network_info(backend)
+      .map_err(|err| native::Error::new(format!("{err}")))?;
+    *self.network_info.lock().unwrap() = info.clone();
+    if let Some(handler) = self.error_handler.lock().unwrap().as_ref() {
+      handler(
+        "backendRestarted: the network list service restarted, the monitor subscribed again"
+          .to_owned(),
+      );
     }
+    emit(&self.change_handler, info);
+    Ok(())
   }
 
-  #[napi]
-  /// Start the path monitor, it will keep the Node.js alive unless you call stop on it.
-  pub fn start(&mut self, on_update: Function<NetworkInfo, ()>) -> Result<()> {
-    let change_handler = Arc::new(
-      on_update
-        .build_threadsafe_function()
-        .callee_handled::<false>()
-        .weak::<false>()
-        .build_callback(ctx_to_path)?,
-    );
-    self.start_inner::<false>(change_handler)
+  /// Run `job`, and once more on new COM objects if the network list service restarted under it.
+  ///
+  /// The original error is returned when the service is not back yet, the next call tries again.
+  fn recovering<T>(
+    &mut self,
+    backend: Backend,
+    job: impl Fn(&ComState) -> windows_core::Result<T>,
+  ) -> windows_core::Result<T> {
+    match job(self) {
+      Err(err) if is_service_restarted(&err) => {
+        self.backend_lost.store(true, Ordering::Release);
+        if self.restart(backend).is_err() {
+          return Err(err);
+        }
+        job(self)
+      }
+      result => result,
+    }
   }
 
-  #[napi]
-  /// Start the path monitor with weak reference, it will not keep the Node.js alive.
-  pub fn start_weak(&mut self, on_update: Function<NetworkInfo, ()>) -> Result<()> {
-    let change_handler = Arc::new(
-      on_update
-        .build_threadsafe_function()
-        .callee_handled::<false>()
-        .weak::<true>()
-        .build_callback(ctx_to_path)?,
-    );
-    self.start_inner::<true>(change_handler)
+  /// Evaluate every field again, the status and the cost come from the Internet connection profile on the WinRT backend.
+  fn read_network_info(&self, backend: Backend) -> windows_core::Result<NetworkInfo> {
+    let current = self.network_info.lock().unwrap().clone();
+    if backend == Backend::WinRt {
+      return winrt_network_info(
+        &current,
+        &self.network_list_manager,
+        &self.excluded_adapters,
+      );
+    }
+    let connectivity = unsafe { self.network_list_manager.GetConnectivity()? };
+    let mut info = get_network_info(
+      connectivity,
+      &current,
+      &self.network_list_manager,
+      &AdapterSummary::read(&self.excluded_adapters)?,
+    )?;
+    read_connection_cost(
+      &mut info,
+      &self.network_list_manager,
+      &self.excluded_adapters,
+      &self.error_handler,
+    )?;
+    Ok(info)
   }
 
-  fn start_inner<const WEAK: bool>(
-    &mut self,
-    change_handler: Arc<ThreadsafeFunction<NetworkInfo, (), NetworkInfo, false, { WEAK }>>,
-  ) -> Result<()> {
-    let change_handler_for_cost = change_handler.clone();
+  fn destination_cost(&self, destination: IpAddr) -> windows_core::Result<DestinationCost> {
+    let sockaddr = destination_sockaddr(destination);
+    let mut cost = 0;
+    unsafe { self.network_cost_manager.GetCost(&mut cost, &sockaddr)? };
+    let data_plan = data_plan_status(|data_plan| unsafe {
+      self
+        .network_cost_manager
+        .GetDataPlanStatus(data_plan, &sockaddr)
+    });
+    Ok(DestinationCost {
+      cost_flags: cost,
+      is_expensive: MeteredStatus::from_cost_flags(cost).is_expensive,
+      data_limit_mb: data_plan
+        .ok()
+        .and_then(|data_plan| data_limit_mb(&data_plan)),
+    })
+  }
+
+  fn subscribe(&mut self, backend: Backend) -> native::Result<()> {
+    if backend == Backend::WinRt {
+      if self.network_status_changed_token.is_none() {
+        let change_handler = self.change_handler.clone();
+        let error_handler = self.error_handler.clone();
+        let network_info = self.network_info.clone();
+        let excluded_adapters = self.excluded_adapters.clone();
+        let on_status_changed = NetworkStatusChangedEventHandler::new(move |_| {
+          let result = (|| {
+            // The event is raised on a thread pool thread of the multithreaded apartment,
+            // the network list manager of the monitor can't be moved out of its thread.
+            let network_list_manager: INetworkListManager =
+              unsafe { CoCreateInstance(&NetworkListManager, None, CLSCTX_ALL)? };
+            let current = network_info.lock().unwrap().clone();
+            let info = winrt_network_info(&current, &network_list_manager, &excluded_adapters)?;
+            *network_info.lock().unwrap() = info.clone();
+            emit(&change_handler, info);
+            Ok(())
+          })();
+          report_error(&error_handler, result)
+        });
+        let token =
+          NetworkInformation::NetworkStatusChanged(&on_status_changed).map_err(|err| {
+            native::Error::new(format!(
+              "NetworkInformation::NetworkStatusChanged failed: {err}"
+            ))
+          })?;
+        self.network_status_changed_token = Some(token);
+      }
+      return Ok(());
+    }
+
+    // the sinks read the callback from `change_handler`, starting again only swaps it,
+    // advising them again would leak the first registrations and their callback would keep firing
+    if self.advise_network_list_manager_cookie != 0 && self.advise_cost_manager_cookie != 0 {
+      return Ok(());
+    }
 
     // SAFETY: Windows API requires unsafe block
     unsafe {
-      let network_event: INetworkEvents = NetworkEventsHandler {
-        inner: Box::new(move |status| {
-          change_handler.call(status, ThreadsafeFunctionCallMode::NonBlocking);
-        }),
-        network_list_manager: self.network_list_manager.clone(),
-        is_expensive: self.is_expensive.clone(),
-        is_low_data_mode: self.is_low_data_mode.clone(),
-        status: self.status.clone(),
-      }
-      .into();
-      let cost_event: INetworkCostManagerEvents = NetworkCostEventsHandler {
-        inner: Box::new(move |status| {
-          change_handler_for_cost.call(status, ThreadsafeFunctionCallMode::NonBlocking);
-        }),
-        network_cost_manager: self.network_cost_manager.clone(),
-        is_expensive: self.is_expensive.clone(),
-        is_low_data_mode: self.is_low_data_mode.clone(),
-        has_ipv4: self.has_ipv4.clone(),
-        has_ipv6: self.has_ipv6.clone(),
-        has_dns: self.has_dns.clone(),
-        status: self.status.clone(),
-      }
-      .into();
       let mut cost_event_handler = MaybeUninit::<IUnknown>::uninit();
-      cost_event
+      self
+        .cost_event_manager
         .query(&IUnknown::IID, cost_event_handler.as_mut_ptr().cast())
         .ok()
         .map_err(|_| {
-          Error::new(
-            Status::GenericFailure,
-            "Failed to query IUnknown::IID on INetworkConnectionCostEvents",
-          )
+          native::Error::new("Failed to query IUnknown::IID on INetworkConnectionCostEvents")
         })?;
       let cost_event_handler = cost_event_handler.assume_init();
-      let advise_network_list_manager_cookie = self
-        .network_list_manager_events_connection_point
-        .Advise(&network_event)
-        .map_err(handle_advise_error)?;
-      let advise_cost_manager_cookie = self
-        .network_cost_manager_events_connection_point
-        .Advise(&cost_event_handler)
-        .map_err(handle_advise_error)?;
-      self.network_events_manager = network_event;
-      self.cost_event_manager = cost_event;
-      self.advise_network_list_manager_cookie = advise_network_list_manager_cookie;
-      self.advise_cost_manager_cookie = advise_cost_manager_cookie;
+      advise_pair(
+        &mut self.advise_network_list_manager_cookie,
+        &mut self.advise_cost_manager_cookie,
+        || {
+          self
+            .network_list_manager_events_connection_point
+            .Advise(&self.network_events_manager)
+        },
+        || {
+          self
+            .network_cost_manager_events_connection_point
+            .Advise(&cost_event_handler)
+        },
+        |cookie| {
+          self
+            .network_list_manager_events_connection_point
+            .Unadvise(cookie)
+        },
+      )
     }
-    Ok(())
   }
 
-  #[napi]
-  /// Stop the path monitor.
-  ///
-  /// If you don't call this method and leave the monitor alone, it will be stopped automatically when it is GC.
-  pub fn stop(&mut self) -> Result<()> {
-    // SAFETY: Windows API requires unsafe block
-    unsafe {
-      if self.advise_network_list_manager_cookie != 0 {
-        self
-          .network_list_manager_events_connection_point
-          .Unadvise(self.advise_network_list_manager_cookie)
-          .map_err(|_| {
-            Error::new(
-              Status::GenericFailure,
-              "IConnectionPoint::Unadvise INetworkListManagerEvents failed",
-            )
-          })?;
-      }
-
-      if self.advise_cost_manager_cookie != 0 {
-        self
-          .network_cost_manager_events_connection_point
-          .Unadvise(self.advise_cost_manager_cookie)
-          .map_err(|_| {
-            Error::new(
-              Status::GenericFailure,
-              "IConnectionPoint::Unadvise INetworkListManagerEvents failed",
-            )
-          })?;
-      }
-
-      // unref the ThreadsafeFunction
-      self.network_events_manager = NetworkEventsHandler {
-        inner: Box::new(move |_status| {}),
-        network_list_manager: self.network_list_manager.clone(),
-        is_expensive: self.is_expensive.clone(),
-        is_low_data_mode: self.is_low_data_mode.clone(),
-        status: self.status.clone(),
-      }
-      .into();
-
-      // unref the ThreadsafeFunction
-      self.cost_event_manager = NetworkCostEventsHandler {
-        inner: Box::new(move |_status| {}),
-        network_cost_manager: self.network_cost_manager.clone(),
-        is_expensive: self.is_expensive.clone(),
-        is_low_data_mode: self.is_low_data_mode.clone(),
-        has_ipv4: self.has_ipv4.clone(),
-        has_ipv6: self.has_ipv6.clone(),
-        has_dns: self.has_dns.clone(),
-        status: self.status.clone(),
-      }
-      .into();
+  /// Remove the event registrations, it's a no-op if they are already removed.
+  fn unsubscribe(&mut self) -> native::Result<()> {
+    if let Some(token) = self.network_status_changed_token.take() {
+      NetworkInformation::RemoveNetworkStatusChanged(token).map_err(|err| {
+        native::Error::new(format!(
+          "NetworkInformation::RemoveNetworkStatusChanged failed: {err}"
+        ))
+      })?;
     }
-    Ok(())
+
+    unadvise(
+      &self.network_list_manager_events_connection_point,
+      &mut self.advise_network_list_manager_cookie,
+      "INetworkEvents",
+    )?;
+    unadvise(
+      &self.network_cost_manager_events_connection_point,
+      &mut self.advise_cost_manager_cookie,
+      "INetworkCostManagerEvents",
+    )
   }
 }
 
-#[inline]
-fn ctx_to_path(ctx: ThreadsafeCallContext<NetworkInfo>) -> Result<NetworkInfo> {
-  Ok(ctx.value)
+type ComJob = Box<dyn FnOnce(&mut ComState) + Send>;
+
+/// A thread in the multithreaded apartment owning the COM objects of a monitor, the monitor sends it jobs.
+///
+/// The objects don't depend on the apartment of the thread which created the monitor, e.g. the single-threaded
+/// apartment of an Electron main process whose events would only be delivered through its message pump,
+/// and the monitor can be used from any JavaScript thread, worker threads included.
+#[cfg_attr(not(feature = "napi"), allow(dead_code))]
+struct ComThread {
+  /// Dropped to stop the thread
+  jobs: Option<mpsc::Sender<ComJob>>,
+  thread: Option<JoinHandle<()>>,
+  /// The `backend_lost` flag of the `ComState`, the COM objects are created again on the next `current`
+  backend_lost: Arc<AtomicBool>,
 }
 
-fn handle_advise_error(err: windows_core::Error) -> Error {
-  let message = match err.code() {
-    Foundation::E_POINTER => Cow::Borrowed("The value in pUnkSink or pdwCookie is not valid. For example, either pointer may be NULL. "),
-    System::Ole::CONNECT_E_ADVISELIMIT => {
-      Cow::Borrowed("The connection point has already reached its limit of connections and cannot accept any more.")
-    }
-    System::Ole::CONNECT_E_CANNOTCONNECT => {
-      Cow::Borrowed("The sink does not support the interface required by this connection point.")
+#[cfg_attr(not(feature = "napi"), allow(dead_code))]
+impl ComThread {
+  /// Spawn the thread and wait until it created the COM objects, returns the snapshot the sinks keep up to date.
+  fn spawn(
+    change_handler: Arc<Mutex<Option<ChangeHandler>>>,
+    error_handler: Arc<Mutex<Option<ErrorHandler>>>,
+    lifecycle_handler: Arc<Mutex<Option<LifecycleHandler>>>,
+    excluded_adapters: Arc<ExcludedAdapters>,
+  ) -> native::Result<(Self, Arc<Mutex<NetworkInfo>>)> {
+    let (jobs, pending_jobs) = mpsc::channel::<ComJob>();
+    let (ready, on_ready) = mpsc::sync_channel(1);
+    let backend_lost = Arc::new(AtomicBool::new(false));
+    let state_backend_lost = backend_lost.clone();
+    let thread = std::thread::Builder::new()
+      .name("network-change-com".to_owned())
+      .spawn(move || {
+        let com_apartment = match ComApartment::initialize() {
+          Ok(com_apartment) => com_apartment,
+          Err(err) => {
+            let _ = ready.send(Err(err));
+            return;
+          }
+        };
+        let mut state = match ComState::new(
+          change_handler,
+          error_handler,
+          lifecycle_handler,
+          excluded_adapters,
+          None,
+          state_backend_lost,
+        ) {
+          Ok(state) => state,
+          Err(err) => {
+            let _ = ready.send(Err(err));
+            return;
+          }
+        };
+        let _ = ready.send(Ok(state.network_info.clone()));
+        for job in pending_jobs {
+          job(&mut state);
+        }
+        // the monitor is gone, the connection points keep the sinks alive until they are unadvised
+        let _ = state.unsubscribe();
+        // COM objects are released before the apartment is torn down
+        drop(state);
+        drop(com_apartment);
+      })
+      .map_err(|err| native::Error::new(format!("Failed to spawn the COM thread: {err}")))?;
+    let network_info = on_ready
+      .recv()
+      .map_err(|_| native::Error::new("The COM thread panicked"))??;
+    Ok((
+      Self {
+        jobs: Some(jobs),
+        thread: Some(thread),
+        backend_lost,
+      },
+      network_info,
+    ))
+  }
+
+  /// Run `job` on the thread and wait for its result.
+  fn run<T: Send + 'static, E: From<native::Error> + Send + 'static>(
+    &self,
+    job: impl FnOnce(&mut ComState) -> std::result::Result<T, E> + Send + 'static,
+  ) -> std::result::Result<T, E> {
+    let (sender, receiver) = mpsc::sync_channel(1);
+    self
+      .jobs
+      .as_ref()
+      .and_then(|jobs| {
+        jobs
+          .send(Box::new(move |state: &mut ComState| {
+            let _ = sender.send(job(state));
+          }))
+          .ok()
+      })
+      .ok_or_else(|| native::Error::new("The COM thread is gone"))?;
+    receiver
+      .recv()
+      .map_err(|_| native::Error::new("The COM thread panicked"))?
+  }
+}
+
+impl Drop for ComThread {
+  fn drop(&mut self) {
+    self.jobs.take();
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
     }
-    _ => Cow::Owned(format!("{err}")),
-  };
-  Error::new(
-    Status::GenericFailure,
-    format!("IConnectionPoint::Advise INetworkConnectionCostEvents failed {message}",),
-  )
+  }
 }
 
-#[implement(INetworkEvents)]
-struct NetworkEventsHandler {
-  inner: Box<dyn Fn(NetworkInfo)>,
-  is_expensive: Arc<AtomicBool>,
-  is_low_data_mode: Arc<AtomicBool>,
-  status: Arc<AtomicU8>,
-  network_list_manager: Rc<INetworkListManager>,
+/// A running `ComThread` and the state it shares with the monitor.
+///
+/// Creating it is the blocking part of the initialization, `InternetMonitor::create` does it on the libuv thread pool.
+#[cfg_attr(not(feature = "napi"), allow(dead_code))]
+pub struct ComBackend {
+  com_thread: ComThread,
+  change_handler: Arc<Mutex<Option<ChangeHandler>>>,
+  error_handler: Arc<Mutex<Option<ErrorHandler>>>,
+  lifecycle_handler: Arc<Mutex<Option<LifecycleHandler>>>,
+  network_info: Arc<Mutex<NetworkInfo>>,
+  excluded_adapters: Arc<ExcludedAdapters>,
 }
 
-#[implement(INetworkCostManagerEvents)]
-struct NetworkCostEventsHandler {
-  inner: Box<dyn Fn(NetworkInfo)>,
-  network_cost_manager: Rc<INetworkCostManager>,
-  is_expensive: Arc<AtomicBool>,
-  is_low_data_mode: Arc<AtomicBool>,
-  has_ipv4: Arc<AtomicBool>,
-  has_ipv6: Arc<AtomicBool>,
-  has_dns: Arc<AtomicBool>,
-  status: Arc<AtomicU8>,
+impl ComBackend {
+  fn new(excluded_adapters: Arc<ExcludedAdapters>) -> native::Result<Self> {
+    let change_handler: Arc<Mutex<Option<ChangeHandler>>> = Arc::new(Mutex::new(None));
+    let error_handler: Arc<Mutex<Option<ErrorHandler>>> = Arc::new(Mutex::new(None));
+    let lifecycle_handler: Arc<Mutex<Option<LifecycleHandler>>> = Arc::new(Mutex::new(None));
+    let (com_thread, network_info) = ComThread::spawn(
+      change_handler.clone(),
+      error_handler.clone(),
+      lifecycle_handler.clone(),
+      excluded_adapters.clone(),
+    )?;
+    Ok(Self {
+      com_thread,
+      change_handler,
+      error_handler,
+      lifecycle_handler,
+      network_info,
+      excluded_adapters,
+    })
+  }
 }
 
-impl INetworkEvents_Impl for NetworkEventsHandler_Impl {
-  fn NetworkAdded(&self, _networkid: &windows_core::GUID) -> windows_core::Result<()> {
-    Ok(())
+#[cfg(feature = "napi")]
+pub struct CreateInternetMonitor {
+  timeout_ms: Option<u32>,
+  excluded_adapters: Arc<ExcludedAdapters>,
+  history_capacity: Option<u32>,
+  ip_interface_events: bool,
+}
+
+#[cfg(feature = "napi")]
+impl Task for CreateInternetMonitor {
+  type Output = ComBackend;
+  type JsValue = InternetMonitor;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let excluded_adapters = self.excluded_adapters.clone();
+    // an abandoned backend stops its COM thread once it's ready
+    crate::init_with_timeout(self.timeout_ms, || Ok(ComBackend::new(excluded_adapters)?))
   }
 
-  fn NetworkDeleted(&self, _networkid: &windows_core::GUID) -> windows_core::Result<()> {
-    Ok(())
+  fn resolve(&mut self, env: Env, backend: Self::Output) -> Result<Self::JsValue> {
+    let mut monitor = InternetMonitor::from_backend(env, backend)?;
+    monitor.history = History::new(self.history_capacity);
+    monitor.ip_interface_events = self.ip_interface_events;
+    Ok(monitor)
   }
 
-  fn NetworkConnectivityChanged(
-    &self,
-    _: &windows_core::GUID,
-    new_connectivity: NLM_CONNECTIVITY,
-  ) -> windows_core::Result<()> {
-    (self.inner)(get_network_info(
-      new_connectivity,
-      &self.is_expensive,
-      &self.is_low_data_mode,
-      &self.status,
-      &self.network_list_manager,
-    )?);
+  fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+    crate::reject_create(&env, err)
+  }
+}
 
-    Ok(())
+#[cfg(feature = "napi")]
+#[napi]
+impl InternetMonitor {
+  #[napi(constructor)]
+  /// `timeoutMs` is the only option ignored by the constructor, it doesn't time out.
+  pub fn new(env: Env, options: Option<CreateOptions>) -> Result<Self> {
+    let options = options.unwrap_or_default();
+    let excluded_adapters = ExcludedAdapters::new(options.exclude_adapters);
+    let backend = ComBackend::new(Arc::new(excluded_adapters))?;
+    let mut monitor = Self::from_backend(env, backend)?;
+    monitor.history = History::new(options.history_capacity);
+    monitor.ip_interface_events = options.ip_interface_events.unwrap_or(false);
+    Ok(monitor)
   }
 
-  fn NetworkPropertyChanged(
-    &self,
-    _networkid: &windows_core::GUID,
-    _flags: NLM_NETWORK_PROPERTY_CHANGE,
-  ) -> windows_core::Result<()> {
-    Ok(())
+  #[napi(factory)]
+  /// Create an InternetMonitor backed by the WinRT `NetworkInformation` API, Windows 10+ only.
+  ///
+  /// The COM network list manager of `new` sometimes reports a stale connectivity after sleep/resume,
+  /// this backend takes the status and the cost from the Internet connection profile instead and
+  /// refreshes them on `NetworkStatusChanged`. The rest of the `NetworkInfo` is read the same way.
+  pub fn new_winrt(env: Env) -> Result<Self> {
+    let mut monitor = Self::new(env, None)?;
+    monitor.backend = Backend::WinRt;
+    let network_info = monitor.com_thread.run(|state| {
+      state
+        .recovering(Backend::WinRt, |state| {
+          state.read_network_info(Backend::WinRt)
+        })
+        .map_err(|err| Error::new(Status::GenericFailure, format!("{err}")))
+    })?;
+    *monitor.network_info.lock().unwrap() = network_info;
+    Ok(monitor)
   }
-}
 
-bitflags! {
-  #[derive(Debug)]
-  pub struct NlmConnectionCost: u32 {
-    const UNKNOWN = 0;
-    const UNRESTRICTED = 0x1;
-    const FIXED = 0x2;
-    const VARIABLE = 0x4;
-    const OVERDATALIMIT = 0x10000;
-    const CONGESTED = 0x20000;
-    const ROAMING = 0x40000;
-    const APPROACHINGDATALIMIT = 0x80000;
+  #[napi(factory)]
+  /// Create an InternetMonitor which only reports the changes while `interfaceType` carries the traffic,
+  /// and the one moving the traffic away from it, e.g. to only follow the Wi-Fi.
+  ///
+  /// The same as `NWPathMonitor.newWithType` on macOS. `current` and `history` still describe every interface.
+  pub fn new_with_type(env: Env, interface_type: NWInterfaceType) -> Result<Self> {
+    let mut monitor = Self::new(env, None)?;
+    monitor.interface_type = Some(interface_type);
+    Ok(monitor)
   }
-}
 
-impl INetworkCostManagerEvents_Impl for NetworkCostEventsHandler_Impl {
-  fn CostChanged(&self, newcost: u32, _pdestaddr: *const NLM_SOCKADDR) -> windows_core::Result<()> {
-    let is_low_data_mode = newcost > NlmConnectionCost::UNRESTRICTED.bits();
-    self
-      .is_low_data_mode
-      .store(is_low_data_mode, Ordering::SeqCst);
-    (self.inner)(NetworkInfo {
-      is_expensive: self.is_expensive.load(Ordering::SeqCst),
-      is_low_data_mode,
-      has_ipv4: self.has_ipv4.load(Ordering::SeqCst),
-      has_ipv6: self.has_ipv6.load(Ordering::SeqCst),
-      has_dns: self.has_dns.load(Ordering::SeqCst),
-      status: match self.status.load(Ordering::SeqCst) {
-        0 => NetworkStatus::Invalid,
-        1 => NetworkStatus::Satisfied,
-        2 => NetworkStatus::Unsatisfied,
-        3 => NetworkStatus::Satisfiable,
-        4 => NetworkStatus::Unknown,
-        _ => return Err(windows_core::Error::empty()),
-      },
-    });
-    Ok(())
+  #[napi(factory)]
+  /// Create an InternetMonitor whose `start` uses a weak reference, it will not keep the Node.js alive.
+  ///
+  /// Use `startStrong` to opt out for a single listener.
+  pub fn new_weak_by_default(env: Env) -> Result<Self> {
+    let mut monitor = Self::new(env, None)?;
+    monitor.weak_by_default = true;
+    Ok(monitor)
   }
 
-  fn DataPlanStatusChanged(&self, pdestaddr: *const NLM_SOCKADDR) -> windows_core::Result<()> {
-    let mut data_plan_status = NLM_DATAPLAN_STATUS::default();
-    unsafe {
-      self
-        .network_cost_manager
-        .GetDataPlanStatus(&mut data_plan_status, pdestaddr)?
-    };
-    let is_unlimited = data_plan_status.DataLimitInMegabytes == u32::MAX;
-    if is_unlimited {
-      self.is_expensive.store(false, Ordering::SeqCst);
-      self.is_low_data_mode.store(false, Ordering::SeqCst);
-    }
-    self.is_expensive.store(!is_unlimited, Ordering::SeqCst);
-    (self.inner)(NetworkInfo {
-      is_expensive: !is_unlimited,
-      is_low_data_mode: self.is_low_data_mode.load(Ordering::SeqCst),
-      has_ipv4: self.has_ipv4.load(Ordering::SeqCst),
-      has_ipv6: self.has_ipv6.load(Ordering::SeqCst),
-      has_dns: self.has_dns.load(Ordering::SeqCst),
-      status: match self.status.load(Ordering::SeqCst) {
-        0 => NetworkStatus::Invalid,
-        1 => NetworkStatus::Satisfied,
-        2 => NetworkStatus::Unsatisfied,
-        3 => NetworkStatus::Satisfiable,
-        4 => NetworkStatus::Unknown,
-        _ => NetworkStatus::Invalid,
-      },
-    });
-    Ok(())
+  #[napi(ts_return_type = "Promise<InternetMonitor>")]
+  /// Create the InternetMonitor on the libuv thread pool, so creating the COM objects and walking the adapters won't block the JavaScript thread.
+  pub fn create(options: Option<CreateOptions>) -> Result<AsyncTask<CreateInternetMonitor>> {
+    let options = options.unwrap_or_default();
+    Ok(AsyncTask::new(CreateInternetMonitor {
+      timeout_ms: options.timeout_ms,
+      excluded_adapters: Arc::new(ExcludedAdapters::new(options.exclude_adapters)),
+      history_capacity: options.history_capacity,
+      ip_interface_events: options.ip_interface_events.unwrap_or(false),
+    }))
   }
-}
 
-fn get_available_connections<
-  F: FnMut(
+  fn from_backend(env: Env, backend: ComBackend) -> Result<Self> {
+    let ComBackend {
+      com_thread,
+      change_handler,
+      error_handler,
+      lifecycle_handler,
+      network_info,
+      excluded_adapters,
+    } = backend;
+    let interface_change_handler: Arc<Mutex<Option<InterfaceChangeHandler>>> = Default::default();
+    let route_change_handler: Arc<Mutex<Option<RouteChangeHandler>>> = Default::default();
+    let airplane_mode_handler: Arc<Mutex<Option<AirplaneModeHandler>>> = Default::default();
+    // The environment (Electron renderer, worker thread) may be torn down before the monitor is dropped,
+    // its threadsafe functions must not be reachable from the COM sinks after that.
+    let cleanup_hook = EnvCleanupHook::new(&env, {
+      let change_handler = change_handler.clone();
+      let error_handler = error_handler.clone();
+      let lifecycle_handler = lifecycle_handler.clone();
+      let interface_change_handler = interface_change_handler.clone();
+      let route_change_handler = route_change_handler.clone();
+      let airplane_mode_handler = airplane_mode_handler.clone();
+      move || {
+        change_handler.lock().unwrap().take();
+        error_handler.lock().unwrap().take();
+        lifecycle_handler.lock().unwrap().take();
+        interface_change_handler.lock().unwrap().take();
+        route_change_handler.lock().unwrap().take();
+        airplane_mode_handler.lock().unwrap().take();
+      }
+    })?;
+
+    Ok(Self {
+      cleanup_hook: Some(cleanup_hook),
+      change_handler,
+      error_handler,
+      lifecycle_handler,
+      interface_change_handler,
+      route_change_handler,
+      airplane_mode_handler,
+      ip_interface_events: false,
+      ip_interface_notification: None,
+      address_notification: None,
+      route_notification: None,
+      radio_watch: None,
+      excluded_adapters,
+      emitter: Emitter::default(),
+      history: History::default(),
+      ready: Ready::default(),
+      interface_type: None,
+      network_info,
+      weak_by_default: false,
+      is_disposed: AtomicBool::new(false),
+      throughput_sampler: None,
+      backend: Backend::Com,
+      com_thread,
+    })
+  }
+
+  #[napi]
+  /// The last snapshots delivered to the callback, oldest first, `historyCapacity` of them at most.
+  ///
+  /// They are recorded while the monitor is started, whichever callback it's started with.
+  pub fn history(&self) -> Result<Vec<NetworkHistoryEntry>> {
+    self.check_open()?;
+    Ok(self.history.entries())
+  }
+
+  #[napi]
+  pub fn current(&self) -> Result<NetworkInfo> {
+    self.check_open()?;
+    // the snapshot is stale once the network list service restarted, the sinks don't receive its events anymore
+    if self.com_thread.backend_lost.load(Ordering::Acquire) {
+      let backend = self.backend;
+      if let Err(err) = self.com_thread.run(move |state| state.restart(backend)) {
+        // the service is not back yet, the last snapshot is returned and the next call tries again
+        if let Some(handler) = self.error_handler.lock().unwrap().as_ref() {
+          handler(err.message().to_owned());
+        }
+      }
+    }
+    Ok(self.network_info.lock().unwrap().clone())
+  }
+
+  #[napi]
+  /// Evaluate every field again instead of waiting for the next event, and return the new snapshot,
+  /// e.g. after a VPN was connected by another library and the events are late.
+  ///
+  /// The callback passed to `start` is not called.
+  pub fn refresh(&self) -> Result<NetworkInfo> {
+    self.check_open()?;
+    let backend = self.backend;
+    let info = self.com_thread.run(move |state| {
+      state
+        .recovering(backend, |state| state.read_network_info(backend))
+        .map_err(|err| Error::new(Status::GenericFailure, format!("{err}")))
+    })?;
+    *self.network_info.lock().unwrap() = info.clone();
+    Ok(info)
+  }
+
+  #[napi]
+  /// The state of every adapter, `current` only describes the primary one.
+  pub fn current_all(&self) -> Result<Vec<InterfaceNetworkInfo>> {
+    self.check_open()?;
+    let backend = self.backend;
+    self.com_thread.run(move |state| {
+      state
+        .recovering(backend, |state| {
+          current_all(&state.network_list_manager, &state.excluded_adapters)
+        })
+        .map_err(|err| Error::new(Status::GenericFailure, format!("{err}")))
+    })
+  }
+
+  #[napi]
+  /// The category of the connected network, Windows is the only platform which has one,
+  /// the other platforms always return `Unknown`.
+  pub fn network_category(&self) -> Result<NetworkCategory> {
+    self.check_open()?;
+    let network_category = self.network_info.lock().unwrap().network_category;
+    Ok(network_category.unwrap_or(NetworkCategory::Unknown))
+  }
+
+  #[napi]
+  /// Every network Windows remembers, connected or not, e.g. for a "known networks" UI.
+  ///
+  /// Not available on macOS, Network.framework only describes the current path.
+  pub fn list_networks(&self) -> Result<Vec<KnownNetwork>> {
+    self.check_open()?;
+    let backend = self.backend;
+    self.com_thread.run(move |state| {
+      state
+        .recovering(backend, |state| list_networks(&state.network_list_manager))
+        .map_err(|err| {
+          Error::new(
+            Status::GenericFailure,
+            format!("INetworkListManager::GetNetworks failed: {err}"),
+          )
+        })
+    })
+  }
+
+  #[napi]
+  /// The networks of the network list manager with their GUID and raw connectivity, e.g. for a diagnostics panel.
+  ///
+  /// `filter` is `connected` by default.
+  pub fn get_networks(&self, filter: Option<NetworkListFilter>) -> Result<Vec<NetworkListEntry>> {
+    self.check_open()?;
+    let filter = filter.unwrap_or(NetworkListFilter::Connected);
+    let backend = self.backend;
+    self.com_thread.run(move |state| {
+      state
+        .recovering(backend, |state| {
+          get_networks(&state.network_list_manager, filter)
+        })
+        .map_err(|err| {
+          Error::new(
+            Status::GenericFailure,
+            format!("INetworkListManager::GetNetworks failed: {err}"),
+          )
+        })
+    })
+  }
+
+  #[napi]
+  /// The cost of the connection used to reach `address`, e.g. behind a split-tunnel VPN some destinations go over LTE.
+  ///
+  /// `address` must be an IPv4 or IPv6 literal, resolve the hostnames first.
+  pub fn get_cost_for_destination(&self, address: String) -> Result<DestinationCost> {
+    self.check_open()?;
+    let destination = address.parse::<IpAddr>().map_err(|_| {
+      Error::new(
+        Status::InvalidArg,
+        format!("{address} is not an IPv4 or IPv6 address, resolve the hostname first"),
+      )
+    })?;
+    let backend = self.backend;
+    self.com_thread.run(move |state| {
+      state
+        .recovering(backend, |state| state.destination_cost(destination))
+        .map_err(|err| {
+          Error::new(
+            Status::GenericFailure,
+            format!("INetworkCostManager::GetCost failed: {err}"),
+          )
+        })
+    })
+  }
+
+  #[napi]
+  /// A coarse quality tier of the current connection, see `ConnectionClass` for the thresholds.
+  pub fn connection_class(&self) -> Result<ConnectionClass> {
+    self.check_open()?;
+    Ok(ConnectionClass::new(&self.network_info.lock().unwrap()))
+  }
+
+  #[napi]
+  /// It's safe to do a large download right now: the network is `Satisfied`, not expensive, not in Low Data Mode and not roaming.
+  pub fn can_use_unmetered(&self) -> Result<bool> {
+    self.check_open()?;
+    Ok(self.network_info.lock().unwrap().can_use_unmetered())
+  }
+
+  #[napi]
+  /// Whether the machine runs on battery, from `GetSystemPowerStatus`, to combine with the cost of the network.
+  pub fn power_state(&self) -> Result<Option<PowerState>> {
+    self.check_open()?;
+    let mut status = SYSTEM_POWER_STATUS::default();
+    unsafe { GetSystemPowerStatus(&mut status) }.map_err(|err| {
+      Error::new(
+        Status::GenericFailure,
+        format!("GetSystemPowerStatus failed: {err}"),
+      )
+    })?;
+    // 255 is unknown for both, and the battery flag 128 means there is no battery at all
+    const UNKNOWN: u8 = 255;
+    const NO_SYSTEM_BATTERY: u8 = 128;
+    Ok(Some(PowerState {
+      on_battery: status.ACLineStatus == 0,
+      battery_percent: (status.BatteryFlag & NO_SYSTEM_BATTERY == 0
+        && status.BatteryFlag != UNKNOWN
+        && status.BatteryLifePercent <= 100)
+        .then_some(status.BatteryLifePercent),
+    }))
+  }
+
+  #[napi]
+  /// Serialize the current `NetworkInfo` to JSON with the schema shared by every platform, for logging and telemetry.
+  pub fn snapshot_json(&self) -> Result<String> {
+    self.check_open()?;
+    crate::snapshot_json(&self.network_info.lock().unwrap())
+  }
+
+  #[napi]
+  /// Start the path monitor, it will keep the Node.js alive unless you call stop on it.
+  ///
+  /// Monitors created with `newWeakByDefault` don't keep the Node.js alive, the same as `startWeak`.
+  pub fn start(&mut self, on_update: Function<NetworkInfo, ()>) -> Result<()> {
+    if self.weak_by_default {
+      self.start_weak(on_update)
+    } else {
+      self.start_strong(on_update)
+    }
+  }
+
+  #[napi]
+  /// Start with a strong reference whatever the monitor was created with, it will keep the Node.js alive unless you call stop on it.
+  pub fn start_strong(&mut self, on_update: Function<NetworkInfo, ()>) -> Result<()> {
+    self.check_open()?;
+    let change_handler = Arc::new(
+      on_update
+        .build_threadsafe_function()
+        .callee_handled::<false>()
+        .weak::<false>()
+        .build_callback(ctx_to_path)?,
+    );
+    self.start_inner::<false>(change_handler)
+  }
+
+  #[napi]
+  /// Start the path monitor with weak reference, it will not keep the Node.js alive.
+  pub fn start_weak(&mut self, on_update: Function<NetworkInfo, ()>) -> Result<()> {
+    self.check_open()?;
+    let change_handler = Arc::new(
+      on_update
+        .build_threadsafe_function()
+        .callee_handled::<false>()
+        .weak::<true>()
+        .build_callback(ctx_to_path)?,
+    );
+    self.start_inner::<true>(change_handler)
+  }
+
+  #[napi]
+  /// Emit `RetrySuggested` events with an exponential backoff while the network is down,
+  /// and a single `Reconnected` event once it's back.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn start_with_backoff(
+    &mut self,
+    options: Option<BackoffOptions>,
+    on_event: Function<BackoffEvent, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::backoff::change_handler(options.unwrap_or_default(), on_event, self.weak_by_default)?;
+    // the monitor may already be offline, start counting from now
+    handler(self.current()?);
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onChange` only when `isExpensive` or `isLowDataMode` flips, the connectivity changes are ignored.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_metered_change(&mut self, on_change: Function<MeteredStatus, ()>) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::metered::change_handler(Some(&self.current()?), on_change, self.weak_by_default)?;
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onChange` with the new `canUseUnmetered` when it flips, e.g. to pause the large downloads when the network
+  /// becomes expensive, Low Data Mode is turned on, roaming starts or the connection is lost.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_unmetered_change(&mut self, on_change: Function<bool, ()>) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::unmetered::change_handler(Some(&self.current()?), on_change, self.weak_by_default)?;
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onChange` with the new interface type when the traffic moves to another one, e.g. from Wi-Fi to cellular
+  /// or from Ethernet to Wi-Fi, to reset the connections even though the status stays `Satisfied`.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_primary_interface_change(
+    &mut self,
+    on_change: Function<NWInterfaceType, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::primary::change_handler(Some(&self.current()?), on_change, self.weak_by_default)?;
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onReconnect` with how long the network was offline, in milliseconds, when the status becomes `Satisfied` again,
+  /// e.g. to size the retry windows. Every offline episode starts the timer over.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_reconnect(&mut self, on_reconnect: Function<f64, ()>) -> Result<()> {
+    self.check_open()?;
+    let handler =
+      crate::reconnect::change_handler(Some(&self.current()?), on_reconnect, self.weak_by_default)?;
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onOnline` once the network stayed `Satisfied` for `minStableMs` in a row, any other status in between
+  /// starts the wait over. Unlike the first `Satisfied` update, it's not fired while the connectivity flaps.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on_stable_online(
+    &mut self,
+    min_stable_ms: u32,
+    on_online: Function<NetworkInfo, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler = crate::stable::change_handler(min_stable_ms, on_online, self.weak_by_default)?;
+    // the monitor may already be online, start counting from now
+    handler(self.current()?);
+    self.start_with_handler(handler)
+  }
+
+  #[napi]
+  /// Call `onUpdate` only with the snapshots matching `filter`, a combination of `ChangeFilter` flags,
+  /// the other ones are dropped before they cross into JavaScript.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn start_filtered(
+    &mut self,
+    filter: u32,
+    on_update: Function<NetworkInfo, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler = crate::filter::change_handler(
+      filter,
+      Some(&self.current()?),
+      on_update,
+      self.weak_by_default,
+    )?;
+    self.start_with_handler(handler)
+  }
+
+  #[napi(ts_args_type = "event: MonitorEvent, listener: (arg: any) => void")]
+  /// Add a listener for `event`, like `EventEmitter.on`. The `change`, `online` and `offline` listeners
+  /// receive a `NetworkInfo`, the `metered` ones a `MeteredStatus` and the `error` ones an `Error`.
+  ///
+  /// It replaces the callback passed to `start`.
+  pub fn on(&mut self, event: MonitorEvent, listener: Function<EventPayload, ()>) -> Result<()> {
+    self.check_open()?;
+    self.emitter.on(event, listener, self.weak_by_default)?;
+    self
+      .error_handler
+      .lock()
+      .unwrap()
+      .replace(self.emitter.error_handler());
+    let handler = self.emitter.change_handler(Some(&self.current()?));
+    self.start_with_handler(handler)
+  }
+
+  #[napi(ts_args_type = "event: MonitorEvent, listener: (arg: any) => void")]
+  /// Remove a listener added by `on`, the monitor is stopped once the last one is removed.
+  pub fn off(
+    &mut self,
+    env: Env,
+    event: MonitorEvent,
+    listener: Function<EventPayload, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    if self.emitter.off(&env, event, listener)? && self.emitter.is_empty() {
+      self.unsubscribe()?;
+    }
+    Ok(())
+  }
+
+  #[napi(ts_return_type = "Promise<void>")]
+  /// Resolve once the monitor saw a `Satisfied` status, right away when it already did,
+  /// so it can be awaited before every request cheaply.
+  ///
+  /// It never resets, going offline afterwards doesn't make it wait again, listen to `offline` with `on` for that.
+  /// A monitor which is not started is started without a callback, `stop` rejects the pending promises.
+  pub fn connected(&mut self, env: Env) -> Result<JsObject> {
+    self.check_open()?;
+    let promise = self.ready.wait(&env, Some(&self.current()?))?;
+    if self.ready.is_pending() && self.change_handler.lock().unwrap().is_none() {
+      // the snapshots only resolve the promises
+      self.start_with_handler(Box::new(|_| {}))?;
+    }
+    Ok(promise)
+  }
+
+  #[napi(ts_return_type = "Promise<void>")]
+  /// Alias of `connected`.
+  pub fn ready(&mut self, env: Env) -> Result<JsObject> {
+    self.connected(env)
+  }
+
+  #[napi]
+  /// Call `onEvent` when a network is added to or deleted from the network list, e.g. to notice a new VPN or hotspot
+  /// before the connectivity flips. The events are not fired by the monitors created with `newWinrt`.
+  ///
+  /// It's independent of the callback passed to `start`, `stop` removes both.
+  pub fn on_network_lifecycle(
+    &mut self,
+    on_event: Function<NetworkLifecycleEvent, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    if self.backend == Backend::WinRt {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "onNetworkLifecycle is not supported by the WinRT backend",
+      ));
+    }
+    let handler: LifecycleHandler = crate::callback(on_event, self.weak_by_default)?;
+    self.lifecycle_handler.lock().unwrap().replace(handler);
+    let backend = self.backend;
+    Ok(self.com_thread.run(move |state| state.subscribe(backend))?)
+  }
+
+  #[napi]
+  /// Call `onEvent` as soon as IP Helper reports an IPv4 or IPv6 interface added, removed or changed,
+  /// ahead of the connectivity events which may come seconds later.
+  ///
+  /// The monitor must be created with the `ipInterfaceEvents` option. It's independent of the callback passed to `start`,
+  /// `stop` removes both.
+  pub fn on_interface_change(
+    &mut self,
+    on_event: Function<IpInterfaceChangeEvent, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    if !self.ip_interface_events {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "onInterfaceChange needs the ipInterfaceEvents option",
+      ));
+    }
+    let handler: InterfaceChangeHandler = crate::callback(on_event, self.weak_by_default)?;
+    self
+      .interface_change_handler
+      .lock()
+      .unwrap()
+      .replace(handler);
+    if self.ip_interface_notification.is_none() {
+      use windows::Win32::NetworkManagement::IpHelper::NotifyIpInterfaceChange;
+      use windows::Win32::Networking::WinSock::AF_UNSPEC;
+
+      self.ip_interface_notification = Some(MibNotification::register(
+        self.interface_change_handler.clone(),
+        "NotifyIpInterfaceChange",
+        |context, handle| unsafe {
+          NotifyIpInterfaceChange(
+            AF_UNSPEC,
+            Some(on_ip_interface_change),
+            Some(context),
+            false,
+            handle,
+          )
+        },
+      )?);
+    }
+    Ok(())
+  }
+
+  #[napi]
+  /// Call `onEvent` when the IPv4 or the IPv6 default route moves to another interface, e.g. to re-establish the sockets
+  /// when the traffic switches from Ethernet to Wi-Fi without any connectivity change.
+  ///
+  /// It's independent of the callback passed to `start`, `stop` removes both.
+  pub fn on_default_route_change(
+    &mut self,
+    on_event: Function<DefaultRouteChangeEvent, ()>,
+  ) -> Result<()> {
+    self.check_open()?;
+    let handler: RouteChangeHandler = crate::callback(on_event, self.weak_by_default)?;
+    self.route_change_handler.lock().unwrap().replace(handler);
+    self.watch_routes()
+  }
+
+  /// Register `NotifyRouteChange2` for both families, it's a no-op if it's already registered.
+  fn watch_routes(&mut self) -> Result<()> {
+    use windows::Win32::NetworkManagement::IpHelper::NotifyRouteChange2;
+    use windows::Win32::Networking::WinSock::AF_UNSPEC;
+
+    if self.route_notification.is_some() {
+      return Ok(());
+    }
+    let default_route =
+      |family| default_route_index(family).map(|index| (index, interface_alias_by_index(index)));
+    let watch = RouteWatch {
+      route_change_handler: self.route_change_handler.clone(),
+      change_handler: self.change_handler.clone(),
+      error_handler: self.error_handler.clone(),
+      network_info: self.network_info.clone(),
+      excluded_adapters: self.excluded_adapters.clone(),
+      default_routes: Mutex::new([default_route(IpFamily::Ipv4), default_route(IpFamily::Ipv6)]),
+    };
+    self.route_notification = Some(MibNotification::register(
+      Arc::new(watch),
+      "NotifyRouteChange2",
+      |context, handle| unsafe {
+        NotifyRouteChange2(
+          AF_UNSPEC,
+          Some(on_route_change),
+          Some(context),
+          false,
+          handle,
+        )
+      },
+    )?);
+    Ok(())
+  }
+
+  #[napi]
+  /// Whether airplane mode is on, read from the radios: at least one of them and none is on.
+  ///
+  /// `false` on the machines without radios, e.g. a desktop tower, and when the radios can't be enumerated.
+  pub fn is_airplane_mode_on(&self) -> Result<bool> {
+    self.check_open()?;
+    self.com_thread.run(|_| Ok(airplane_mode()))
+  }
+
+  #[napi]
+  /// Call `onChange` when airplane mode is turned on or off, `airplaneMode` of the snapshot follows it as well.
+  ///
+  /// It's independent of the callback passed to `start`, `stop` removes both.
+  pub fn on_airplane_mode_change(&mut self, on_change: Function<bool, ()>) -> Result<()> {
+    self.check_open()?;
+    let handler: AirplaneModeHandler = crate::callback(on_change, self.weak_by_default)?;
+    self.airplane_mode_handler.lock().unwrap().replace(handler);
+    self.watch_radios()
+  }
+
+  /// Register `StateChanged` on every radio, it's a no-op if it's already registered.
+  fn watch_radios(&mut self) -> Result<()> {
+    if self.radio_watch.is_some() {
+      return Ok(());
+    }
+    let watch = Arc::new(AirplaneModeWatch {
+      airplane_mode_handler: self.airplane_mode_handler.clone(),
+      change_handler: self.change_handler.clone(),
+      network_info: self.network_info.clone(),
+    });
+    self.radio_watch = Some(self.com_thread.run(move |_| RadioWatch::register(watch))?);
+    Ok(())
+  }
+
+  fn start_inner<const WEAK: bool>(
+    &mut self,
+    change_handler: Arc<ThreadsafeFunction<NetworkInfo, (), NetworkInfo, false, { WEAK }>>,
+  ) -> Result<()> {
+    self.start_with_handler(Box::new(move |status| {
+      change_handler.call(status, ThreadsafeFunctionCallMode::NonBlocking);
+    }))
+  }
+
+  fn start_with_handler(&mut self, handler: ChangeHandler) -> Result<()> {
+    let handler = match self.interface_type {
+      Some(interface_type) => {
+        InterfaceTypeFilter::new(interface_type, &self.network_info.lock().unwrap()).watch(handler)
+      }
+      None => handler,
+    };
+    let handler = self.ready.watch(self.history.record(handler));
+    let previous_handler = self.change_handler.lock().unwrap().replace(handler);
+    let backend = self.backend;
+    if let Err(err) = self.com_thread.run(move |state| state.subscribe(backend)) {
+      // a failed start leaves the monitor as it was, the new threadsafe function is released
+      *self.change_handler.lock().unwrap() = previous_handler;
+      return Err(err.into());
+    }
+    if self.address_notification.is_none() {
+      use windows::Win32::NetworkManagement::IpHelper::NotifyUnicastIpAddressChange;
+      use windows::Win32::Networking::WinSock::AF_UNSPEC;
+
+      let watch = AddressWatch {
+        change_handler: self.change_handler.clone(),
+        error_handler: self.error_handler.clone(),
+        network_info: self.network_info.clone(),
+        excluded_adapters: self.excluded_adapters.clone(),
+      };
+      self.address_notification = Some(MibNotification::register(
+        Arc::new(watch),
+        "NotifyUnicastIpAddressChange",
+        |context, handle| unsafe {
+          NotifyUnicastIpAddressChange(
+            AF_UNSPEC,
+            Some(on_unicast_address_change),
+            Some(context),
+            false,
+            handle,
+          )
+        },
+      )?);
+    }
+    self.watch_routes()?;
+    self.watch_radios()
+  }
+
+  #[napi]
+  /// Sample the byte counters of the physical interfaces every `intervalMs` on a separate thread,
+  /// `throughput` returns the rate between the last two samples. Starting it again restarts the sampling.
+  ///
+  /// It measures the total traffic of the machine, not the traffic of the app.
+  /// The sampling is independent of `start` and `stop`, it runs until `stopThroughput` or `close`.
+  pub fn start_throughput(&mut self, interval_ms: u32) -> Result<()> {
+    self.check_open()?;
+    // join the previous sampling thread before spawning another one
+    self.throughput_sampler.take();
+    self.throughput_sampler = Some(ThroughputSampler::start(interval_ms)?);
+    Ok(())
+  }
+
+  #[napi]
+  /// The received and sent bytes per second, `null` until `startThroughput` sampled twice.
+  pub fn throughput(&self) -> Result<Option<Throughput>> {
+    self.check_open()?;
+    Ok(
+      self
+        .throughput_sampler
+        .as_ref()
+        .and_then(ThroughputSampler::throughput),
+    )
+  }
+
+  #[napi]
+  /// Stop the sampling started by `startThroughput`.
+  pub fn stop_throughput(&mut self) -> Result<()> {
+    self.check_open()?;
+    self.throughput_sampler.take();
+    Ok(())
+  }
+
+  #[napi]
+  /// Stop the path monitor.
+  ///
+  /// If you don't call this method and leave the monitor alone, it will be stopped automatically when it is GC.
+  pub fn stop(&mut self) -> Result<()> {
+    self.check_open()?;
+    self.unsubscribe()
+  }
+
+  #[napi]
+  /// Stop the path monitor and release its callback, every method throws once it's closed.
+  ///
+  /// Calling `close` again is a no-op.
+  pub fn close(&mut self) -> Result<()> {
+    if self.is_disposed.swap(true, Ordering::AcqRel) {
+      return Ok(());
+    }
+    self.throughput_sampler.take();
+    self.cleanup_hook.take();
+    self.unsubscribe()
+  }
+
+  fn check_open(&self) -> Result<()> {
+    if self.is_disposed.load(Ordering::Acquire) {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "monitor has been disposed",
+      ));
+    }
+    Ok(())
+  }
+
+  /// Remove the event registrations and release the callback, it's a no-op if they are already removed.
+  fn unsubscribe(&mut self) -> Result<()> {
+    self.com_thread.run(ComState::unsubscribe)?;
+    // no callback runs once the notifications are cancelled
+    self.ip_interface_notification.take();
+    self.address_notification.take();
+    self.route_notification.take();
+    self.radio_watch.take();
+    // unref the ThreadsafeFunction
+    self.change_handler.lock().unwrap().take();
+    self.error_handler.lock().unwrap().take();
+    self.lifecycle_handler.lock().unwrap().take();
+    self.interface_change_handler.lock().unwrap().take();
+    self.route_change_handler.lock().unwrap().take();
+    self.airplane_mode_handler.lock().unwrap().take();
+    self.emitter.clear();
+    self.ready.cancel();
+    Ok(())
+  }
+}
+
+fn emit(handler: &Mutex<Option<ChangeHandler>>, info: NetworkInfo) {
+  if let Some(handler) = handler.lock().unwrap().as_ref() {
+    handler(info);
+  }
+}
+
+/// Forward the failure of an event handler to the `error` listeners, the HRESULT returned to the caller of the sink is dropped.
+fn report_error(
+  handler: &Mutex<Option<ErrorHandler>>,
+  result: windows_core::Result<()>,
+) -> windows_core::Result<()> {
+  if let Err(err) = &result {
+    if let Some(handler) = handler.lock().unwrap().as_ref() {
+      handler(format!("{err}"));
+    }
+  }
+  result
+}
+
+#[cfg(feature = "napi")]
+#[inline]
+fn ctx_to_path(ctx: ThreadsafeCallContext<NetworkInfo>) -> Result<NetworkInfo> {
+  Ok(ctx.value)
+}
+
+#[cfg(feature = "napi")]
+#[napi]
+/// Read the connectivity and the cost once, for CLI tools and health checks which don't need a long-lived monitor.
+///
+/// The network list manager is created and released before returning, without subscribing to its events.
+pub fn get_global_network_info() -> Result<NetworkInfo> {
+  Ok(current_network_info()?)
+}
+
+pub(crate) fn current_network_info() -> native::Result<NetworkInfo> {
+  let backend = ComBackend::new(Arc::new(ExcludedAdapters::new(None)))?;
+  let network_info = backend.network_info.lock().unwrap().clone();
+  Ok(network_info)
+}
+
+#[cfg(feature = "napi")]
+#[napi(object)]
+pub struct AdapterDnsServersOptions {
+  /// List the adapters which are not operationally up as well, `false` by default.
+  pub include_down: Option<bool>,
+}
+
+#[cfg(feature = "napi")]
+#[napi(object, object_from_js = false)]
+#[derive(Debug, Clone)]
+/// The DNS configuration of an adapter, see `getAdapterDnsServers`.
+pub struct AdapterDnsServers {
+  /// The alias of the adapter, e.g. `Wi-Fi`
+  pub adapter: String,
+  /// The DNS servers in the order the resolver tries them, e.g. `192.168.1.1` or `fec0:0:0:ffff::1`
+  pub servers: Vec<String>,
+  /// The connection-specific DNS suffix, e.g. `corp.example.com`, empty without one
+  pub suffix: String,
+}
+
+#[cfg(feature = "napi")]
+#[napi]
+/// The DNS servers and the DNS suffix of every adapter, e.g. to diagnose a split DNS VPN which didn't push its resolvers.
+///
+/// Every adapter is listed, the `excludeAdapters` of the monitors and the built-in list don't apply.
+pub fn get_adapter_dns_servers(
+  options: Option<AdapterDnsServersOptions>,
+) -> Result<Vec<AdapterDnsServers>> {
+  let include_down = options
+    .and_then(|options| options.include_down)
+    .unwrap_or(false);
+  let mut adapters = Vec::new();
+  get_available_connections(&ExcludedAdapters::default(), |adapter| {
+    if !include_down && adapter.OperStatus != IfOperStatusUp {
+      return Ok(true);
+    }
+    adapters.push(AdapterDnsServers {
+      adapter: unsafe { adapter.FriendlyName.to_string() }.unwrap_or_default(),
+      servers: dns_server_addresses(adapter)
+        .iter()
+        .map(ToString::to_string)
+        .collect(),
+      suffix: unsafe { adapter.DnsSuffix.to_string() }.unwrap_or_default(),
+    });
+    Ok(true)
+  })
+  .map_err(|err| {
+    Error::new(
+      Status::GenericFailure,
+      format!("Failed to read the adapters: {err}"),
+    )
+  })?;
+  Ok(adapters)
+}
+
+/// Unadvise the sink of `cookie` and reset it to 0, a zero cookie means the sink is not advised.
+///
+/// `CONNECT_E_NOCONNECTION` means the sink is already gone, e.g. the service restarted, it's treated as a success.
+fn unadvise(
+  connection_point: &IConnectionPoint,
+  cookie: &mut u32,
+  sink: &str,
+) -> native::Result<()> {
+  // SAFETY: Windows API requires unsafe block
+  unadvise_with(cookie, sink, |cookie| unsafe {
+    connection_point.Unadvise(cookie)
+  })
+}
+
+/// `unadvise` with the `IConnectionPoint::Unadvise` call passed in.
+fn unadvise_with(
+  cookie: &mut u32,
+  sink: &str,
+  unadvise: impl FnOnce(u32) -> windows_core::Result<()>,
+) -> native::Result<()> {
+  if *cookie == 0 {
+    return Ok(());
+  }
+  match unadvise(*cookie) {
+    Ok(()) => {}
+    Err(err) if err.code() == System::Ole::CONNECT_E_NOCONNECTION => {}
+    Err(err) => {
+      return Err(native::Error::new(format!(
+        "IConnectionPoint::Unadvise {sink} failed: {err}"
+      )))
+    }
+  }
+  *cookie = 0;
+  Ok(())
+}
+
+/// Advise the `INetworkEvents` sink, then the `INetworkConnectionCostEvents` one.
+///
+/// When the second one fails the first one is unadvised, it would keep delivering the events of a start which failed
+/// and nothing would unadvise it.
+fn advise_pair(
+  network_events_cookie: &mut u32,
+  cost_events_cookie: &mut u32,
+  advise_network_events: impl FnOnce() -> windows_core::Result<u32>,
+  advise_cost_events: impl FnOnce() -> windows_core::Result<u32>,
+  unadvise_network_events: impl FnOnce(u32) -> windows_core::Result<()>,
+) -> native::Result<()> {
+  *network_events_cookie = advise_network_events().map_err(handle_advise_error)?;
+  match advise_cost_events() {
+    Ok(cookie) => {
+      *cost_events_cookie = cookie;
+      Ok(())
+    }
+    Err(err) => {
+      let _ = unadvise_with(
+        network_events_cookie,
+        "INetworkEvents",
+        unadvise_network_events,
+      );
+      Err(handle_advise_error(err))
+    }
+  }
+}
+
+/// The failures of a call on a proxy of the network list service once the service restarted,
+/// the objects of the old process are gone and have to be created again.
+fn is_service_restarted(err: &windows_core::Error) -> bool {
+  let code = err.code();
+  code == HRESULT::from_win32(System::Rpc::RPC_S_SERVER_UNAVAILABLE.0 as u32)
+    || code == HRESULT::from_win32(System::Rpc::RPC_S_CALL_FAILED.0 as u32)
+    || code == Foundation::RPC_E_DISCONNECTED
+    || code == Foundation::RPC_E_SERVER_DIED
+    || code == Foundation::RPC_E_SERVER_DIED_DNE
+    || code == Foundation::CO_E_OBJNOTCONNECTED
+}
+
+fn handle_advise_error(err: windows_core::Error) -> native::Error {
+  let message = match err.code() {
+    Foundation::E_POINTER => Cow::Borrowed("The value in pUnkSink or pdwCookie is not valid. For example, either pointer may be NULL. "),
+    System::Ole::CONNECT_E_ADVISELIMIT => {
+      Cow::Borrowed("The connection point has already reached its limit of connections and cannot accept any more.")
+    }
+    System::Ole::CONNECT_E_CANNOTCONNECT => {
+      Cow::Borrowed("The sink does not support the interface required by this connection point.")
+    }
+    _ => Cow::Owned(format!("{err}")),
+  };
+  native::Error::new(format!(
+    "IConnectionPoint::Advise INetworkConnectionCostEvents failed {message}",
+  ))
+}
+
+#[implement(INetworkEvents)]
+struct NetworkEventsHandler {
+  inner: Arc<Mutex<Option<ChangeHandler>>>,
+  error_handler: Arc<Mutex<Option<ErrorHandler>>>,
+  lifecycle_handler: Arc<Mutex<Option<LifecycleHandler>>>,
+  network_info: Arc<Mutex<NetworkInfo>>,
+  network_list_manager: INetworkListManager,
+  excluded_adapters: Arc<ExcludedAdapters>,
+  backend_lost: Arc<AtomicBool>,
+}
+
+#[implement(INetworkCostManagerEvents)]
+struct NetworkCostEventsHandler {
+  inner: Arc<Mutex<Option<ChangeHandler>>>,
+  error_handler: Arc<Mutex<Option<ErrorHandler>>>,
+  network_cost_manager: INetworkCostManager,
+  network_info: Arc<Mutex<NetworkInfo>>,
+}
+
+impl NetworkEventsHandler {
+  /// Adapters can come and go without flipping the connectivity, e.g. a USB Ethernet dongle or a VPN adapter
+  /// plugged in while Wi-Fi is connected, re-read the connectivity and report the new topology.
+  fn recompute(&self) -> windows_core::Result<()> {
+    let result = unsafe { self.network_list_manager.GetConnectivity() }
+      .and_then(|connectivity| self.update(connectivity));
+    self.report(result)
+  }
+
+  /// Report a failed update, the COM objects are created again on the next `current` if the service restarted.
+  fn report(&self, result: windows_core::Result<()>) -> windows_core::Result<()> {
+    if result.as_ref().is_err_and(is_service_restarted) {
+      self.backend_lost.store(true, Ordering::Release);
+    }
+    report_error(&self.error_handler, result)
+  }
+
+  fn update(&self, connectivity: NLM_CONNECTIVITY) -> windows_core::Result<()> {
+    let info = update_network_info(
+      connectivity,
+      &self.network_info,
+      &self.network_list_manager,
+      &self.excluded_adapters,
+    )?;
+    emit(&self.inner, info);
+    Ok(())
+  }
+
+  fn emit_lifecycle(&self, kind: NetworkLifecycleKind, network_id: &windows_core::GUID) {
+    let lifecycle_handler = self.lifecycle_handler.lock().unwrap();
+    let Some(lifecycle_handler) = lifecycle_handler.as_ref() else {
+      return;
+    };
+    // a deleted network is usually gone from the list already, the event is reported without its name then
+    let network = unsafe { self.network_list_manager.GetNetwork(*network_id) }.ok();
+    lifecycle_handler(NetworkLifecycleEvent {
+      kind,
+      network_id: format!("{{{network_id:?}}}"),
+      name: network
+        .as_ref()
+        .and_then(|network| unsafe { network.GetName() }.ok())
+        .map(|name| name.to_string()),
+      description: network
+        .as_ref()
+        .and_then(|network| unsafe { network.GetDescription() }.ok())
+        .map(|description| description.to_string()),
+    });
+  }
+}
+
+impl INetworkEvents_Impl for NetworkEventsHandler_Impl {
+  fn NetworkAdded(&self, networkid: &windows_core::GUID) -> windows_core::Result<()> {
+    self.emit_lifecycle(NetworkLifecycleKind::Added, networkid);
+    self.recompute()
+  }
+
+  fn NetworkDeleted(&self, networkid: &windows_core::GUID) -> windows_core::Result<()> {
+    self.emit_lifecycle(NetworkLifecycleKind::Deleted, networkid);
+    self.recompute()
+  }
+
+  fn NetworkConnectivityChanged(
+    &self,
+    _: &windows_core::GUID,
+    new_connectivity: NLM_CONNECTIVITY,
+  ) -> windows_core::Result<()> {
+    self.report(self.update(new_connectivity))
+  }
+
+  fn NetworkPropertyChanged(
+    &self,
+    _networkid: &windows_core::GUID,
+    flags: NLM_NETWORK_PROPERTY_CHANGE,
+  ) -> windows_core::Result<()> {
+    // e.g. the user switched the network from public to private, the network was renamed or a captive portal was passed,
+    // the icon and the description are not part of the snapshot
+    let relevant = NLM_NETWORK_PROPERTY_CHANGE_CONNECTION.0
+      | NLM_NETWORK_PROPERTY_CHANGE_CATEGORY_VALUE.0
+      | NLM_NETWORK_PROPERTY_CHANGE_NAME.0;
+    if flags.0 & relevant == 0 {
+      return Ok(());
+    }
+    // The sink runs on a thread of the multithreaded apartment, neither the JavaScript thread nor the `ComThread`,
+    // walking the adapters again doesn't hold up the monitor
+    self.recompute()
+  }
+}
+
+bitflags! {
+  #[derive(Debug)]
+  pub struct NlmConnectionCost: u32 {
+    const UNKNOWN = 0;
+    const UNRESTRICTED = 0x1;
+    const FIXED = 0x2;
+    const VARIABLE = 0x4;
+    const OVERDATALIMIT = 0x10000;
+    const CONGESTED = 0x20000;
+    const ROAMING = 0x40000;
+    const APPROACHINGDATALIMIT = 0x80000;
+  }
+}
+
+impl INetworkCostManagerEvents_Impl for NetworkCostEventsHandler_Impl {
+  fn CostChanged(&self, newcost: u32, _pdestaddr: *const NLM_SOCKADDR) -> windows_core::Result<()> {
+    let info = {
+      let mut network_info = self.network_info.lock().unwrap();
+      let metered_status = MeteredStatus::from_cost_flags(newcost);
+      network_info.is_expensive = metered_status.is_expensive;
+      network_info.is_low_data_mode = metered_status.is_low_data_mode;
+      network_info.is_roaming = newcost & NlmConnectionCost::ROAMING.bits() != 0;
+      // kept so `current` has the latest raw cost, not only the events
+      network_info.cost_flags = newcost;
+      network_info.clone()
+    };
+    emit(&self.inner, info);
+    Ok(())
+  }
+
+  fn DataPlanStatusChanged(&self, pdestaddr: *const NLM_SOCKADDR) -> windows_core::Result<()> {
+    let data_plan_status = data_plan_status(|data_plan| unsafe {
+      self
+        .network_cost_manager
+        .GetDataPlanStatus(data_plan, pdestaddr)
+    });
+    let data_limit_mb = reported_data_limit_mb(&self.error_handler, data_plan_status);
+    // the metered status follows the cost flags, `CostChanged` is raised as well when they change
+    let info = {
+      let mut network_info = self.network_info.lock().unwrap();
+      if network_info.data_limit_mb == data_limit_mb {
+        return Ok(());
+      }
+      network_info.data_limit_mb = data_limit_mb;
+      network_info.clone()
+    };
+    emit(&self.inner, info);
+    Ok(())
+  }
+}
+
+#[cfg(feature = "napi")]
+/// A registration of an IP Helper change notification, cancelled on drop.
+///
+/// The callback receives the `T` leaked into its caller context, it's released once the registration is cancelled.
+struct MibNotification<T> {
+  handle: Foundation::HANDLE,
+  context: *const T,
+}
+
+#[cfg(feature = "napi")]
+// SAFETY: the handle and the context are only used to cancel the registration, from any thread
+unsafe impl<T: Send + Sync> Send for MibNotification<T> {}
+
+#[cfg(feature = "napi")]
+impl<T> MibNotification<T> {
+  /// `notify` calls one of the `Notify*Change` functions with the caller context and the handle to fill.
+  fn register(
+    context: Arc<T>,
+    function: &str,
+    notify: impl FnOnce(*const std::ffi::c_void, &mut Foundation::HANDLE) -> Foundation::WIN32_ERROR,
+  ) -> Result<Self> {
+    let context = Arc::into_raw(context);
+    let mut handle = Foundation::HANDLE::default();
+    let code = notify(context.cast(), &mut handle);
+    if code.0 != 0 {
+      // SAFETY: the callback was not registered, nothing else owns the context
+      drop(unsafe { Arc::from_raw(context) });
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!(
+          "{function} failed: {}",
+          HRESULT::from_win32(code.0).message()
+        ),
+      ));
+    }
+    Ok(Self { handle, context })
+  }
+}
+
+#[cfg(feature = "napi")]
+impl<T> Drop for MibNotification<T> {
+  fn drop(&mut self) {
+    use windows::Win32::NetworkManagement::IpHelper::CancelMibChangeNotify2;
+
+    // it waits for the callbacks in progress, the context is not used anymore once it returns
+    unsafe {
+      let _ = CancelMibChangeNotify2(self.handle);
+      drop(Arc::from_raw(self.context));
+    }
+  }
+}
+
+#[cfg(feature = "napi")]
+/// The caller context of `NotifyUnicastIpAddressChange`.
+struct AddressWatch {
+  change_handler: Arc<Mutex<Option<ChangeHandler>>>,
+  error_handler: Arc<Mutex<Option<ErrorHandler>>>,
+  network_info: Arc<Mutex<NetworkInfo>>,
+  excluded_adapters: Arc<ExcludedAdapters>,
+}
+
+#[cfg(feature = "napi")]
+impl AddressWatch {
+  /// Re-evaluate `hasIpv4`, `hasIpv6` and the connectivity levels from the addresses of the adapters
+  /// and the last connectivity, the callback is only called when they change.
+  fn update(&self) -> windows_core::Result<()> {
+    let adapters = AdapterSummary::read(&self.excluded_adapters)?;
+    let info = {
+      let mut network_info = self.network_info.lock().unwrap();
+      let has_ipv4 = reaches_internet(
+        network_info.connectivity_flags,
+        NLM_CONNECTIVITY_IPV4_INTERNET,
+        adapters.has_routable_ipv4,
+      );
+      let has_ipv6 = reaches_internet(
+        network_info.connectivity_flags,
+        NLM_CONNECTIVITY_IPV6_INTERNET,
+        adapters.has_routable_ipv6,
+      );
+      let ipv4_connectivity = connectivity_level(
+        network_info.connectivity_flags,
+        false,
+        adapters.has_routable_ipv4,
+      );
+      let ipv6_connectivity = connectivity_level(
+        network_info.connectivity_flags,
+        true,
+        adapters.has_routable_ipv6,
+      );
+      if network_info.has_ipv4 == has_ipv4
+        && network_info.has_ipv6 == has_ipv6
+        && network_info.ipv4_connectivity == ipv4_connectivity
+        && network_info.ipv6_connectivity == ipv6_connectivity
+      {
+        return Ok(());
+      }
+      network_info.has_ipv4 = has_ipv4;
+      network_info.has_ipv6 = has_ipv6;
+      network_info.ipv4_connectivity = ipv4_connectivity;
+      network_info.ipv6_connectivity = ipv6_connectivity;
+      network_info.stack = IpStackType::new(has_ipv4, has_ipv6);
+      network_info.clone()
+    };
+    emit(&self.change_handler, info);
+    Ok(())
+  }
+}
+
+#[cfg(feature = "napi")]
+unsafe extern "system" fn on_unicast_address_change(
+  context: *const std::ffi::c_void,
+  row: *const windows::Win32::NetworkManagement::IpHelper::MIB_UNICASTIPADDRESS_ROW,
+  _notification_type: windows::Win32::NetworkManagement::IpHelper::MIB_NOTIFICATION_TYPE,
+) {
+  use windows::Win32::Networking::WinSock::{AF_INET, AF_INET6};
+
+  // the row is null for MibInitialNotification
+  let Some(row) = (unsafe { row.as_ref() }) else {
+    return;
+  };
+  let address: IpAddr = match unsafe { row.Address.si_family } {
+    AF_INET => Ipv4Addr::from(u32::from_be(unsafe {
+      row.Address.Ipv4.sin_addr.S_un.S_addr
+    }))
+    .into(),
+    AF_INET6 => Ipv6Addr::from(unsafe { row.Address.Ipv6.sin6_addr.u.Byte }).into(),
+    _ => return,
+  };
+  // e.g. the APIPA address assigned while DHCP doesn't answer, it can't change the flags
+  if !is_routable(&address) {
+    return;
+  }
+  // SAFETY: the context is alive until the registration is cancelled
+  let watch = unsafe { &*context.cast::<AddressWatch>() };
+  let _ = report_error(&watch.error_handler, watch.update());
+}
+
+#[cfg(feature = "napi")]
+unsafe extern "system" fn on_ip_interface_change(
+  context: *const std::ffi::c_void,
+  row: *const windows::Win32::NetworkManagement::IpHelper::MIB_IPINTERFACE_ROW,
+  notification_type: windows::Win32::NetworkManagement::IpHelper::MIB_NOTIFICATION_TYPE,
+) {
+  use windows::Win32::NetworkManagement::IpHelper::{
+    MibAddInstance, MibDeleteInstance, MibParameterNotification,
+  };
+  use windows::Win32::Networking::WinSock::AF_INET6;
+
+  // the row is null for MibInitialNotification
+  let Some(row) = (unsafe { row.as_ref() }) else {
+    return;
+  };
+  let kind = match notification_type {
+    MibAddInstance => IpInterfaceChangeKind::Added,
+    MibDeleteInstance => IpInterfaceChangeKind::Deleted,
+    MibParameterNotification => IpInterfaceChangeKind::ParameterChanged,
+    _ => return,
+  };
+  // SAFETY: the context is alive until the registration is cancelled
+  let handler = unsafe { &*context.cast::<Mutex<Option<InterfaceChangeHandler>>>() };
+  let handler = handler.lock().unwrap();
+  let Some(handler) = handler.as_ref() else {
+    return;
+  };
+  handler(IpInterfaceChangeEvent {
+    kind,
+    name: interface_alias(&row.InterfaceLuid),
+    interface_index: row.InterfaceIndex,
+    family: if row.Family == AF_INET6 {
+      IpFamily::Ipv6
+    } else {
+      IpFamily::Ipv4
+    },
+    is_up: kind != IpInterfaceChangeKind::Deleted && row.Connected,
+  });
+}
+
+#[cfg(feature = "napi")]
+/// The caller context of `NotifyRouteChange2`.
+struct RouteWatch {
+  route_change_handler: Arc<Mutex<Option<RouteChangeHandler>>>,
+  change_handler: Arc<Mutex<Option<ChangeHandler>>>,
+  error_handler: Arc<Mutex<Option<ErrorHandler>>>,
+  network_info: Arc<Mutex<NetworkInfo>>,
+  excluded_adapters: Arc<ExcludedAdapters>,
+  /// The interface index and name of the IPv4 and the IPv6 default routes,
+  /// the name of the previous interface can't be looked up once it's removed
+  default_routes: Mutex<[Option<(u32, Option<String>)>; 2]>,
+}
+
+#[cfg(feature = "napi")]
+impl RouteWatch {
+  fn update(&self, family: IpFamily) -> windows_core::Result<()> {
+    let default_route =
+      default_route_index(family).map(|index| (index, interface_alias_by_index(index)));
+    let previous = {
+      let mut default_routes = self.default_routes.lock().unwrap();
+      let slot = &mut default_routes[match family {
+        IpFamily::Ipv4 => 0,
+        IpFamily::Ipv6 => 1,
+      }];
+      if slot.as_ref().map(|(index, _)| *index) == default_route.as_ref().map(|(index, _)| *index) {
+        return Ok(());
+      }
+      std::mem::replace(slot, default_route.clone())
+    };
+    if let Some(handler) = self.route_change_handler.lock().unwrap().as_ref() {
+      let (previous_interface_index, previous_interface_name) = previous.unzip();
+      let (interface_index, interface_name) = default_route.unzip();
+      handler(DefaultRouteChangeEvent {
+        family,
+        previous_interface_index,
+        previous_interface_name: previous_interface_name.flatten(),
+        interface_index,
+        interface_name: interface_name.flatten(),
+      });
+    }
+    // the interface of the snapshot follows the IPv4 default route, see `AdapterSummary::primary_adapter`
+    let primary_adapter = AdapterSummary::read(&self.excluded_adapters)?.primary_adapter;
+    let interface_type = primary_adapter.map(|(interface_type, _)| interface_type);
+    let link_speed_mbps = primary_adapter.and_then(|(_, link_speed_mbps)| link_speed_mbps);
+    let info = {
+      let mut network_info = self.network_info.lock().unwrap();
+      if network_info.interface_type == interface_type
+        && network_info.link_speed_mbps == link_speed_mbps
+      {
+        return Ok(());
+      }
+      network_info.interface_type = interface_type;
+      network_info.link_speed_mbps = link_speed_mbps;
+      network_info.uses_wifi = interface_type == Some(NWInterfaceType::Wifi);
+      network_info.uses_cellular = interface_type == Some(NWInterfaceType::Cellular);
+      network_info.uses_wired = interface_type == Some(NWInterfaceType::Wired);
+      network_info.uses_loopback = interface_type == Some(NWInterfaceType::Loopback);
+      network_info.clone()
+    };
+    emit(&self.change_handler, info);
+    Ok(())
+  }
+}
+
+#[cfg(feature = "napi")]
+unsafe extern "system" fn on_route_change(
+  context: *const std::ffi::c_void,
+  row: *const windows::Win32::NetworkManagement::IpHelper::MIB_IPFORWARD_ROW2,
+  _notification_type: windows::Win32::NetworkManagement::IpHelper::MIB_NOTIFICATION_TYPE,
+) {
+  use windows::Win32::Networking::WinSock::{AF_INET, AF_INET6};
+
+  // the row is null for MibInitialNotification
+  let Some(row) = (unsafe { row.as_ref() }) else {
+    return;
+  };
+  // only the default routes, 0.0.0.0/0 and ::/0
+  if row.DestinationPrefix.PrefixLength != 0 {
+    return;
+  }
+  let family = match unsafe { row.DestinationPrefix.Prefix.si_family } {
+    AF_INET => IpFamily::Ipv4,
+    AF_INET6 => IpFamily::Ipv6,
+    _ => return,
+  };
+  // SAFETY: the context is alive until the registration is cancelled
+  let watch = unsafe { &*context.cast::<RouteWatch>() };
+  let _ = report_error(&watch.error_handler, watch.update(family));
+}
+
+#[cfg(feature = "napi")]
+/// Shared by the `StateChanged` handlers of every radio.
+struct AirplaneModeWatch {
+  airplane_mode_handler: Arc<Mutex<Option<AirplaneModeHandler>>>,
+  change_handler: Arc<Mutex<Option<ChangeHandler>>>,
+  network_info: Arc<Mutex<NetworkInfo>>,
+}
+
+#[cfg(feature = "napi")]
+impl AirplaneModeWatch {
+  fn update(&self) {
+    let airplane_mode = airplane_mode();
+    let info = {
+      let mut network_info = self.network_info.lock().unwrap();
+      // every radio raises its own event when airplane mode is toggled
+      if network_info.airplane_mode == airplane_mode {
+        return;
+      }
+      network_info.airplane_mode = airplane_mode;
+      network_info.clone()
+    };
+    if let Some(handler) = self.airplane_mode_handler.lock().unwrap().as_ref() {
+      handler(airplane_mode);
+    }
+    emit(&self.change_handler, info);
+  }
+}
+
+#[cfg(feature = "napi")]
+/// The `StateChanged` registrations of the radios, removed on drop.
+///
+/// The radios are enumerated once, a radio plugged in afterwards, e.g. a Bluetooth dongle, is not watched.
+struct RadioWatch {
+  registrations: Vec<(Radio, i64)>,
+}
+
+#[cfg(feature = "napi")]
+impl RadioWatch {
+  /// Without radios, or when they can't be enumerated, nothing is registered.
+  fn register(watch: Arc<AirplaneModeWatch>) -> Result<Self> {
+    let mut radio_watch = Self {
+      registrations: Vec::new(),
+    };
+    for radio in radios().unwrap_or_default() {
+      let watch = watch.clone();
+      let on_state_changed = TypedEventHandler::<Radio, IInspectable>::new(move |_, _| {
+        watch.update();
+        Ok(())
+      });
+      let token = radio.StateChanged(&on_state_changed).map_err(|err| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Radio::StateChanged failed: {err}"),
+        )
+      })?;
+      radio_watch.registrations.push((radio, token));
+    }
+    Ok(radio_watch)
+  }
+}
+
+#[cfg(feature = "napi")]
+impl Drop for RadioWatch {
+  fn drop(&mut self) {
+    for (radio, token) in self.registrations.drain(..) {
+      let _ = radio.RemoveStateChanged(token);
+    }
+  }
+}
+
+fn radios() -> windows_core::Result<Vec<Radio>> {
+  Ok(Radio::GetRadiosAsync()?.get()?.into_iter().collect())
+}
+
+/// The radio manager behind the airplane mode toggle is not a public API, airplane mode turns every radio off,
+/// so it's on when there is at least one radio and none of them is on.
+///
+/// The radios can be turned on one by one afterwards, e.g. Wi-Fi on a plane, it reads as off then.
+fn airplane_mode() -> bool {
+  // e.g. the Server editions don't have the radio management service
+  let Ok(radios) = radios() else {
+    return false;
+  };
+  !radios.is_empty()
+    && radios
+      .iter()
+      .all(|radio| radio.State().is_ok_and(|state| state != RadioState::On))
+}
+
+#[cfg(feature = "napi")]
+/// The alias of an interface, e.g. `Wi-Fi`, `None` once it's removed.
+fn interface_alias(luid: &windows::Win32::NetworkManagement::Ndis::NET_LUID_LH) -> Option<String> {
+  use windows::Win32::NetworkManagement::IpHelper::ConvertInterfaceLuidToAlias;
+
+  // NDIS_IF_MAX_STRING_SIZE and the NUL terminator
+  let mut alias = [0u16; 257];
+  if unsafe { ConvertInterfaceLuidToAlias(luid, &mut alias) }.0 != 0 {
+    return None;
+  }
+  let len = alias.iter().position(|&c| c == 0).unwrap_or(alias.len());
+  Some(String::from_utf16_lossy(&alias[..len]))
+}
+
+#[cfg(feature = "napi")]
+fn interface_alias_by_index(index: u32) -> Option<String> {
+  use windows::Win32::NetworkManagement::IpHelper::ConvertInterfaceIndexToLuid;
+
+  let mut luid = Default::default();
+  if unsafe { ConvertInterfaceIndexToLuid(index, &mut luid) }.0 != 0 {
+    return None;
+  }
+  interface_alias(&luid)
+}
+
+/// The interface index of the default route of `family`, `None` without one.
+fn default_route_index(family: IpFamily) -> Option<u32> {
+  use windows::Win32::NetworkManagement::IpHelper::GetBestInterfaceEx;
+
+  // any public address is routed through the default route, nothing is sent to it
+  let destination = match family {
+    IpFamily::Ipv4 => IpAddr::from([8, 8, 8, 8]),
+    IpFamily::Ipv6 => IpAddr::from([0x2001, 0x4860, 0x4860, 0, 0, 0, 0, 0x8888]),
+  };
+  let sockaddr = destination_sockaddr(destination);
+  let mut best_index = 0;
+  // e.g. ERROR_NETWORK_UNREACHABLE, there is no default route
+  (unsafe { GetBestInterfaceEx(sockaddr.data.as_ptr().cast(), &mut best_index) } == 0)
+    .then_some(best_index)
+}
+
+thread_local! {
+  /// The buffer of `GetAdaptersAddresses`, it only grows. Every thread walking the adapters keeps its own:
+  /// the COM thread, the IP Helper notification threads and the WinRT thread pool.
+  static ADAPTERS_BUFFER: std::cell::RefCell<Vec<u64>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+fn get_available_connections<
+  F: FnMut(
     &windows::Win32::NetworkManagement::IpHelper::IP_ADAPTER_ADDRESSES_LH,
   ) -> windows_core::Result<bool>,
 >(
+  excluded_adapters: &ExcludedAdapters,
   mut callback: F,
 ) -> windows_core::Result<()> {
   use windows::Win32::NetworkManagement::IpHelper::{
@@ -547,107 +2363,1059 @@ fn get_available_connections<
   };
   use windows::Win32::Networking::WinSock::AF_UNSPEC;
 
-  unsafe {
-    let mut buffer_length = 0;
-    let code = GetAdaptersAddresses(
-      AF_UNSPEC.0 as u32,
-      GAA_FLAG_INCLUDE_ALL_INTERFACES,
-      None,
-      None,
-      &mut buffer_length,
-    );
-
-    // https://github.com/microsoft/windows-rs/issues/2832#issuecomment-1922306953
-    // ERROR_BUFFER_OVERFLOW is expected because the buffer length is initially 0
-    if code != 0x00000000 && code != ERROR_BUFFER_OVERFLOW.0 {
-      return HRESULT::from_win32(code).ok();
-    }
-
-    let mut buffer = vec![0u8; buffer_length as usize];
-    let addresses = buffer.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES_LH;
-    let code = GetAdaptersAddresses(
-      AF_UNSPEC.0 as u32,
-      GAA_FLAG_INCLUDE_ALL_INTERFACES,
-      None,
-      Some(addresses),
-      &mut buffer_length,
-    );
-    if code != 0x00000000 {
-      return HRESULT::from_win32(code).ok();
+  ADAPTERS_BUFFER.with_borrow_mut(|buffer| unsafe {
+    // the buffer of the previous walk is usually large enough, it's a single call then
+    loop {
+      // u64 elements keep the IP_ADAPTER_ADDRESSES_LH structs aligned
+      let mut buffer_length = (buffer.len() * std::mem::size_of::<u64>()) as u32;
+      let addresses =
+        (!buffer.is_empty()).then(|| buffer.as_mut_ptr().cast::<IP_ADAPTER_ADDRESSES_LH>());
+      let code = GetAdaptersAddresses(
+        AF_UNSPEC.0 as u32,
+        GAA_FLAG_INCLUDE_ALL_INTERFACES,
+        None,
+        addresses,
+        &mut buffer_length,
+      );
+      // https://github.com/microsoft/windows-rs/issues/2832#issuecomment-1922306953
+      // ERROR_BUFFER_OVERFLOW is expected the first time and when an adapter was added, try again with the new length
+      if code == ERROR_BUFFER_OVERFLOW.0 {
+        buffer.resize(
+          (buffer_length as usize).div_ceil(std::mem::size_of::<u64>()),
+          0,
+        );
+        continue;
+      }
+      // ERROR_NO_DATA without any adapter, e.g. in a VM or early during boot, it's an empty walk
+      if code == ERROR_NO_DATA.0 {
+        return Ok(());
+      }
+      if code != 0x00000000 || addresses.is_none() {
+        return HRESULT::from_win32(code).ok();
+      }
+      break;
     }
-    let mut current_addresses = addresses;
+    let mut current_addresses = buffer.as_ptr().cast::<IP_ADAPTER_ADDRESSES_LH>();
     while !current_addresses.is_null() {
       let adapter = &*current_addresses;
+      current_addresses = adapter.Next;
+      if excluded_adapters.excludes(adapter) {
+        continue;
+      }
       if !callback(adapter)? {
         return Ok(());
       }
-      current_addresses = adapter.Next;
     }
     Ok(())
-  }
+  })
 }
 
-fn has_available_connections() -> windows_core::Result<bool> {
-  let mut available = false;
-  get_available_connections(|adapter| {
-    if adapter.OperStatus == IfOperStatusUp {
-      // break the iterator
-      available = true;
-      Ok(false)
-    } else {
+/// What a snapshot needs to know about the adapters, collected in a single walk.
+#[derive(Debug, Default)]
+struct AdapterSummary {
+  /// Any adapter is listed besides the loopback pseudo-interface, whatever its status.
+  ///
+  /// There may be none in a VM or early during boot, while the network list manager still reports the last connectivity.
+  has_adapters: bool,
+  /// An adapter which can carry a connection is up, the loopback pseudo-interface and the Teredo/ISATAP tunnels
+  /// are always up and don't count.
+  has_available_connections: bool,
+  /// Any up adapter has a DNS server, the first up one may be a virtual switch without any, e.g. Hyper-V.
+  has_dns: bool,
+  /// An up adapter has an IPv4 address which can reach beyond the link, see `is_routable`.
+  has_routable_ipv4: bool,
+  /// An up adapter has an IPv6 address which can reach beyond the link, see `is_routable`.
+  has_routable_ipv6: bool,
+  /// The type and the link speed of the adapter carrying the IPv4 default route,
+  /// or the first up one without a default route. Loopback only counts when nothing else is up.
+  primary_adapter: Option<(NWInterfaceType, Option<u32>)>,
+}
+
+impl AdapterSummary {
+  fn read(excluded_adapters: &ExcludedAdapters) -> windows_core::Result<Self> {
+    let default_route_index = default_route_index(IpFamily::Ipv4);
+    let mut adapters = Vec::new();
+    get_available_connections(excluded_adapters, |adapter| {
+      adapters.push(AdapterEntry::new(adapter));
       Ok(true)
+    })?;
+    Ok(Self::from_adapters(&adapters, default_route_index))
+  }
+
+  /// Summarize the adapters in the order of `GetAdaptersAddresses`, `default_route_index` is the `IfIndex`
+  /// of the IPv4 default route.
+  fn from_adapters(adapters: &[AdapterEntry], default_route_index: Option<u32>) -> Self {
+    let mut summary = Self::default();
+    let mut is_default_route_found = false;
+    for adapter in adapters {
+      let interface_type = adapter_interface_type(adapter.if_type);
+      summary.has_adapters |= interface_type != NWInterfaceType::Loopback;
+      if !adapter.is_up {
+        continue;
+      }
+      summary.has_available_connections |= !is_virtual_adapter(adapter.if_type);
+      summary.has_dns |= interface_type != NWInterfaceType::Loopback && adapter.has_dns_server;
+      for address in &adapter.addresses {
+        if is_routable(address) {
+          summary.has_routable_ipv4 |= address.is_ipv4();
+          summary.has_routable_ipv6 |= address.is_ipv6();
+        }
+      }
+      if !is_default_route_found {
+        is_default_route_found = default_route_index == Some(adapter.if_index);
+        // keep the first real adapter until the one of the default route is found
+        if is_default_route_found
+          || summary
+            .primary_adapter
+            .is_none_or(|(primary_type, _)| primary_type == NWInterfaceType::Loopback)
+        {
+          summary.primary_adapter = Some((interface_type, adapter.link_speed_mbps));
+        }
+      }
+    }
+    summary
+  }
+}
+
+/// What `AdapterSummary` needs from an adapter of `GetAdaptersAddresses`, copied out of the buffer
+/// so the summary can be computed from synthesized adapter lists as well.
+#[derive(Debug, Clone)]
+struct AdapterEntry {
+  if_index: u32,
+  /// `IF_TYPE_SOFTWARE_LOOPBACK`, `IF_TYPE_TUNNEL`, `IF_TYPE_IEEE80211`...
+  if_type: u32,
+  /// `OperStatus` is `IfOperStatusUp`
+  is_up: bool,
+  has_dns_server: bool,
+  addresses: Vec<IpAddr>,
+  /// `None` when the driver doesn't know it
+  link_speed_mbps: Option<u32>,
+}
+
+impl AdapterEntry {
+  fn new(adapter: &windows::Win32::NetworkManagement::IpHelper::IP_ADAPTER_ADDRESSES_LH) -> Self {
+    Self {
+      if_index: unsafe { adapter.Anonymous1.Anonymous.IfIndex },
+      if_type: adapter.IfType,
+      is_up: adapter.OperStatus == IfOperStatusUp,
+      has_dns_server: !adapter.FirstDnsServerAddress.is_null(),
+      addresses: unicast_addresses(adapter),
+      // the speed is in bit/s, u64::MAX when it's unknown
+      link_speed_mbps: (adapter.TransmitLinkSpeed != u64::MAX)
+        .then(|| (adapter.TransmitLinkSpeed / 1_000_000).min(u32::MAX as u64) as u32),
+    }
+  }
+}
+
+/// Neither loopback nor link-local, i.e. APIPA `169.254.0.0/16` and `fe80::/10`.
+fn is_routable(address: &IpAddr) -> bool {
+  match address {
+    IpAddr::V4(address) => {
+      !address.is_loopback() && !address.is_link_local() && !address.is_unspecified()
+    }
+    IpAddr::V6(address) => {
+      !address.is_loopback()
+        && !address.is_unspecified()
+        && address.segments()[0] & 0xffc0 != 0xfe80
+    }
+  }
+}
+
+/// The `AdapterName` of the adapter carrying the IPv4 default route, e.g. `{4D36E972-E325-11CE-BFC1-08002BE10318}`.
+fn default_route_adapter(
+  excluded_adapters: &ExcludedAdapters,
+) -> windows_core::Result<Option<String>> {
+  let Some(best_index) = default_route_index(IpFamily::Ipv4) else {
+    return Ok(None);
+  };
+  let mut adapter_name = None;
+  get_available_connections(excluded_adapters, |adapter| {
+    if unsafe { adapter.Anonymous1.Anonymous.IfIndex } != best_index {
+      return Ok(true);
     }
+    adapter_name = unsafe { adapter.AdapterName.to_string() }.ok();
+    Ok(false)
   })?;
-  Ok(available)
+  Ok(adapter_name)
 }
 
-fn has_dns() -> windows_core::Result<bool> {
-  let mut has_dns = false;
-  get_available_connections(|adapter| {
-    if adapter.OperStatus == IfOperStatusUp {
-      // break the iterator
-      has_dns = !adapter.FirstDnsServerAddress.is_null();
-      Ok(false)
-    } else {
-      Ok(true)
+/// Fill the cost fields from the connection of the default route, they are unmetered without any connection.
+///
+/// The data plan is optional, see `reported_data_limit_mb`.
+fn read_connection_cost(
+  network_info: &mut NetworkInfo,
+  network_list_manager: &INetworkListManager,
+  excluded_adapters: &ExcludedAdapters,
+  error_handler: &Mutex<Option<ErrorHandler>>,
+) -> windows_core::Result<()> {
+  let Some((cost, data_plan)) = default_route_cost(network_list_manager, excluded_adapters)? else {
+    network_info.is_expensive = false;
+    network_info.is_low_data_mode = false;
+    network_info.is_roaming = false;
+    network_info.cost_flags = 0;
+    network_info.data_limit_mb = None;
+    return Ok(());
+  };
+  let metered_status = MeteredStatus::from_cost_flags(cost);
+  network_info.is_expensive = metered_status.is_expensive;
+  network_info.is_low_data_mode = metered_status.is_low_data_mode;
+  network_info.is_roaming = cost & NlmConnectionCost::ROAMING.bits() != 0;
+  network_info.cost_flags = cost;
+  network_info.data_limit_mb = reported_data_limit_mb(error_handler, data_plan);
+  Ok(())
+}
+
+/// Query a data plan, `GetDataPlanStatus` returns `E_NOTIMPL` on some Ethernet adapters.
+fn data_plan_status(
+  query: impl FnOnce(&mut NLM_DATAPLAN_STATUS) -> windows_core::Result<()>,
+) -> windows_core::Result<NLM_DATAPLAN_STATUS> {
+  let mut data_plan = NLM_DATAPLAN_STATUS {
+    DataLimitInMegabytes: u32::MAX,
+    ..Default::default()
+  };
+  query(&mut data_plan)?;
+  Ok(data_plan)
+}
+
+/// The data limit of a data plan query, a failed one is reported to `error_handler` and read as no limit.
+fn reported_data_limit_mb(
+  error_handler: &Mutex<Option<ErrorHandler>>,
+  data_plan: windows_core::Result<NLM_DATAPLAN_STATUS>,
+) -> Option<u32> {
+  match data_plan {
+    Ok(data_plan) => data_limit_mb(&data_plan),
+    Err(err) => {
+      let _ = report_error(error_handler, Err(err));
+      None
+    }
+  }
+}
+
+/// `DataLimitInMegabytes` is `u32::MAX` without a limit, some drivers leave the structure empty and it's 0.
+fn data_limit_mb(data_plan: &NLM_DATAPLAN_STATUS) -> Option<u32> {
+  (data_plan.DataLimitInMegabytes != u32::MAX && data_plan.DataLimitInMegabytes != 0)
+    .then_some(data_plan.DataLimitInMegabytes)
+}
+
+/// The cost and the data plan of the connection running over the adapter of the default route.
+///
+/// On multi-homed machines, e.g. Ethernet with a metered LTE failover, the enumeration order of the connections
+/// doesn't tell which one carries the traffic. When the default route adapter has no connection,
+/// e.g. a VPN without a network profile, the cheapest connected one is used. `None` without any connection.
+fn default_route_cost(
+  network_list_manager: &INetworkListManager,
+  excluded_adapters: &ExcludedAdapters,
+) -> windows_core::Result<Option<(u32, windows_core::Result<NLM_DATAPLAN_STATUS>)>> {
+  // (adapter id, cost), the connection cost interfaces are at the same index
+  let mut connections = Vec::new();
+  let mut connection_costs = Vec::new();
+  let network_connections = unsafe { network_list_manager.GetNetworkConnections()? };
+  loop {
+    let mut next_connection = [None];
+    unsafe { network_connections.Next(&mut next_connection, None)? };
+    let [Some(connection)] = next_connection else {
+      break;
+    };
+    if unsafe { connection.IsConnected()? } != true {
+      continue;
     }
+    let mut network_connection_cost: MaybeUninit<INetworkConnectionCost> = MaybeUninit::uninit();
+    unsafe {
+      connection
+        .query(
+          &INetworkConnectionCost::IID,
+          network_connection_cost.as_mut_ptr().cast(),
+        )
+        .ok()?
+    };
+    // SAFETY: network_connection_cost is initialized when query is successful
+    let network_connection_cost = unsafe { network_connection_cost.assume_init() };
+    connections.push((unsafe { connection.GetAdapterId()? }, unsafe {
+      network_connection_cost.GetCost()?
+    }));
+    connection_costs.push(network_connection_cost);
+  }
+
+  let default_route_adapter = default_route_adapter(excluded_adapters)?;
+  let Some(index) = default_route_connection(&connections, default_route_adapter.as_deref()) else {
+    return Ok(None);
+  };
+  let network_connection_cost = &connection_costs[index];
+  let data_plan =
+    data_plan_status(|data_plan| unsafe { network_connection_cost.GetDataPlanStatus(data_plan) });
+  Ok(Some((connections[index].1, data_plan)))
+}
+
+/// The index of the connection over `default_route_adapter`, the `{GUID}` adapter name,
+/// or of the cheapest one when it has no connection. `None` without any connection.
+fn default_route_connection(
+  connections: &[(windows_core::GUID, u32)],
+  default_route_adapter: Option<&str>,
+) -> Option<usize> {
+  connections
+    .iter()
+    .position(|(adapter_id, _)| {
+      default_route_adapter.is_some_and(|adapter_name| {
+        adapter_name.eq_ignore_ascii_case(&format!("{{{adapter_id:?}}}"))
+      })
+    })
+    .or_else(|| {
+      connections
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, (_, cost))| *cost)
+        .map(|(index, _)| index)
+    })
+}
+
+/// The `FriendlyName` or `Description` fragments of the host-only adapters of the hypervisors and the packet capture drivers,
+/// they are up on developer machines without carrying any traffic to the Internet.
+///
+/// The Hyper-V switches bound to a physical adapter, e.g. `vEthernet (External)`, carry the real connection and are not excluded.
+const DEFAULT_EXCLUDED_ADAPTERS: &[&str] = &[
+  "vEthernet (Default Switch)",
+  "vEthernet (WSL",
+  "VMware Virtual Ethernet Adapter",
+  "VirtualBox Host-Only",
+  "Npcap Loopback",
+];
+
+/// The adapters skipped by `get_available_connections`, matched case-insensitively as substrings
+/// of the `FriendlyName` or the `Description`.
+#[derive(Default)]
+struct ExcludedAdapters {
+  /// Lowercase
+  patterns: Vec<String>,
+}
+
+impl ExcludedAdapters {
+  /// The built-in list plus the `excludeAdapters` option.
+  fn new(exclude_adapters: Option<Vec<String>>) -> Self {
+    let patterns = DEFAULT_EXCLUDED_ADAPTERS
+      .iter()
+      .map(|pattern| pattern.to_string())
+      .chain(exclude_adapters.unwrap_or_default())
+      .filter(|pattern| !pattern.is_empty())
+      .map(|pattern| pattern.to_lowercase())
+      .collect();
+    Self { patterns }
+  }
+
+  fn excludes(
+    &self,
+    adapter: &windows::Win32::NetworkManagement::IpHelper::IP_ADAPTER_ADDRESSES_LH,
+  ) -> bool {
+    let friendly_name = unsafe { adapter.FriendlyName.to_string() }.unwrap_or_default();
+    let description = unsafe { adapter.Description.to_string() }.unwrap_or_default();
+    let friendly_name = friendly_name.to_lowercase();
+    let description = description.to_lowercase();
+    self
+      .patterns
+      .iter()
+      .any(|pattern| friendly_name.contains(pattern) || description.contains(pattern))
+  }
+}
+
+/// The loopback pseudo-interface and the tunnels, e.g. Teredo and ISATAP, they are up without any real network.
+fn is_virtual_adapter(if_type: u32) -> bool {
+  use windows::Win32::NetworkManagement::IpHelper::{IF_TYPE_SOFTWARE_LOOPBACK, IF_TYPE_TUNNEL};
+
+  matches!(if_type, IF_TYPE_SOFTWARE_LOOPBACK | IF_TYPE_TUNNEL)
+}
+
+/// The interface type of an adapter `IfType`.
+fn adapter_interface_type(if_type: u32) -> NWInterfaceType {
+  use windows::Win32::NetworkManagement::IpHelper::{
+    IF_TYPE_ETHERNET_CSMACD, IF_TYPE_IEEE80211, IF_TYPE_SOFTWARE_LOOPBACK, IF_TYPE_WWANPP,
+    IF_TYPE_WWANPP2,
+  };
+
+  match if_type {
+    IF_TYPE_IEEE80211 => NWInterfaceType::Wifi,
+    IF_TYPE_WWANPP | IF_TYPE_WWANPP2 => NWInterfaceType::Cellular,
+    IF_TYPE_ETHERNET_CSMACD => NWInterfaceType::Wired,
+    IF_TYPE_SOFTWARE_LOOPBACK => NWInterfaceType::Loopback,
+    _ => NWInterfaceType::Other,
+  }
+}
+
+#[cfg(feature = "napi")]
+/// Every adapter, with the Internet connectivity and the cost of the connection running over it.
+fn current_all(
+  network_list_manager: &INetworkListManager,
+  excluded_adapters: &ExcludedAdapters,
+) -> windows_core::Result<Vec<InterfaceNetworkInfo>> {
+  // (adapter id, reaches the Internet, metered)
+  let mut connections = Vec::new();
+  let network_connections = unsafe { network_list_manager.GetNetworkConnections()? };
+  loop {
+    let mut next_connection = [None];
+    unsafe { network_connections.Next(&mut next_connection, None)? };
+    let [Some(connection)] = next_connection else {
+      break;
+    };
+    let mut network_connection_cost: MaybeUninit<INetworkConnectionCost> = MaybeUninit::uninit();
+    unsafe {
+      connection
+        .query(
+          &INetworkConnectionCost::IID,
+          network_connection_cost.as_mut_ptr().cast(),
+        )
+        .ok()?
+    };
+    // SAFETY: network_connection_cost is initialized when query is successful
+    let cost = unsafe { network_connection_cost.assume_init().GetCost()? };
+    connections.push((
+      unsafe { connection.GetAdapterId()? },
+      unsafe { connection.IsConnectedToInternet()? } == true,
+      cost > NlmConnectionCost::UNRESTRICTED.bits(),
+    ));
+  }
+
+  let mut interfaces = Vec::new();
+  get_available_connections(excluded_adapters, |adapter| {
+    // e.g. `{4D36E972-E325-11CE-BFC1-08002BE10318}`
+    let adapter_name = unsafe { adapter.AdapterName.to_string() }.unwrap_or_default();
+    let connection = connections
+      .iter()
+      .find(|(adapter_id, ..)| adapter_name.eq_ignore_ascii_case(&format!("{{{adapter_id:?}}}")));
+    let status = if adapter.OperStatus != IfOperStatusUp {
+      NetworkStatus::Unsatisfied
+    } else if connection.is_some_and(|(_, reaches_internet, _)| *reaches_internet) {
+      NetworkStatus::Satisfied
+    } else {
+      NetworkStatus::Satisfiable
+    };
+    interfaces.push(InterfaceNetworkInfo {
+      name: unsafe { adapter.FriendlyName.to_string() }.unwrap_or(adapter_name),
+      interface_type: adapter_interface_type(adapter.IfType),
+      status,
+      addresses: unicast_addresses(adapter)
+        .iter()
+        .map(ToString::to_string)
+        .collect(),
+      is_metered: connection.is_some_and(|(_, _, is_metered)| *is_metered),
+    });
+    Ok(true)
   })?;
-  Ok(has_dns)
+  Ok(interfaces)
 }
 
-fn get_network_info(
+fn unicast_addresses(
+  adapter: &windows::Win32::NetworkManagement::IpHelper::IP_ADAPTER_ADDRESSES_LH,
+) -> Vec<IpAddr> {
+  let mut addresses = Vec::new();
+  let mut unicast_address = adapter.FirstUnicastAddress;
+  while !unicast_address.is_null() {
+    // SAFETY: the list lives as long as the buffer filled by GetAdaptersAddresses
+    let address = unsafe { &*unicast_address };
+    addresses.extend(socket_address(&address.Address));
+    unicast_address = address.Next;
+  }
+  addresses
+}
+
+#[cfg(feature = "napi")]
+/// The DNS servers configured on the adapter, in the order the resolver tries them.
+fn dns_server_addresses(
+  adapter: &windows::Win32::NetworkManagement::IpHelper::IP_ADAPTER_ADDRESSES_LH,
+) -> Vec<IpAddr> {
+  let mut addresses = Vec::new();
+  let mut dns_server_address = adapter.FirstDnsServerAddress;
+  while !dns_server_address.is_null() {
+    // SAFETY: the list lives as long as the buffer filled by GetAdaptersAddresses
+    let address = unsafe { &*dns_server_address };
+    addresses.extend(socket_address(&address.Address));
+    dns_server_address = address.Next;
+  }
+  addresses
+}
+
+/// The IPv4 or IPv6 address of a `SOCKET_ADDRESS` of the adapter lists, `None` for the other families.
+fn socket_address(address: &windows::Win32::Networking::WinSock::SOCKET_ADDRESS) -> Option<IpAddr> {
+  use windows::Win32::Networking::WinSock::{AF_INET, AF_INET6, SOCKADDR_IN, SOCKADDR_IN6};
+
+  let sockaddr = address.lpSockaddr;
+  if sockaddr.is_null() {
+    return None;
+  }
+  match unsafe { (*sockaddr).sa_family } {
+    AF_INET => {
+      let sockaddr = unsafe { &*sockaddr.cast::<SOCKADDR_IN>() };
+      let address = u32::from_be(unsafe { sockaddr.sin_addr.S_un.S_addr });
+      Some(Ipv4Addr::from(address).into())
+    }
+    AF_INET6 => {
+      let sockaddr = unsafe { &*sockaddr.cast::<SOCKADDR_IN6>() };
+      Some(Ipv6Addr::from(unsafe { sockaddr.sin6_addr.u.Byte }).into())
+    }
+    _ => None,
+  }
+}
+
+/// The `NLM_SOCKADDR` of a destination, a `SOCKADDR_IN` or a `SOCKADDR_IN6` without a port at the start of the buffer.
+fn destination_sockaddr(destination: IpAddr) -> NLM_SOCKADDR {
+  use windows::Win32::Networking::WinSock::{
+    AF_INET, AF_INET6, IN6_ADDR, IN6_ADDR_0, IN_ADDR, IN_ADDR_0, SOCKADDR_IN, SOCKADDR_IN6,
+  };
+
+  let mut sockaddr = NLM_SOCKADDR { data: [0; 128] };
+  let buffer = sockaddr.data.as_mut_ptr();
+  // SAFETY: both structs are smaller than the 128 bytes of the buffer, which has no alignment
+  match destination {
+    IpAddr::V4(address) => unsafe {
+      buffer.cast::<SOCKADDR_IN>().write_unaligned(SOCKADDR_IN {
+        sin_family: AF_INET,
+        sin_addr: IN_ADDR {
+          S_un: IN_ADDR_0 {
+            S_addr: u32::from(address).to_be(),
+          },
+        },
+        ..Default::default()
+      })
+    },
+    IpAddr::V6(address) => unsafe {
+      buffer.cast::<SOCKADDR_IN6>().write_unaligned(SOCKADDR_IN6 {
+        sin6_family: AF_INET6,
+        sin6_addr: IN6_ADDR {
+          u: IN6_ADDR_0 {
+            Byte: address.octets(),
+          },
+        },
+        ..Default::default()
+      })
+    },
+  }
+  sockaddr
+}
+
+impl From<NLM_NETWORK_CATEGORY> for NetworkCategory {
+  fn from(category: NLM_NETWORK_CATEGORY) -> Self {
+    match category {
+      NLM_NETWORK_CATEGORY_PRIVATE => NetworkCategory::Private,
+      NLM_NETWORK_CATEGORY_DOMAIN_AUTHENTICATED => NetworkCategory::DomainAuthenticated,
+      // Windows Firewall treats everything it doesn't trust as public
+      _ => NetworkCategory::Public,
+    }
+  }
+}
+
+/// The category of the first connected network.
+fn network_category(
+  network_list_manager: &INetworkListManager,
+) -> windows_core::Result<Option<NetworkCategory>> {
+  let networks = unsafe { network_list_manager.GetNetworks(NLM_ENUM_NETWORK_CONNECTED)? };
+  let mut connected_networks = [None];
+  unsafe { networks.Next(&mut connected_networks, None)? };
+  let Some(Some(network)) = connected_networks.first() else {
+    return Ok(None);
+  };
+  Ok(Some(unsafe { network.GetCategory()? }.into()))
+}
+
+#[cfg(feature = "napi")]
+fn list_networks(
+  network_list_manager: &INetworkListManager,
+) -> windows_core::Result<Vec<KnownNetwork>> {
+  let networks = unsafe { network_list_manager.GetNetworks(NLM_ENUM_NETWORK_ALL)? };
+  let mut known_networks = Vec::new();
+  loop {
+    let mut next_network = [None];
+    // S_FALSE with nothing fetched once the enumeration is over
+    unsafe { networks.Next(&mut next_network, None)? };
+    let [Some(network)] = next_network else {
+      break;
+    };
+    let is_connected = unsafe { network.IsConnected()? } == true;
+    let connectivity = if unsafe { network.IsConnectedToInternet()? } == true {
+      NetworkStatus::Satisfied
+    } else if is_connected {
+      NetworkStatus::Satisfiable
+    } else {
+      NetworkStatus::Unsatisfied
+    };
+    known_networks.push(KnownNetwork {
+      name: unsafe { network.GetName()? }.to_string(),
+      is_connected,
+      connectivity,
+      category: unsafe { network.GetCategory()? }.into(),
+    });
+  }
+  Ok(known_networks)
+}
+
+#[cfg(feature = "napi")]
+fn get_networks(
+  network_list_manager: &INetworkListManager,
+  filter: NetworkListFilter,
+) -> windows_core::Result<Vec<NetworkListEntry>> {
+  let networks = unsafe { network_list_manager.GetNetworks(filter.into())? };
+  let mut entries = Vec::new();
+  loop {
+    let mut next_network = [None];
+    // S_FALSE with nothing fetched once the enumeration is over
+    unsafe { networks.Next(&mut next_network, None)? };
+    // the network is released at the end of the iteration
+    let [Some(network)] = next_network else {
+      break;
+    };
+    let network_id = unsafe { network.GetNetworkId()? };
+    entries.push(NetworkListEntry {
+      name: unsafe { network.GetName()? }.to_string(),
+      description: unsafe { network.GetDescription()? }.to_string(),
+      network_id: format!("{{{network_id:?}}}"),
+      category: unsafe { network.GetCategory()? }.into(),
+      connectivity: unsafe { network.GetConnectivity()? }.0 as u32,
+      is_connected_to_internet: unsafe { network.IsConnectedToInternet()? } == true,
+    });
+  }
+  Ok(entries)
+}
+
+/// Re-evaluate the connectivity related fields and replace the snapshot in one go.
+fn update_network_info(
   connectivity: NLM_CONNECTIVITY,
-  is_expensive: &Arc<AtomicBool>,
-  is_low_data_mode: &Arc<AtomicBool>,
-  network_status: &Arc<AtomicU8>,
-  network_list_manager: &Rc<INetworkListManager>,
+  network_info: &Mutex<NetworkInfo>,
+  network_list_manager: &INetworkListManager,
+  excluded_adapters: &ExcludedAdapters,
 ) -> windows_core::Result<NetworkInfo> {
-  let ipv4_internet =
-    connectivity.0 & NLM_CONNECTIVITY_IPV4_INTERNET.0 == NLM_CONNECTIVITY_IPV4_INTERNET.0;
-  let ipv4_no_traffic =
-    connectivity.0 & NLM_CONNECTIVITY_IPV4_NOTRAFFIC.0 == NLM_CONNECTIVITY_IPV4_NOTRAFFIC.0;
-  let ipv6_internet =
-    connectivity.0 & NLM_CONNECTIVITY_IPV6_INTERNET.0 == NLM_CONNECTIVITY_IPV6_INTERNET.0;
-  let ipv6_no_traffic =
-    connectivity.0 & NLM_CONNECTIVITY_IPV6_NOTRAFFIC.0 == NLM_CONNECTIVITY_IPV6_NOTRAFFIC.0;
-  let is_connected_to_internet = unsafe { network_list_manager.IsConnectedToInternet()? };
-  let is_connected = unsafe { network_list_manager.IsConnected()? };
-  let status = if is_connected_to_internet == true {
+  // the adapters walk is slow, don't hold the lock while doing it
+  let current = network_info.lock().unwrap().clone();
+  let next = get_network_info(
+    connectivity,
+    &current,
+    network_list_manager,
+    &AdapterSummary::read(excluded_adapters)?,
+  )?;
+  let mut network_info = network_info.lock().unwrap();
+  // cost events may have landed in between, keep their fields
+  *network_info = NetworkInfo {
+    is_expensive: network_info.is_expensive,
+    is_low_data_mode: network_info.is_low_data_mode,
+    is_roaming: network_info.is_roaming,
+    cost_flags: network_info.cost_flags,
+    data_limit_mb: network_info.data_limit_mb,
+    ..next
+  };
+  Ok(network_info.clone())
+}
+
+/// `hasIpv4` or `hasIpv6`: the `*_INTERNET` bit of the family in `connectivity_flags` and a routable address,
+/// the network list manager may report Internet while the adapters only have link-local addresses.
+fn reaches_internet(
+  connectivity_flags: u32,
+  internet: NLM_CONNECTIVITY,
+  has_routable_address: bool,
+) -> bool {
+  connectivity_flags & internet.0 as u32 != 0 && has_routable_address
+}
+
+/// `ipv4Connectivity` or `ipv6Connectivity`, gated like `reaches_internet`:
+/// `Internet` without a routable address of the family is only `LocalOnly`.
+fn connectivity_level(
+  connectivity_flags: u32,
+  ipv6: bool,
+  has_routable_address: bool,
+) -> ConnectivityLevel {
+  match ConnectivityLevel::from_connectivity_flags(connectivity_flags, ipv6) {
+    ConnectivityLevel::Internet if !has_routable_address => ConnectivityLevel::LocalOnly,
+    level => level,
+  }
+}
+
+/// The status of a snapshot, `no_traffic` is set when either IP family has the `NOTRAFFIC` connectivity.
+///
+/// Without any adapter the network list manager may still report the last connectivity, it's `Invalid` then.
+fn network_status(
+  adapters: &AdapterSummary,
+  is_connected_to_internet: bool,
+  is_connected: bool,
+  no_traffic: bool,
+) -> NetworkStatus {
+  if !adapters.has_adapters {
+    NetworkStatus::Invalid
+  } else if is_connected_to_internet {
     NetworkStatus::Satisfied
-  } else if is_connected == true && (ipv4_no_traffic || ipv6_no_traffic) {
+  } else if is_connected && no_traffic {
     NetworkStatus::Unsatisfied
-  } else if has_available_connections()? {
+  } else if adapters.has_available_connections {
     NetworkStatus::Satisfiable
   } else {
     NetworkStatus::Invalid
-  };
-  network_status.store(status as u8, Ordering::SeqCst);
+  }
+}
+
+fn get_network_info(
+  connectivity: NLM_CONNECTIVITY,
+  current: &NetworkInfo,
+  network_list_manager: &INetworkListManager,
+  adapters: &AdapterSummary,
+) -> windows_core::Result<NetworkInfo> {
+  let ipv4_internet = reaches_internet(
+    connectivity.0 as u32,
+    NLM_CONNECTIVITY_IPV4_INTERNET,
+    adapters.has_routable_ipv4,
+  );
+  let ipv4_no_traffic =
+    connectivity.0 & NLM_CONNECTIVITY_IPV4_NOTRAFFIC.0 == NLM_CONNECTIVITY_IPV4_NOTRAFFIC.0;
+  let ipv6_internet = reaches_internet(
+    connectivity.0 as u32,
+    NLM_CONNECTIVITY_IPV6_INTERNET,
+    adapters.has_routable_ipv6,
+  );
+  let ipv6_no_traffic =
+    connectivity.0 & NLM_CONNECTIVITY_IPV6_NOTRAFFIC.0 == NLM_CONNECTIVITY_IPV6_NOTRAFFIC.0;
+  let status = network_status(
+    adapters,
+    unsafe { network_list_manager.IsConnectedToInternet()? } == true,
+    unsafe { network_list_manager.IsConnected()? } == true,
+    ipv4_no_traffic || ipv6_no_traffic,
+  );
+  let primary_adapter = adapters.primary_adapter;
+  let interface_type = primary_adapter.map(|(interface_type, _)| interface_type);
   Ok(NetworkInfo {
     has_ipv4: ipv4_internet,
     has_ipv6: ipv6_internet,
-    has_dns: has_dns()?,
-    is_low_data_mode: is_low_data_mode.load(Ordering::SeqCst),
-    is_expensive: is_expensive.load(Ordering::SeqCst),
+    has_dns: adapters.has_dns,
+    is_low_data_mode: current.is_low_data_mode,
+    is_expensive: current.is_expensive,
+    is_roaming: current.is_roaming,
     status,
+    stack: IpStackType::new(ipv4_internet, ipv6_internet),
+    interface_type,
+    network_category: network_category(network_list_manager)?,
+    link_speed_mbps: primary_adapter.and_then(|(_, link_speed_mbps)| link_speed_mbps),
+    uses_wifi: interface_type == Some(NWInterfaceType::Wifi),
+    uses_cellular: interface_type == Some(NWInterfaceType::Cellular),
+    uses_wired: interface_type == Some(NWInterfaceType::Wired),
+    uses_loopback: interface_type == Some(NWInterfaceType::Loopback),
+    connectivity_flags: connectivity.0 as u32,
+    ipv4_connectivity: connectivity_level(connectivity.0 as u32, false, adapters.has_routable_ipv4),
+    ipv6_connectivity: connectivity_level(connectivity.0 as u32, true, adapters.has_routable_ipv6),
+    cost_flags: current.cost_flags,
+    data_limit_mb: current.data_limit_mb,
+    airplane_mode: current.airplane_mode,
+    ..Default::default()
   })
 }
+
+/// The snapshot of the WinRT backend, the status and the cost come from the Internet connection profile,
+/// the other fields from the network list manager and the adapters like the COM backend.
+fn winrt_network_info(
+  current: &NetworkInfo,
+  network_list_manager: &INetworkListManager,
+  excluded_adapters: &ExcludedAdapters,
+) -> windows_core::Result<NetworkInfo> {
+  let connectivity = unsafe { network_list_manager.GetConnectivity()? };
+  let adapters = AdapterSummary::read(excluded_adapters)?;
+  let mut info = get_network_info(connectivity, current, network_list_manager, &adapters)?;
+  // without any adapter a leftover connection profile can't be trusted either
+  if !adapters.has_adapters {
+    return Ok(info);
+  }
+  // there is no Internet connection profile while the machine is offline
+  let Ok(profile) = NetworkInformation::GetInternetConnectionProfile() else {
+    info.status = if adapters.has_available_connections {
+      NetworkStatus::Satisfiable
+    } else {
+      NetworkStatus::Invalid
+    };
+    return Ok(info);
+  };
+  info.status = match profile.GetNetworkConnectivityLevel()? {
+    NetworkConnectivityLevel::InternetAccess => NetworkStatus::Satisfied,
+    // e.g. a captive portal, the same as the `NOTRAFFIC` connectivity of the network list manager
+    NetworkConnectivityLevel::ConstrainedInternetAccess => NetworkStatus::Unsatisfied,
+    _ => NetworkStatus::Satisfiable,
+  };
+  let cost = profile.GetConnectionCost()?;
+  let cost_type = cost.NetworkCostType()?;
+  info.is_roaming = cost.Roaming()?;
+  // the same bits as `NLM_CONNECTION_COST`, rebuilt from the connection cost of the profile
+  let mut cost_flags = match cost_type {
+    NetworkCostType::Unrestricted => NlmConnectionCost::UNRESTRICTED,
+    NetworkCostType::Fixed => NlmConnectionCost::FIXED,
+    NetworkCostType::Variable => NlmConnectionCost::VARIABLE,
+    _ => NlmConnectionCost::UNKNOWN,
+  };
+  cost_flags.set(NlmConnectionCost::ROAMING, info.is_roaming);
+  cost_flags.set(NlmConnectionCost::OVERDATALIMIT, cost.OverDataLimit()?);
+  cost_flags.set(
+    NlmConnectionCost::APPROACHINGDATALIMIT,
+    cost.ApproachingDataLimit()?,
+  );
+  info.cost_flags = cost_flags.bits();
+  let metered_status = MeteredStatus::from_cost_flags(info.cost_flags);
+  info.is_expensive = metered_status.is_expensive;
+  info.is_low_data_mode = metered_status.is_low_data_mode;
+  // the data limit is null when the connection has no data plan, and the query fails on some Ethernet adapters
+  info.data_limit_mb = profile
+    .GetDataPlanStatus()
+    .and_then(|data_plan| data_plan.DataLimitInMegabytes())
+    .and_then(|data_limit| data_limit.Value())
+    .ok();
+  Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+  use windows::Win32::NetworkManagement::IpHelper::{
+    IF_TYPE_ETHERNET_CSMACD, IF_TYPE_IEEE80211, IF_TYPE_SOFTWARE_LOOPBACK, IF_TYPE_TUNNEL,
+  };
+
+  use super::*;
+
+  /// An up adapter with the given addresses and no DNS server.
+  fn adapter(if_index: u32, if_type: u32, addresses: &[&str]) -> AdapterEntry {
+    AdapterEntry {
+      if_index,
+      if_type,
+      is_up: true,
+      has_dns_server: false,
+      addresses: addresses
+        .iter()
+        .map(|address| address.parse().unwrap())
+        .collect(),
+      link_speed_mbps: None,
+    }
+  }
+
+  fn loopback() -> AdapterEntry {
+    adapter(1, IF_TYPE_SOFTWARE_LOOPBACK, &["127.0.0.1", "::1"])
+  }
+
+  fn teredo() -> AdapterEntry {
+    adapter(2, IF_TYPE_TUNNEL, &["2001:0:2851:782c::1"])
+  }
+
+  #[test]
+  fn adapters_without_a_real_one_are_invalid() {
+    // (case, adapters, IsConnectedToInternet, IsConnected)
+    let cases = [
+      // the network list manager may still report the last connectivity
+      ("no adapter", vec![], true, true),
+      ("loopback only", vec![loopback()], true, true),
+      ("tunnel only", vec![loopback(), teredo()], false, false),
+    ];
+    for (case, adapters, is_connected_to_internet, is_connected) in cases {
+      let summary = AdapterSummary::from_adapters(&adapters, None);
+      assert_eq!(
+        network_status(&summary, is_connected_to_internet, is_connected, false),
+        NetworkStatus::Invalid,
+        "{case}"
+      );
+    }
+  }
+
+  #[test]
+  fn a_real_adapter_follows_the_network_list_manager() {
+    let adapters = [
+      loopback(),
+      adapter(3, IF_TYPE_ETHERNET_CSMACD, &["192.168.1.2"]),
+    ];
+    let summary = AdapterSummary::from_adapters(&adapters, Some(3));
+    // (IsConnectedToInternet, IsConnected, NOTRAFFIC, status)
+    let cases = [
+      (true, true, false, NetworkStatus::Satisfied),
+      (false, true, true, NetworkStatus::Unsatisfied),
+      (false, false, false, NetworkStatus::Satisfiable),
+    ];
+    for (is_connected_to_internet, is_connected, no_traffic, status) in cases {
+      assert_eq!(
+        network_status(&summary, is_connected_to_internet, is_connected, no_traffic),
+        status
+      );
+    }
+  }
+
+  #[test]
+  fn always_up_adapters_are_not_available_connections() {
+    // ISATAP has the IPv4 address of the host in its interface identifier
+    let isatap = adapter(6, IF_TYPE_TUNNEL, &["fe80::5efe:c0a8:102"]);
+    let cases = [
+      ("loopback only", vec![loopback()]),
+      ("Teredo only", vec![loopback(), teredo()]),
+      ("ISATAP only", vec![loopback(), isatap.clone()]),
+    ];
+    for (case, adapters) in cases {
+      let summary = AdapterSummary::from_adapters(&adapters, None);
+      assert!(!summary.has_available_connections, "{case}");
+      assert_eq!(
+        network_status(&summary, false, false, false),
+        NetworkStatus::Invalid,
+        "{case}"
+      );
+    }
+
+    let nic = adapter(3, IF_TYPE_ETHERNET_CSMACD, &["192.168.1.2"]);
+    let summary = AdapterSummary::from_adapters(&[loopback(), teredo(), isatap, nic], None);
+    assert!(summary.has_available_connections);
+    assert_eq!(
+      network_status(&summary, false, false, false),
+      NetworkStatus::Satisfiable
+    );
+  }
+
+  #[test]
+  fn has_dns_looks_past_a_virtual_switch_without_a_dns_server() {
+    // the Hyper-V internal switch is listed before the Wi-Fi adapter which has the DNS servers
+    let hyper_v_switch = adapter(4, IF_TYPE_ETHERNET_CSMACD, &["172.17.80.1"]);
+    let wifi = AdapterEntry {
+      has_dns_server: true,
+      ..adapter(5, IF_TYPE_IEEE80211, &["192.168.1.20"])
+    };
+    let summary =
+      AdapterSummary::from_adapters(&[loopback(), hyper_v_switch.clone(), wifi], Some(5));
+    assert!(summary.has_dns);
+    assert_eq!(summary.primary_adapter, Some((NWInterfaceType::Wifi, None)));
+
+    let summary = AdapterSummary::from_adapters(&[loopback(), hyper_v_switch], Some(4));
+    assert!(!summary.has_dns);
+    // a DNS server on the loopback pseudo-interface, e.g. a local resolver, doesn't count
+    let loopback = AdapterEntry {
+      has_dns_server: true,
+      ..loopback()
+    };
+    assert!(!AdapterSummary::from_adapters(&[loopback], None).has_dns);
+  }
+
+  #[test]
+  fn has_ipv4_needs_the_internet_bit_and_a_routable_address() {
+    let ipv4 = NLM_CONNECTIVITY_IPV4_INTERNET;
+    let lan = NLM_CONNECTIVITY_IPV4_LOCALNETWORK.0 as u32;
+    let internet = ipv4.0 as u32;
+    // an address event on a LAN without Internet must not set it, the connectivity events wouldn't
+    assert!(!reaches_internet(lan, ipv4, true));
+    // e.g. only an APIPA address while the connectivity is still the last one
+    assert!(!reaches_internet(internet, ipv4, false));
+    assert!(reaches_internet(internet, ipv4, true));
+  }
+
+  #[test]
+  fn internet_connectivity_needs_a_routable_address() {
+    let internet = NLM_CONNECTIVITY_IPV4_INTERNET.0 as u32;
+    let lan = NLM_CONNECTIVITY_IPV4_LOCALNETWORK.0 as u32;
+    assert_eq!(
+      connectivity_level(internet, false, true),
+      ConnectivityLevel::Internet
+    );
+    // only an APIPA address, consistent with `hasIpv4`
+    assert_eq!(
+      connectivity_level(internet, false, false),
+      ConnectivityLevel::LocalOnly
+    );
+    assert_eq!(
+      connectivity_level(lan, false, false),
+      ConnectivityLevel::LocalOnly
+    );
+    assert_eq!(connectivity_level(0, false, true), ConnectivityLevel::None);
+  }
+
+  const ETHERNET: windows_core::GUID =
+    windows_core::GUID::from_u128(0x4d36e972_e325_11ce_bfc1_08002be10318);
+  const LTE: windows_core::GUID =
+    windows_core::GUID::from_u128(0x9f2a6c1e_3b7d_4e52_a1c4_5d8e0f6b7a29);
+
+  #[test]
+  fn the_default_route_picks_the_connection_on_a_multi_homed_machine() {
+    let unrestricted = NLM_CONNECTION_COST_UNRESTRICTED.0 as u32;
+    let fixed = NLM_CONNECTION_COST_FIXED.0 as u32;
+    // Ethernet enumerated first, the traffic fails over to the metered LTE
+    let connections = [(ETHERNET, unrestricted), (LTE, fixed)];
+    assert_eq!(
+      default_route_connection(&connections, Some("{9F2A6C1E-3B7D-4E52-A1C4-5D8E0F6B7A29}")),
+      Some(1)
+    );
+    // the adapter names are not always uppercase
+    assert_eq!(
+      default_route_connection(&connections, Some("{9f2a6c1e-3b7d-4e52-a1c4-5d8e0f6b7a29}")),
+      Some(1)
+    );
+    // without a default route, e.g. while the route table is being updated
+    let connections = [(LTE, fixed), (ETHERNET, unrestricted)];
+    assert_eq!(default_route_connection(&connections, None), Some(1));
+    // a VPN without a network profile owns the default route
+    assert_eq!(
+      default_route_connection(&connections, Some("{00000000-0000-0000-0000-000000000001}")),
+      Some(1)
+    );
+    assert_eq!(
+      default_route_connection(&[], Some("{4D36E972-E325-11CE-BFC1-08002BE10318}")),
+      None
+    );
+  }
+
+  #[test]
+  fn a_failed_cost_advise_unadvises_the_network_events_sink() {
+    let mut network_events_cookie = 0;
+    let mut cost_events_cookie = 0;
+    let mut unadvised = None;
+    let result = advise_pair(
+      &mut network_events_cookie,
+      &mut cost_events_cookie,
+      || Ok(7),
+      || Err(System::Ole::CONNECT_E_ADVISELIMIT.into()),
+      |cookie| {
+        unadvised = Some(cookie);
+        Ok(())
+      },
+    );
+    assert!(result.is_err());
+    assert_eq!(unadvised, Some(7));
+    assert_eq!((network_events_cookie, cost_events_cookie), (0, 0));
+  }
+
+  #[test]
+  fn a_gone_sink_is_unadvised() {
+    let mut cookie = 7;
+    // e.g. the network list service restarted
+    unadvise_with(&mut cookie, "INetworkEvents", |_| {
+      Err(System::Ole::CONNECT_E_NOCONNECTION.into())
+    })
+    .unwrap();
+    assert_eq!(cookie, 0);
+    // a zero cookie is not advised, there is nothing to call
+    unadvise_with(&mut cookie, "INetworkEvents", |_| unreachable!()).unwrap();
+  }
+
+  #[test]
+  fn a_single_threaded_apartment_is_left_to_the_host() {
+    std::thread::spawn(|| {
+      // the way the Electron main process initializes its thread
+      unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) }
+        .ok()
+        .unwrap();
+      let apartment = ComApartment::initialize().unwrap();
+      assert!(!apartment.initialized);
+      // nothing to balance, the host's initialization is still the only one
+      drop(apartment);
+      unsafe { CoUninitialize() };
+    })
+    .join()
+    .unwrap();
+  }
+  #[test]
+  #[ignore = "a benchmark, run it with `cargo test --release -- --ignored --nocapture`"]
+  fn bench_adapter_summary_against_a_walk_per_field() {
+    use std::time::Instant;
+
+    const ROUNDS: u32 = 1_000;
+    let excluded_adapters = ExcludedAdapters::new(None);
+    // the previous snapshot walked the adapters for the routable IPv4 and IPv6 addresses, the adapter presence,
+    // the available connections, the primary adapter and the DNS, each walk starting from an empty buffer
+    let started = Instant::now();
+    for _ in 0..ROUNDS {
+      for _ in 0..6 {
+        ADAPTERS_BUFFER.take();
+        get_available_connections(&excluded_adapters, |_| Ok(true)).unwrap();
+      }
+    }
+    let walk_per_field = started.elapsed() / ROUNDS;
+    let started = Instant::now();
+    for _ in 0..ROUNDS {
+      AdapterSummary::read(&excluded_adapters).unwrap();
+    }
+    let summary = started.elapsed() / ROUNDS;
+    println!("a walk per field: {walk_per_field:?}, AdapterSummary::read: {summary:?}");
+  }
+}